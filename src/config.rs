@@ -0,0 +1,120 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! The optional configuration file (in TOML format),
+//! for settings that do not fit on the command-line.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::custom_keys;
+use crate::normalize;
+use crate::sources::plugin;
+
+/// The name of the configuration file we look for in the project root,
+/// if none is given explicitly.
+pub const DEFAULT_FILE: &str = ".projvar.toml";
+
+/// This enumerates all possible errors returned by this module.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// Represents all other cases of `std::io::Error`.
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+
+    #[error("Failed to parse the configuration file: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error(transparent)]
+    CustomKey(#[from] custom_keys::Error),
+
+    #[error(transparent)]
+    Plugin(#[from] plugin::Error),
+
+    #[error(transparent)]
+    Normalize(#[from] normalize::Error),
+}
+
+/// The contents of the configuration file.
+///
+/// for example:
+///
+/// ```toml
+/// [[key]]
+/// name = "FirmwareTarget"
+/// description = "The micro-controller the firmware is built for"
+/// required = true
+/// validator = { regex = "^(stm32|esp32)" }
+/// sources = [
+///     { var = "FW_TARGET" },
+///     { file = "firmware/Makefile", regex = "TARGET\\s*=\\s*(?P<value>\\S+)" },
+///     { value = "stm32f4" },
+/// ]
+///
+/// [[key]]
+/// name = "FirmwareFileName"
+/// description = "The file name of the firmware binary"
+/// validator = { regex = "^[0-9a-zA-Z_.-]+$" }
+/// sources = [
+///     { template = "{NameMachineReadable}-{FirmwareTarget}-{Version}.bin" },
+/// ]
+///
+/// [[plugin]]
+/// name = "artifacts-db"
+/// command = ["scripts/projvar-artifacts.sh", "--quick"]
+/// hierarchy = "High"
+/// timeout = 5
+/// keys = ["BuildNumber", "FirmwareTarget"]
+/// inputs = ["CI", "BUILD_ID"]
+///
+/// [normalize.BuildArch]
+/// ppc64el = "powerpc64"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Keys in addition to the built-in ones
+    #[serde(default, rename = "key")]
+    pub keys: Vec<custom_keys::Definition>,
+    /// External executables to supply values,
+    /// see [`crate::sources::plugin`].
+    /// These are only run if the user explicitly allows it
+    /// on the command-line, as they come with the project.
+    #[serde(default, rename = "plugin")]
+    pub plugins: Vec<plugin::Definition>,
+    /// Mappings from vendor specific values to canonical ones, by key,
+    /// see [`crate::normalize`]
+    #[serde(default)]
+    pub normalize: HashMap<String, HashMap<String, String>>,
+}
+
+impl Config {
+    /// Reads the configuration from a TOML file.
+    ///
+    /// # Errors
+    ///
+    /// If the file could not be read,
+    /// or its content is not a valid configuration.
+    pub fn load(file: &Path) -> Result<Config, Error> {
+        let content = fs::read_to_string(file)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    /// Makes the settings in this configuration take effect
+    /// for the rest of the process.
+    ///
+    /// # Errors
+    ///
+    /// If any of the custom keys, plugin definitions or value mappings is invalid.
+    pub fn apply(&self) -> Result<(), Error> {
+        custom_keys::register(&self.keys)?;
+        plugin::register(&self.plugins)?;
+        normalize::register(&self.normalize)?;
+        Ok(())
+    }
+}
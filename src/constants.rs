@@ -7,9 +7,13 @@ pub const DEFAULT_KEY_PREFIX: &str = "PROJECT_";
 pub const D_GIT_HUB_COM: &str = "github.com";
 pub const D_GIT_HUB_COM_RAW: &str = "raw.githubusercontent.com";
 pub const DS_GIT_HUB_IO_SUFIX: &str = "github.io";
+pub const D_GIT_HUB_CONTAINER_REGISTRY: &str = "ghcr.io";
 
 pub const D_GIT_LAB_COM: &str = "gitlab.com";
 pub const DS_GIT_LAB_IO_SUFIX: &str = "gitlab.io";
+/// GitLab (the company) hosts its container registry under "registry.gitlab.com",
+/// and this is the convention for self-hosted instances as well.
+pub const DP_GIT_LAB_CONTAINER_REGISTRY: &str = "registry.";
 
 pub const D_BIT_BUCKET_ORG: &str = "bitbucket.org";
 
@@ -18,8 +22,22 @@ pub const D_GIT_SOURCE_HUT: &str = "git.sr.ht";
 pub const D_REPO_OR_CZ: &str = "repo.or.cz";
 
 pub const D_CODE_BERG_ORG: &str = "codeberg.org";
+pub const DS_CODE_BERG_PAGE_SUFIX: &str = "codeberg.page";
 
 pub const D_SOURCE_FORGE_NET: &str = "sourceforge.net";
+pub const D_SOURCE_FORGE_GIT: &str = "git.code.sf.net";
+pub const DS_SOURCE_FORGE_IO_SUFIX: &str = "sourceforge.io";
 
+pub const D_DEV_AZURE_COM: &str = "dev.azure.com";
+pub const D_DEV_AZURE_COM_SSH: &str = "ssh.dev.azure.com";
+
+pub const DP_AWS_CODE_COMMIT_GIT: &str = "git-codecommit.";
+pub const DS_AWS_CODE_COMMIT_GIT_SUFIX: &str = ".amazonaws.com";
+pub const DS_AWS_CONSOLE_SUFIX: &str = ".console.aws.amazon.com";
+
+/// Canonical values for [`crate::var::Key::BuildOsFamily`],
+/// see [`crate::normalize`].
 pub const VALID_OS_FAMILIES: &[&str] = &["linux", "unix", "bsd", "osx", "windows"]; // TODO
-pub const VALID_ARCHS: &[&str] = &["x86", "x86_64", "arm", "arm64"]; // TODO
+/// Canonical values for [`crate::var::Key::BuildArch`],
+/// see [`crate::normalize`].
+pub const VALID_ARCHS: &[&str] = &["x86", "x86_64", "arm", "arm64", "powerpc64", "s390x"]; // TODO
@@ -0,0 +1,443 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Keys defined by the user in the configuration file
+//! (see [`crate::config`]), in addition to the built-in ones
+//! (see [`crate::var::Key`]).
+//! Once registered, they flow through the sources, the storage,
+//! the selector and the sinks, just like the built-in keys.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::environment::Environment;
+use crate::var::{self, Confidence, Key, Variable};
+
+/// Identifies a custom key.
+/// It is the index of the key in the registry.
+pub type Id = u8;
+
+/// A custom key, as declared in the configuration file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Definition {
+    /// The name of the key in "CamelCase", for example "FirmwareTarget".
+    /// The variable key is derived from it,
+    /// for example "PROJECT_FIRMWARE_TARGET".
+    pub name: String,
+    pub description: String,
+    /// Whether a value for this key is required by default.
+    #[serde(default)]
+    pub required: bool,
+    /// How to check a value of this key for validity.
+    #[serde(default)]
+    pub validator: Option<CheckDefinition>,
+    /// Where to get the value from.
+    /// These are tried in order; the first one to supply a value wins.
+    #[serde(default)]
+    pub sources: Vec<SourceDefinition>,
+}
+
+/// How to check a value of a custom key for validity.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum CheckDefinition {
+    /// One of the built-in checks, for example `validator = "url"`
+    Builtin(Builtin),
+    /// The value has to match a regex, for example `validator = { regex = "^v[0-9]+$" }`
+    Regex { regex: String },
+}
+
+/// Built-in checks for the values of custom keys.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Builtin {
+    /// A boolean: "true" or "false"
+    Bool,
+    /// A date in RFC 3339 format, or in the format given with `--date-format`
+    Date,
+    /// A whole number
+    Integer,
+    /// A semantic version, like "1.2.3" or "1.2.3-rc.1"
+    Semver,
+    /// An HTTP(S) URL
+    Url,
+}
+
+/// Where to get the value of a custom key from.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SourceDefinition {
+    /// Extracts the value from a file (relative to the project root)
+    /// through a regex, using the capture group named "value",
+    /// or the first one, or the whole match.
+    File { file: PathBuf, regex: String },
+    /// Fills in the values of other keys into a template,
+    /// for example "{NameMachineReadable}-{Version}".
+    Template { template: String },
+    /// Uses the value of an input variable, for example "FW_TARGET".
+    Var { var: String },
+    /// Uses a static value.
+    Value { value: String },
+}
+
+/// A compiled [`CheckDefinition`].
+#[derive(Debug)]
+pub enum Check {
+    Builtin(Builtin),
+    Regex(Regex),
+}
+
+/// A compiled [`SourceDefinition`].
+#[derive(Debug)]
+pub enum Source {
+    File { file: PathBuf, regex: Regex },
+    Template { template: String },
+    Var { var: String },
+    Value { value: String },
+}
+
+/// A registered custom key.
+#[derive(Debug)]
+pub struct CustomKey {
+    pub name: &'static str,
+    pub variable: Variable,
+    pub check: Option<Check>,
+    pub sources: Vec<Source>,
+    /// The keys referred to by the template sources of this key
+    pub template_inputs: &'static [Key],
+}
+
+/// This enumerates all possible errors returned by this module.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(
+        "Invalid custom key name '{0}'; it has to be in CamelCase, for example \"FirmwareTarget\""
+    )]
+    BadName(String),
+
+    #[error("The name of the custom key '{0}' is already in use")]
+    DuplicateName(String),
+
+    #[error("Too many custom keys; at most {} are supported", usize::from(Id::MAX) + 1)]
+    TooMany,
+
+    #[error("Invalid regex in the definition of the custom key '{key}': {source}")]
+    BadRegex { key: String, source: regex::Error },
+
+    #[error("The template of the custom key '{key}' refers to the unknown key '{reference}'")]
+    UnknownReference { key: String, reference: String },
+}
+
+lazy_static! {
+    // Custom keys live as long as the process does,
+    // just like the built-in ones.
+    static ref REGISTRY: RwLock<Vec<&'static CustomKey>> = RwLock::new(vec![]);
+    static ref R_NAME: Regex = Regex::new(r"^[A-Z][0-9a-zA-Z]*$").unwrap();
+    static ref R_TEMPLATE_REF: Regex = Regex::new(r"\{(?P<key>[0-9a-zA-Z]+)\}").unwrap();
+}
+
+fn leak(value: String) -> &'static str {
+    Box::leak(value.into_boxed_str())
+}
+
+fn compile_regex(key: &str, regex: &str) -> Result<Regex, Error> {
+    Regex::new(regex).map_err(|source| Error::BadRegex {
+        key: key.to_owned(),
+        source,
+    })
+}
+
+fn compile_source(
+    definition: &Definition,
+    source: &SourceDefinition,
+    names: &HashMap<&str, Key>,
+    template_inputs: &mut Vec<Key>,
+) -> Result<Source, Error> {
+    Ok(match source {
+        SourceDefinition::File { file, regex } => Source::File {
+            file: file.clone(),
+            regex: compile_regex(&definition.name, regex)?,
+        },
+        SourceDefinition::Template { template } => {
+            for caps in R_TEMPLATE_REF.captures_iter(template) {
+                let reference = &caps["key"];
+                let key = Key::from_str(reference)
+                    .ok()
+                    .or_else(|| names.get(reference).copied())
+                    .ok_or_else(|| Error::UnknownReference {
+                        key: definition.name.clone(),
+                        reference: reference.to_owned(),
+                    })?;
+                if !template_inputs.contains(&key) {
+                    template_inputs.push(key);
+                }
+            }
+            Source::Template {
+                template: template.clone(),
+            }
+        }
+        SourceDefinition::Var { var } => Source::Var { var: var.clone() },
+        SourceDefinition::Value { value } => Source::Value {
+            value: value.clone(),
+        },
+    })
+}
+
+/// Registers custom keys, which makes them available
+/// in all the places where built-in keys are used.
+///
+/// # Errors
+///
+/// If a name is not in CamelCase or already in use,
+/// if there are too many custom keys,
+/// if a regex is invalid,
+/// or if a template refers to an unknown key.
+pub fn register(definitions: &[Definition]) -> Result<(), Error> {
+    let mut registry = REGISTRY.write().unwrap();
+
+    // First assign all the new names,
+    // so templates may refer to any custom key.
+    let mut names = HashMap::<&str, Key>::new();
+    for registered in registry.iter() {
+        if let Some(key) = find_in(&registry, registered.name) {
+            names.insert(registered.name, key);
+        }
+    }
+    for (index, definition) in definitions.iter().enumerate() {
+        let name = definition.name.as_str();
+        if !R_NAME.is_match(name) {
+            return Err(Error::BadName(name.to_owned()));
+        }
+        if Key::from_str(name).is_ok() || names.contains_key(name) {
+            return Err(Error::DuplicateName(name.to_owned()));
+        }
+        let id = Id::try_from(registry.len() + index).map_err(|_err| Error::TooMany)?;
+        names.insert(name, Key::Custom(id));
+    }
+
+    let mut custom_keys = Vec::with_capacity(definitions.len());
+    for definition in definitions {
+        let check = match &definition.validator {
+            None => None,
+            Some(CheckDefinition::Builtin(builtin)) => Some(Check::Builtin(*builtin)),
+            Some(CheckDefinition::Regex { regex }) => {
+                Some(Check::Regex(compile_regex(&definition.name, regex)?))
+            }
+        };
+        let mut template_inputs = vec![];
+        let mut sources = Vec::with_capacity(definition.sources.len());
+        for source in &definition.sources {
+            sources.push(compile_source(
+                definition,
+                source,
+                &names,
+                &mut template_inputs,
+            )?);
+        }
+        let name = leak(definition.name.clone());
+        custom_keys.push(CustomKey {
+            name,
+            variable: Variable::new(
+                leak(var::camel_to_upper_snake_case(name)),
+                leak(definition.description.clone()),
+                definition.required,
+            ),
+            check,
+            sources,
+            template_inputs: Box::leak(template_inputs.into_boxed_slice()),
+        });
+    }
+    for custom_key in custom_keys {
+        log::trace!("Registered custom key {}.", custom_key.name);
+        registry.push(Box::leak(Box::new(custom_key)));
+    }
+    Ok(())
+}
+
+fn find_in(registry: &[&'static CustomKey], name: &str) -> Option<Key> {
+    registry
+        .iter()
+        .position(|custom_key| custom_key.name == name)
+        .and_then(|index| Id::try_from(index).ok())
+        .map(Key::Custom)
+}
+
+/// Returns the custom key registered under the given ID.
+///
+/// # Panics
+///
+/// If no custom key was registered under the given ID.
+/// As IDs are only handed out on registration, this should never happen.
+#[must_use]
+pub fn get(id: Id) -> &'static CustomKey {
+    REGISTRY.read().unwrap()[usize::from(id)]
+}
+
+/// Returns the custom key with the given name, if any.
+#[must_use]
+pub fn find(name: &str) -> Option<Key> {
+    find_in(&REGISTRY.read().unwrap(), name)
+}
+
+/// Returns all the registered custom keys, in order of registration.
+#[must_use]
+pub fn keys() -> Vec<Key> {
+    let num_keys = REGISTRY.read().unwrap().len();
+    (0..num_keys)
+        .filter_map(|index| Id::try_from(index).ok())
+        .map(Key::Custom)
+        .collect()
+}
+
+/// Removes all the registered custom keys,
+/// so tests do not depend on which other tests ran before them.
+/// The keys themselves are not freed, as they may still be referred to.
+#[cfg(test)]
+pub(crate) fn reset() {
+    REGISTRY.write().unwrap().clear();
+}
+
+/// Fills the values of other keys into a template,
+/// for example "{NameMachineReadable}-{Version}".
+/// The resulting confidence is the lowest of the ones of the values used.
+/// Returns `None` if any of the referred to keys has no value (yet).
+#[must_use]
+pub fn fill_template(environment: &Environment, template: &str) -> Option<(Confidence, String)> {
+    let mut confidence = Confidence::MAX;
+    let mut missing = false;
+    let filled = R_TEMPLATE_REF.replace_all(template, |caps: &regex::Captures| {
+        let value = Key::from_name(&caps["key"])
+            .ok()
+            .and_then(|key| environment.output.get(key).map(|value| (key, value)));
+        match value {
+            Some((key, (value_confidence, value))) => {
+                confidence = confidence.min(*value_confidence);
+                value.render(&environment.settings, key)
+            }
+            None => {
+                missing = true;
+                String::new()
+            }
+        }
+    });
+    if missing {
+        None
+    } else {
+        Some((confidence, filled.into_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enum_map::Enum;
+    use std::sync::{Mutex, MutexGuard};
+    use strum::EnumCount;
+
+    lazy_static! {
+        static ref REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    /// Gives the caller exclusive use of an empty registry,
+    /// until the returned guard is dropped.
+    fn empty_registry() -> MutexGuard<'static, ()> {
+        let guard = REGISTRY_LOCK
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        reset();
+        guard
+    }
+
+    fn definition(name: &str, sources: Vec<SourceDefinition>) -> Definition {
+        Definition {
+            name: name.to_owned(),
+            description: "Just for testing".to_owned(),
+            required: false,
+            validator: None,
+            sources,
+        }
+    }
+
+    #[test]
+    fn test_register() {
+        let _registry = empty_registry();
+        register(&[
+            definition(
+                "TestRegisterReferred",
+                vec![SourceDefinition::Value {
+                    value: "x".to_owned(),
+                }],
+            ),
+            definition(
+                "TestRegisterReferring",
+                vec![SourceDefinition::Template {
+                    template: "{Version}-{TestRegisterReferred}".to_owned(),
+                }],
+            ),
+        ])
+        .unwrap();
+        let referred = find("TestRegisterReferred").unwrap();
+        let referring = find("TestRegisterReferring").unwrap();
+        assert_eq!(keys(), vec![Key::Custom(0), Key::Custom(1)]);
+        assert_eq!(referred, Key::Custom(0));
+        assert_eq!(referring, Key::Custom(1));
+        if let Key::Custom(id) = referring {
+            assert_eq!(get(id).template_inputs, &[Key::Version, referred]);
+            assert_eq!(get(id).variable.to_string(), "TEST_REGISTER_REFERRING");
+        } else {
+            panic!("Not a custom key: {:?}", referring);
+        }
+    }
+
+    #[test]
+    fn test_register_invalid() {
+        let _registry = empty_registry();
+        assert!(matches!(
+            register(&[definition("lowerCamelCase", vec![])]),
+            Err(Error::BadName(_))
+        ));
+        assert!(matches!(
+            register(&[definition("Version", vec![])]),
+            Err(Error::DuplicateName(_))
+        ));
+        assert!(matches!(
+            register(&[definition(
+                "TestRegisterInvalidTemplate",
+                vec![SourceDefinition::Template {
+                    template: "{NoSuchKey}".to_owned(),
+                }]
+            )]),
+            Err(Error::UnknownReference { .. })
+        ));
+        assert!(find("TestRegisterInvalidTemplate").is_none());
+        assert!(keys().is_empty());
+    }
+
+    #[test]
+    fn test_enum_index() {
+        let _registry = empty_registry();
+        register(&[definition("TestEnumIndex", vec![])]).unwrap();
+        let num_built_in = Key::COUNT - 1;
+        assert_eq!(Key::LENGTH, num_built_in + 256);
+        assert_eq!(Key::AuthorCount.into_usize(), 0);
+        assert_eq!(
+            Key::Dirty.into_usize(),
+            Key::ContainerImageTag.into_usize() + 1
+        );
+        assert_eq!(Key::Custom(0).into_usize(), num_built_in);
+        assert_eq!(Key::from_usize(Key::LENGTH - 1), Key::Custom(Id::MAX));
+        for (index, key) in Key::iter_all().enumerate() {
+            assert_eq!(key.into_usize(), index);
+            assert_eq!(Key::from_usize(index), key);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Parsing and formatting of dates,
+//! in the formats and the timezone chosen by the user.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone};
+use strum_macros::{EnumString, EnumVariantNames, IntoStaticStr};
+
+/// ISO 8601, with the offset written without colon,
+/// which is not valid RFC 3339.
+const F_ISO_8601: &str = "%Y-%m-%dT%H:%M:%S%z";
+const F_DATE_ONLY: &str = "%Y-%m-%d";
+
+/// The default date format, for example "2021-12-31 23:59:59".
+/// For formatting specifiers, see:
+/// <https://docs.rs/chrono/latest/chrono/format/strftime/index.html>
+pub const DEFAULT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Commonly used date formats, which may be referred to by name.
+#[derive(Debug, EnumString, EnumVariantNames, IntoStaticStr, PartialEq, Eq, Clone, Copy)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Preset {
+    /// For example "2021-12-31T23:59:59+0200"
+    Iso8601,
+    /// For example "2021-12-31T23:59:59+02:00"
+    Rfc3339,
+    /// For example "Fri, 31 Dec 2021 23:59:59 +0200"
+    Rfc2822,
+    /// Seconds since the Unix epoch, for example "1640987999"
+    Epoch,
+    /// For example "2021-12-31"
+    DateOnly,
+}
+
+/// How dates are written to the environment and to files.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Format {
+    Preset(Preset),
+    /// A format string,
+    /// see <https://docs.rs/chrono/latest/chrono/format/strftime/index.html>
+    Custom(String),
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self::Custom(DEFAULT_FORMAT.to_owned())
+    }
+}
+
+impl FromStr for Format {
+    type Err = std::convert::Infallible;
+
+    /// Parses the name of a [`Preset`],
+    /// or else takes the string as a custom format.
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        Ok(match Preset::from_str(format) {
+            Ok(preset) => Self::Preset(preset),
+            Err(_) => Self::Custom(format.to_owned()),
+        })
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Preset(preset) => write!(formatter, "{}", <&str>::from(preset)),
+            Self::Custom(format) => write!(formatter, "{}", format),
+        }
+    }
+}
+
+/// The timezone dates are converted into for output.
+#[derive(Debug, EnumString, EnumVariantNames, IntoStaticStr, PartialEq, Eq, Clone, Copy)]
+#[strum(serialize_all = "lowercase")]
+pub enum Timezone {
+    /// Coordinated Universal Time
+    Utc,
+    /// The timezone of the host we run on
+    Local,
+    /// Keeps the offset each date was recorded with;
+    /// for the version date, that is the one of the committer.
+    Commit,
+}
+
+impl Default for Timezone {
+    fn default() -> Self {
+        Self::Commit
+    }
+}
+
+impl Timezone {
+    /// Converts a date into this timezone.
+    #[must_use]
+    pub fn apply(self, date: &DateTime<FixedOffset>) -> DateTime<FixedOffset> {
+        match self {
+            Self::Utc => date.with_timezone(&FixedOffset::east(0)),
+            Self::Local => {
+                let local = date.with_timezone(&Local);
+                local.with_timezone(local.offset())
+            }
+            Self::Commit => *date,
+        }
+    }
+
+    /// Interprets a date without offset as being in this timezone.
+    /// For [`Self::Commit`], we do not know the offset of the committer,
+    /// so we assume the local one.
+    fn localize(self, naive: &NaiveDateTime) -> Option<DateTime<FixedOffset>> {
+        match self {
+            Self::Utc => Some(FixedOffset::east(0).from_utc_datetime(naive)),
+            Self::Local | Self::Commit => Local
+                .from_local_datetime(naive)
+                .single()
+                .map(|local| local.with_timezone(local.offset())),
+        }
+    }
+}
+
+impl Format {
+    /// Formats a date, converted into the given timezone.
+    #[must_use]
+    pub fn format(&self, date: &DateTime<FixedOffset>, timezone: Timezone) -> String {
+        let date = timezone.apply(date);
+        match self {
+            Self::Preset(Preset::Iso8601) => date.format(F_ISO_8601).to_string(),
+            Self::Preset(Preset::Rfc3339) => date.to_rfc3339_opts(SecondsFormat::AutoSi, false),
+            Self::Preset(Preset::Rfc2822) => date.to_rfc2822(),
+            Self::Preset(Preset::Epoch) => date.timestamp().to_string(),
+            Self::Preset(Preset::DateOnly) => date.format(F_DATE_ONLY).to_string(),
+            Self::Custom(format) => date.format(format).to_string(),
+        }
+    }
+
+    /// Parses a date in RFC 3339 format, or else in this format.
+    /// Dates without offset are interpreted as being in the given timezone.
+    #[must_use]
+    pub fn parse(&self, value: &str, timezone: Timezone) -> Option<DateTime<FixedOffset>> {
+        if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+            return Some(date);
+        }
+        match self {
+            Self::Preset(Preset::Iso8601) => DateTime::parse_from_str(value, F_ISO_8601).ok(),
+            Self::Preset(Preset::Rfc3339) => None,
+            Self::Preset(Preset::Rfc2822) => DateTime::parse_from_rfc2822(value).ok(),
+            Self::Preset(Preset::Epoch) => value
+                .parse::<i64>()
+                .ok()
+                .and_then(|seconds| FixedOffset::east(0).timestamp_opt(seconds, 0).single()),
+            Self::Preset(Preset::DateOnly) => NaiveDate::parse_from_str(value, F_DATE_ONLY)
+                .ok()
+                .and_then(|date| timezone.localize(&date.and_hms(0, 0, 0))),
+            Self::Custom(format) => DateTime::parse_from_str(value, format).ok().or_else(|| {
+                NaiveDateTime::parse_from_str(value, format)
+                    .ok()
+                    .and_then(|naive| timezone.localize(&naive))
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let date = DateTime::parse_from_rfc3339("2021-12-31T23:59:59+02:00").unwrap();
+        let format =
+            |format: &str, timezone| Format::from_str(format).unwrap().format(&date, timezone);
+        assert_eq!(
+            format("iso8601", Timezone::Commit),
+            "2021-12-31T23:59:59+0200"
+        );
+        assert_eq!(
+            format("rfc3339", Timezone::Commit),
+            "2021-12-31T23:59:59+02:00"
+        );
+        assert_eq!(
+            format("rfc3339", Timezone::Utc),
+            "2021-12-31T21:59:59+00:00"
+        );
+        assert_eq!(
+            format("rfc2822", Timezone::Commit),
+            "Fri, 31 Dec 2021 23:59:59 +0200"
+        );
+        assert_eq!(format("epoch", Timezone::Commit), "1640987999");
+        assert_eq!(format("epoch", Timezone::Utc), "1640987999");
+        assert_eq!(format("date-only", Timezone::Commit), "2021-12-31");
+        assert_eq!(format("date-only", Timezone::Utc), "2021-12-31");
+        assert_eq!(format("%H:%M", Timezone::Utc), "21:59");
+    }
+
+    #[test]
+    fn test_parse() {
+        let date = DateTime::parse_from_rfc3339("2021-12-31T23:59:59+02:00").unwrap();
+        let parse = |format: &str, value: &str| {
+            Format::from_str(format)
+                .unwrap()
+                .parse(value, Timezone::Utc)
+        };
+        assert_eq!(parse("epoch", "2021-12-31T23:59:59+02:00"), Some(date));
+        assert_eq!(parse("iso8601", "2021-12-31T23:59:59+0200"), Some(date));
+        assert_eq!(
+            parse("rfc2822", "Fri, 31 Dec 2021 23:59:59 +0200"),
+            Some(date)
+        );
+        assert_eq!(parse("epoch", "1640987999"), Some(date));
+        assert_eq!(
+            parse("%Y-%m-%d %H:%M:%S", "2021-12-31 21:59:59"),
+            Some(date)
+        );
+        assert!(parse("date-only", "2021-12-31").is_some());
+        assert!(parse("date-only", "2021-12-31 23:59:59").is_none());
+        assert!(parse("rfc3339", "2021-12-31 23:59:59").is_none());
+    }
+}
@@ -5,6 +5,7 @@
 use crate::settings::{Settings, STUB};
 use crate::storage::Storage;
 use crate::tools::git;
+use crate::version::Bump;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 
@@ -16,6 +17,9 @@ pub struct Environment {
     /// The output values we evaluated for the project properties we want to know.
     pub output: Storage,
     repo: Option<git::Repo>,
+    /// The bump and the next version, once computed,
+    /// as both require the same walk over the history.
+    pub next_version: Option<Option<(Bump, semver::Version)>>,
 }
 
 impl Environment {
@@ -28,6 +32,7 @@ impl Environment {
             vars,
             output,
             repo: None,
+            next_version: None,
         }
     }
 
@@ -8,9 +8,13 @@ extern crate log;
 extern crate remain;
 extern crate url;
 
+pub mod config;
 mod constants;
+pub mod custom_keys;
+pub mod date;
 pub mod environment;
 mod license;
+pub mod normalize;
 pub mod process;
 pub mod settings;
 pub mod sinks;
@@ -19,7 +23,9 @@ mod std_error;
 mod storage;
 pub mod tools;
 pub mod validator;
+pub mod value;
 pub mod value_conversions;
 pub mod var;
+pub mod version;
 
 pub(crate) type BoxResult<T> = Result<T, Box<dyn std::error::Error>>;
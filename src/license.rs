@@ -6,6 +6,8 @@ use std::fmt;
 
 use askalono::{Store, TextData};
 use lazy_static::lazy_static;
+use regex::Regex;
+use std::path::Path;
 use std::{ffi::OsStr, fs};
 
 const LICENSE_FILE_PREFIXES: [&str; 3] = ["LICENSE", "LICENCE", "COPYING"];
@@ -92,6 +94,67 @@ pub fn get_licenses(dir: &str) -> Result<Vec<String>, std::io::Error> {
     DIR_LICENSES_EXTRACTOR.get_licenses(dir)
 }
 
+/// The licensing information found in the header of a single file,
+/// following the REUSE specification (<https://reuse.software/spec/>).
+#[derive(Debug, Default, Clone)]
+pub struct FileHeader {
+    /// The SPDX license expression; multiple ones are combined with "AND"
+    pub license: Option<String>,
+    /// The copyright notices, as given
+    pub copyright: Vec<String>,
+}
+
+/// Reads the SPDX license and copyright tags
+/// ("SPDX-License-Identifier: ..." and "SPDX-FileCopyrightText: ...")
+/// from the file at `path`,
+/// or from its "<path>.license" companion file, if that exists,
+/// as is common for binary files.
+///
+/// # Errors
+///
+/// If the file could not be read.
+pub fn read_file_header(path: &Path) -> Result<FileHeader, std::io::Error> {
+    lazy_static! {
+        static ref R_SPDX_TAG: Regex = Regex::new(
+            r"SPDX-(?P<tag>License-Identifier|FileCopyrightText):\s*(?P<value>.*?)\s*(\*/|-->)?\s*$"
+        )
+        .unwrap();
+    }
+
+    let mut companion = path.as_os_str().to_owned();
+    companion.push(".license");
+    let companion = Path::new(&companion);
+    let content = fs::read(if companion.is_file() { companion } else { path })?;
+
+    let mut licenses = vec![];
+    let mut header = FileHeader::default();
+    for line in String::from_utf8_lossy(&content).lines() {
+        if let Some(caps) = R_SPDX_TAG.captures(line) {
+            let value = caps["value"].to_owned();
+            if value.is_empty() {
+                continue;
+            }
+            if &caps["tag"] == "License-Identifier" {
+                licenses.push(value);
+            } else {
+                header.copyright.push(value);
+            }
+        }
+    }
+    if !licenses.is_empty() {
+        header.license = Some(if licenses.len() == 1 {
+            licenses.remove(0)
+        } else {
+            licenses
+                .iter()
+                .map(|license| format!("({})", license))
+                .collect::<Vec<_>>()
+                .join(" AND ")
+        });
+    }
+    Ok(header)
+}
+
 /// A basic wrapper around the askalono library;
 /// originally from here:
 /// <https://github.com/o2sh/onefetch/blob/main/src/info/license.rs>
@@ -148,3 +211,58 @@ impl Detector {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_file_header() {
+        let dir =
+            std::env::temp_dir().join(format!("projvar-test-file-header-{}", std::process::id()));
+        let _ignored = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let source = dir.join("main.rs");
+        fs::write(
+            &source,
+            "// SPDX-FileCopyrightText: 2021 Jane Doe <jane@example.org>\n\
+            // SPDX-FileCopyrightText: 2022 John Doe\n\
+            //\n\
+            // SPDX-License-Identifier: AGPL-3.0-or-later\n\
+            /* SPDX-License-Identifier: MIT OR Apache-2.0 */\n\
+            // SPDX-License-Identifier:\n\
+            \n\
+            fn main() {}\n",
+        )
+        .unwrap();
+        let header = read_file_header(&source).unwrap();
+        assert_eq!(
+            header.license.as_deref(),
+            Some("(AGPL-3.0-or-later) AND (MIT OR Apache-2.0)")
+        );
+        assert_eq!(
+            header.copyright,
+            vec![
+                "2021 Jane Doe <jane@example.org>".to_owned(),
+                "2022 John Doe".to_owned()
+            ]
+        );
+
+        let image = dir.join("logo.png");
+        fs::write(&image, b"\x89PNG").unwrap();
+        let header = read_file_header(&image).unwrap();
+        assert_eq!(header.license, None);
+        assert!(header.copyright.is_empty());
+        fs::write(
+            dir.join("logo.png.license"),
+            "SPDX-FileCopyrightText: 2022 Jane Doe\n\nSPDX-License-Identifier: CC0-1.0\n",
+        )
+        .unwrap();
+        let header = read_file_header(&image).unwrap();
+        assert_eq!(header.license.as_deref(), Some("CC0-1.0"));
+        assert_eq!(header.copyright, vec!["2022 Jane Doe".to_owned()]);
+
+        assert!(read_file_header(&dir.join("missing.txt")).is_err());
+    }
+}
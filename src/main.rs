@@ -9,10 +9,10 @@ extern crate log;
 extern crate remain;
 extern crate url;
 
-use clap::{app_from_crate, crate_name, App, Arg, ArgMatches, ValueHint};
+use clap::{app_from_crate, crate_name, App, Arg, ArgGroup, ArgMatches, ValueHint};
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::env;
 use std::path::{Path, PathBuf};
@@ -20,10 +20,14 @@ use std::str::FromStr;
 use strum::IntoEnumIterator;
 use strum::VariantNames;
 
+mod config;
 mod constants;
+mod custom_keys;
+mod date;
 mod environment;
 mod license;
 mod logger;
+mod normalize;
 mod process;
 pub mod settings;
 pub mod sinks;
@@ -32,8 +36,10 @@ mod std_error;
 mod storage;
 pub mod tools;
 mod validator;
+mod value;
 mod value_conversions;
 mod var;
+mod version;
 
 use crate::environment::Environment;
 use crate::settings::{Settings, Verbosity};
@@ -45,6 +51,9 @@ pub(crate) type BoxResult<T> = Result<T, Box<dyn std::error::Error>>;
 
 const A_S_PROJECT_ROOT: char = 'C';
 const A_L_PROJECT_ROOT: &str = "project-root";
+const A_S_CONFIG: char = 'c';
+const A_L_CONFIG: &str = "config";
+const A_L_ALLOW_PLUGINS: &str = "allow-plugins";
 const A_S_VARIABLE: char = 'D';
 const A_L_VARIABLE: &str = "variable";
 const A_S_VARIABLES_FILE: char = 'I';
@@ -55,8 +64,23 @@ const A_S_ENV_OUT: char = 'e';
 const A_L_ENV_OUT: &str = "env-out";
 const A_S_FILE_OUT: char = 'O';
 const A_L_FILE_OUT: &str = "file-out";
+const A_S_JSON_OUT: char = 'J';
+const A_L_JSON_OUT: &str = "json-out";
+// const A_S_RELEASE_NOTES_OUT: char = '?';
+const A_L_RELEASE_NOTES_OUT: &str = "release-notes-out";
+// const A_S_CHANGELOG_PREPEND: char = '?';
+const A_L_CHANGELOG_PREPEND: &str = "changelog-prepend";
+// const A_S_OCI_LABELS_OUT: char = '?';
+const A_L_OCI_LABELS_OUT: &str = "oci-labels-out";
+// const A_S_OCI_BUILD_ARGS_OUT: char = '?';
+const A_L_OCI_BUILD_ARGS_OUT: &str = "oci-build-args-out";
+// const A_S_OCI_DOCKERFILE_OUT: char = '?';
+const A_L_OCI_DOCKERFILE_OUT: &str = "oci-dockerfile-out";
 const A_S_HOSTING_TYPE: char = 't';
 const A_L_HOSTING_TYPE: &str = "hosting-type";
+// const A_S_HOSTING_ROOT_PATH: char = '?';
+const A_L_HOSTING_ROOT_PATH: &str = "hosting-root-path";
+const A_L_PAGES_DOMAIN: &str = "pages-domain";
 const A_S_VERBOSE: char = 'v';
 const A_L_VERBOSE: &str = "verbose";
 const A_S_LOG_LEVEL: char = 'F';
@@ -75,6 +99,10 @@ const A_S_REQUIRE_NOT: char = 'N';
 const A_L_REQUIRE_NOT: &str = "require-not";
 // const A_S_ONLY_REQUIRED: char = '?';
 const A_L_ONLY_REQUIRED: &str = "only-required";
+// const A_S_REPRODUCIBLE: char = '?';
+const A_L_REPRODUCIBLE: &str = "reproducible";
+// const A_S_KEEP_VERSION_PREFIX: char = '?';
+const A_L_KEEP_VERSION_PREFIX: &str = "keep-version-prefix";
 // const A_S_KEY_PREFIX: char = '?';
 const A_L_KEY_PREFIX: &str = "key-prefix";
 const A_S_DRY: char = 'd';
@@ -83,15 +111,36 @@ const A_S_OVERWRITE: char = 'o';
 const A_L_OVERWRITE: &str = "overwrite";
 const A_S_LIST: char = 'l';
 const A_L_LIST: &str = "list";
+// const A_S_LIST_SOURCES: char = '?';
+const A_L_LIST_SOURCES: &str = "list-sources";
+// const A_S_SOURCE: char = '?';
+const A_L_SOURCE: &str = "source";
+// const A_S_NO_SOURCE: char = '?';
+const A_L_NO_SOURCE: &str = "no-source";
+// const A_S_SOURCE_HIERARCHY: char = '?';
+const A_L_SOURCE_HIERARCHY: &str = "source-hierarchy";
 const A_S_LOG_FILE: char = 'L';
 const A_L_LOG_FILE: &str = "log-file";
 const A_S_DATE_FORMAT: char = 'T';
 const A_L_DATE_FORMAT: &str = "date-format";
+// const A_S_TIMEZONE: char = '?';
+const A_L_TIMEZONE: &str = "timezone";
+// const A_S_VERSION_SCHEME: char = '?';
+const A_L_VERSION_SCHEME: &str = "version-scheme";
+// const A_S_RELEASE_TAG_PATTERN: char = '?';
+const A_L_RELEASE_TAG_PATTERN: &str = "release-tag-pattern";
 const A_S_SHOW_ALL_RETRIEVED: char = 'A';
 const A_L_SHOW_ALL_RETRIEVED: &str = "show-all-retrieved";
 const A_S_SHOW_PRIMARY_RETRIEVED: char = 'P';
 const A_L_SHOW_PRIMARY_RETRIEVED: &str = "show-primary-retrieved";
 
+const SC_FILE: &str = "file";
+const A_L_FILE_PATH: &str = "path";
+const A_S_FILE_JSON_OUT: char = 'J';
+const A_L_FILE_JSON_OUT: &str = "json-out";
+const A_S_FILE_ENV_OUT_DIR: char = 'O';
+const A_L_FILE_ENV_OUT_DIR: &str = "env-out-dir";
+
 fn arg_project_root() -> Arg<'static> {
     Arg::new(A_L_PROJECT_ROOT)
         .help("The root dir of the project")
@@ -122,6 +171,30 @@ fn arg_variable() -> Arg<'static> {
         .required(false)
 }
 
+fn arg_config() -> Arg<'static> {
+    Arg::new(A_L_CONFIG)
+        .help("The configuration file (TOML)")
+        .long_help("The configuration file (in TOML format), which allows to define custom keys, for example. If not given, we use \".projvar.toml\" in the project root, if it exists. Plugins defined in it are only run with --allow-plugins.")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("FILE")
+        .value_hint(ValueHint::FilePath)
+        .short(A_S_CONFIG)
+        .long(A_L_CONFIG)
+        .multiple_occurrences(false)
+        .required(false)
+}
+
+fn arg_allow_plugins() -> Arg<'static> {
+    Arg::new(A_L_ALLOW_PLUGINS)
+        .help("Run the plugins defined in the configuration file")
+        .long_help("Run the plugins (external executables) defined in the configuration file (see -c,--config). As the configuration file usually comes with the project, which might not be trusted, they are ignored by default.")
+        .takes_value(false)
+        .long(A_L_ALLOW_PLUGINS)
+        .multiple_occurrences(false)
+        .required(false)
+}
+
 fn arg_variables_file() -> Arg<'static> {
     Arg::new(A_L_VARIABLES_FILE)
         .help("An input file containing KEY=VALUE pairs")
@@ -173,6 +246,90 @@ fn arg_out_file() -> Arg<'static> {
         .required(false)
 }
 
+fn arg_json_out() -> Arg<'static> {
+    Arg::new(A_L_JSON_OUT)
+        .help("Write variables into this file, in JSON format")
+        .long_help("Write evaluated values into a file, as a single JSON object, with lists as arrays, booleans as such and dates in RFC 3339 format (ignoring --date-format).")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("FILE")
+        .value_hint(ValueHint::FilePath)
+        .short(A_S_JSON_OUT)
+        .long(A_L_JSON_OUT)
+        .multiple_occurrences(true)
+        .required(false)
+}
+
+fn arg_release_notes_out() -> Arg<'static> {
+    Arg::new(A_L_RELEASE_NOTES_OUT)
+        .help("Write release notes into this file, in Markdown format")
+        .long_help("Write release notes for the commits since the last release tag (see --release-tag-pattern) into a file, in Markdown format. The commits are grouped by Conventional Commit type, and linked to, as far as the hosting type supports it.")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("MD-FILE")
+        .value_hint(ValueHint::FilePath)
+        // .short(A_S_RELEASE_NOTES_OUT)
+        .long(A_L_RELEASE_NOTES_OUT)
+        .multiple_occurrences(true)
+        .required(false)
+}
+
+fn arg_changelog_prepend() -> Arg<'static> {
+    Arg::new(A_L_CHANGELOG_PREPEND)
+        .help("Prepend release notes to this changelog file")
+        .long_help("Insert release notes (see --release-notes-out) as a new section at the top of a changelog file in Markdown format, like CHANGELOG.md, below its title (if any). If the file does not exist yet, it is created.")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("MD-FILE")
+        .value_hint(ValueHint::FilePath)
+        // .short(A_S_CHANGELOG_PREPEND)
+        .long(A_L_CHANGELOG_PREPEND)
+        .multiple_occurrences(true)
+        .required(false)
+}
+
+fn arg_oci_labels_out() -> Arg<'static> {
+    Arg::new(A_L_OCI_LABELS_OUT)
+        .help("Write OCI image labels into this file, as --label arguments")
+        .long_help("Write the standard OCI image labels (org.opencontainers.image.*: created, licenses, revision, source, title, url and version) into a file, one '--label=<label>=<value>' argument per line, as far as the values are available. Each line is one argument as is, without shell quoting, and values spanning multiple lines are left out. These can be passed to 'docker build' or 'podman build', for example with: mapfile -t labels < FILE; docker build \"${labels[@]}\" .")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("FILE")
+        .value_hint(ValueHint::FilePath)
+        // .short(A_S_OCI_LABELS_OUT)
+        .long(A_L_OCI_LABELS_OUT)
+        .multiple_occurrences(true)
+        .required(false)
+}
+
+fn arg_oci_build_args_out() -> Arg<'static> {
+    Arg::new(A_L_OCI_BUILD_ARGS_OUT)
+        .help("Write the values for OCI image labels into this file, as --build-arg arguments")
+        .long_help("Write the values used for the standard OCI image labels (see --oci-labels-out) into a file, one '--build-arg=<VARIABLE>=<value>' argument per line, using the variable names of the keys the values come from (for example PROJECT_VERSION). This allows to set the labels in the Dockerfile, using ARG instructions. Like with --oci-labels-out, each line is one argument as is, for example to be used with: mapfile -t args < FILE; docker build \"${args[@]}\" .")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("FILE")
+        .value_hint(ValueHint::FilePath)
+        // .short(A_S_OCI_BUILD_ARGS_OUT)
+        .long(A_L_OCI_BUILD_ARGS_OUT)
+        .multiple_occurrences(true)
+        .required(false)
+}
+
+fn arg_oci_dockerfile_out() -> Arg<'static> {
+    Arg::new(A_L_OCI_DOCKERFILE_OUT)
+        .help("Write OCI image labels into this file, as a Dockerfile LABEL instruction")
+        .long_help("Write the standard OCI image labels (see --oci-labels-out) into a file, as a single Dockerfile LABEL instruction, which may be included into a Dockerfile.")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("FILE")
+        .value_hint(ValueHint::FilePath)
+        // .short(A_S_OCI_DOCKERFILE_OUT)
+        .long(A_L_OCI_DOCKERFILE_OUT)
+        .multiple_occurrences(true)
+        .required(false)
+}
+
 fn arg_hosting_type() -> Arg<'static> {
     Arg::new(A_L_HOSTING_TYPE)
         .help("Overrides the hosting type of the primary remote")
@@ -187,6 +344,33 @@ fn arg_hosting_type() -> Arg<'static> {
         .default_value(HostingType::Unknown.into())
 }
 
+fn arg_hosting_root_path() -> Arg<'static> {
+    Arg::new(A_L_HOSTING_ROOT_PATH)
+        .help("The path under which the hosting software is installed")
+        .long_help("Self-hosted installations of hosting software (GitLab in particular) may be installed under a path prefix on their host, for example \"/gitlab\" in \"https://example.org/gitlab/group/sub-group/project\". This path is not part of SSH clone URLs, and it is not part of the project namespace, so we need to know it in order to correctly convert between the different kinds of URLs.")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("PATH")
+        .value_hint(ValueHint::Other)
+        // .short(A_S_HOSTING_ROOT_PATH)
+        .long(A_L_HOSTING_ROOT_PATH)
+        .multiple_occurrences(false)
+        .required(false)
+}
+
+fn arg_pages_domain() -> Arg<'static> {
+    Arg::new(A_L_PAGES_DOMAIN)
+        .help("The domain under which a self-hosted GitLab serves its pages")
+        .long_help("Self-hosted GitLab instances serve the pages of a project under a sub-domain of a domain set by their administrator, for example \"https://group.pages.example.org/project\". If not given, we assume \"pages.\" followed by the host of the instance, which is what gitlab.gnome.org and gitlab.freedesktop.org use.")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("DOMAIN")
+        .value_hint(ValueHint::Hostname)
+        .long(A_L_PAGES_DOMAIN)
+        .multiple_occurrences(false)
+        .required(false)
+}
+
 fn arg_verbose() -> Arg<'static> {
     Arg::new(A_L_VERBOSE)
         .help("More verbose log output")
@@ -308,6 +492,28 @@ fn arg_only_required() -> Arg<'static> {
         .required(false)
 }
 
+fn arg_reproducible() -> Arg<'static> {
+    Arg::new(A_L_REPRODUCIBLE)
+        .help("Produce the same output for the same commit on every host")
+        .long_help("Produce the same output for the same commit, no matter when or where we run. The build date is then taken from SOURCE_DATE_EPOCH if set, or else from the commit date (VersionDate), but never from the system clock. The host specific properties BuildArch, BuildOs and BuildOsFamily are omitted, unless they are explicitly required (see --require). See https://reproducible-builds.org/.")
+        .takes_value(false)
+        // .short(A_S_REPRODUCIBLE)
+        .long(A_L_REPRODUCIBLE)
+        .multiple_occurrences(false)
+        .required(false)
+}
+
+fn arg_keep_version_prefix() -> Arg<'static> {
+    Arg::new(A_L_KEEP_VERSION_PREFIX)
+        .help(r#"Keep the "v" prefix of versions like "v1.2.3""#)
+        .long_help(r#"Keep the "v" prefix of versions like "v1.2.3", as commonly used in git tags. By default, it is stripped before the version is validated and stored, so it is a valid semantic version ("1.2.3")."#)
+        .takes_value(false)
+        // .short(A_S_KEEP_VERSION_PREFIX)
+        .long(A_L_KEEP_VERSION_PREFIX)
+        .multiple_occurrences(false)
+        .required(false)
+}
+
 fn arg_key_prefix() -> Arg<'static> {
     Arg::new(A_L_KEY_PREFIX)
         .help("The key prefix to be used for output")
@@ -359,6 +565,68 @@ fn arg_list() -> Arg<'static> {
         .required(false)
 }
 
+fn arg_list_sources() -> Arg<'static> {
+    Arg::new(A_L_LIST_SOURCES)
+        .help("Show which sources supply which properties")
+        .long_help("Prints a matrix of all the properties (rows) and all the sources (columns) onto stdout and exits. Each cell contains the raw inputs (mostly CI environment variables) the source consults to supply the property, or '-' if it never supplies it.")
+        .takes_value(true)
+        .value_name("FORMAT")
+        .min_values(0)
+        .forbid_empty_values(true)
+        .possible_values(sources::matrix::Format::VARIANTS)
+        .long(A_L_LIST_SOURCES)
+        .multiple_occurrences(false)
+        .required(false)
+        .default_missing_value(sources::matrix::Format::default().into())
+}
+
+fn arg_source() -> Arg<'static> {
+    Arg::new(A_L_SOURCE)
+        .help("Use only this source")
+        .long_help("Use only the sources given with this option, identified by their short name (e.g. \"git\", \"fs\", \"travis_ci\", or \"plugin:<NAME>\" for a plugin); see --list-sources for all of them. Note that without \"selector\" and \"deriver\", values are neither selected by validity nor derived from each other. See --no-source.")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("SOURCE")
+        .value_hint(ValueHint::Other)
+        // .short(A_S_SOURCE)
+        .long(A_L_SOURCE)
+        .multiple_occurrences(true)
+        .required(false)
+}
+
+fn arg_no_source() -> Arg<'static> {
+    Arg::new(A_L_NO_SOURCE)
+        .help("Do not use this source")
+        .long_help("Do not use the source given with this option, identified by its short name (e.g. \"travis_ci\"); see --list-sources for all of them. See --source.")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("SOURCE")
+        .value_hint(ValueHint::Other)
+        // .short(A_S_NO_SOURCE)
+        .long(A_L_NO_SOURCE)
+        .multiple_occurrences(true)
+        .required(false)
+}
+
+fn arg_source_hierarchy() -> Arg<'static> {
+    lazy_static! {
+        static ref HIERARCHIES: String = sources::Hierarchy::VARIANTS.join(", ");
+        static ref LONG_HELP: String = format!("Overrides the hierarchy level of a source, which decides which sources values are preferred over others (if all else is equal), for example \"fs=EvenHigher\" to trust the VERSION file over git. Possible levels, from lowest to highest: {}. The effective order of the sources is logged with log-level Debug.", *HIERARCHIES);
+    }
+    Arg::new(A_L_SOURCE_HIERARCHY)
+        .help("Overrides the hierarchy level of a source")
+        .long_help(LONG_HELP.as_str())
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("SOURCE=LEVEL")
+        .value_hint(ValueHint::Other)
+        .validator(var::is_key_value_str_valid)
+        // .short(A_S_SOURCE_HIERARCHY)
+        .long(A_L_SOURCE_HIERARCHY)
+        .multiple_occurrences(true)
+        .required(false)
+}
+
 fn arg_log_file() -> Arg<'static> {
     lazy_static! {
         static ref LOG_FILE_NAME: String = format!("{}.log.txt", crate_name!());
@@ -377,16 +645,65 @@ fn arg_log_file() -> Arg<'static> {
 }
 
 fn arg_date_format() -> Arg<'static> {
+    lazy_static! {
+        static ref PRESETS: String = date::Preset::VARIANTS.join(", ");
+        static ref LONG_HELP: String = format!("Date format for dates written to the environment and to files (but not to JSON files, which always use RFC 3339). Either a format string (for details, see https://docs.rs/chrono/latest/chrono/format/strftime/index.html), or one of the presets: {}. Prefixed with a key and '=', it applies to that key only, for example \"VersionDate=date-only\" or \"PROJECT_BUILD_DATE=epoch\". Supplied dates are expected in RFC 3339 format, or in the format of their key.", *PRESETS);
+    }
     Arg::new(A_L_DATE_FORMAT)
-        .help("Date format for generated dates")
-        .long_help("Date format string for generated (vs supplied) dates. For details, see https://docs.rs/chrono/latest/chrono/format/strftime/index.html")
+        .help("Date format for output dates")
+        .long_help(LONG_HELP.as_str())
         .takes_value(true)
         .forbid_empty_values(true)
+        .value_name("[KEY=]FORMAT")
         .value_hint(ValueHint::Other)
         .short(A_S_DATE_FORMAT)
         .long(A_L_DATE_FORMAT)
+        .multiple_occurrences(true)
+        .default_value(date::DEFAULT_FORMAT)
+        .required(false)
+}
+
+fn arg_timezone() -> Arg<'static> {
+    Arg::new(A_L_TIMEZONE)
+        .help("Timezone for output dates")
+        .long_help("The timezone dates are converted into for output. \"commit\" keeps the offset each date was recorded with; for the version date, that is the one of the committer, and for the build date the local one.")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .possible_values(date::Timezone::VARIANTS)
+        .value_name("TIMEZONE")
+        // .short(A_S_TIMEZONE)
+        .long(A_L_TIMEZONE)
+        .multiple_occurrences(false)
+        .default_value(date::Timezone::default().into())
+        .required(false)
+}
+
+fn arg_version_scheme() -> Arg<'static> {
+    Arg::new(A_L_VERSION_SCHEME)
+        .help("The scheme the project versions follow")
+        .long_help("The scheme the project versions follow, which is used to validate the version. Versions produced by \"git describe\" (for example \"2024.03-4-gabcdef0-dirty\") are valid if the tag part is valid in the scheme. Independent of this, the version is also converted into the Debian and PEP 440 formats, if possible (see VersionDebian and VersionPep440).")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .possible_values(version::Scheme::VARIANTS)
+        .value_name("SCHEME")
+        // .short(A_S_VERSION_SCHEME)
+        .long(A_L_VERSION_SCHEME)
+        .multiple_occurrences(false)
+        .default_value(version::Scheme::default().into())
+        .required(false)
+}
+
+fn arg_release_tag_pattern() -> Arg<'static> {
+    Arg::new(A_L_RELEASE_TAG_PATTERN)
+        .help("A regex matching the tags that denote releases")
+        .long_help(r#"A regex matching the tags that denote releases, used to compute the next version (see VersionNext and VersionBump). If it contains a capture group named "version", that part of the tag is taken as the version, for example "^firmware-(?P<version>.+)$". By default, all tags that are semantic versions (optionally with a "v" prefix) denote releases."#)
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("REGEX")
+        .value_hint(ValueHint::Other)
+        // .short(A_S_RELEASE_TAG_PATTERN)
+        .long(A_L_RELEASE_TAG_PATTERN)
         .multiple_occurrences(false)
-        .default_value(tools::git::DATE_FORMAT)
         .required(false)
 }
 
@@ -421,15 +738,80 @@ fn arg_show_primary_retrieved() -> Arg<'static> {
         .conflicts_with(A_L_SHOW_ALL_RETRIEVED)
 }
 
+fn arg_file_path() -> Arg<'static> {
+    Arg::new(A_L_FILE_PATH)
+        .help("The files to evaluate values for")
+        .long_help("The files to evaluate values for, like their repo-relative path, the last commit that changed them, permalinks to them and their license and copyright, as found in their SPDX header (or in a \"<file>.license\" companion file).")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("FILE")
+        .value_hint(ValueHint::FilePath)
+        .multiple_values(true)
+        .required(true)
+}
+
+fn arg_file_json_out() -> Arg<'static> {
+    Arg::new(A_L_FILE_JSON_OUT)
+        .help("Write the file variables into this file, in JSON format")
+        .long_help("Write the evaluated values of the files into a file, as a JSON object, which contains one object of values for each repo-relative file path.")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("FILE")
+        .value_hint(ValueHint::FilePath)
+        .short(A_S_FILE_JSON_OUT)
+        .long(A_L_FILE_JSON_OUT)
+        .multiple_occurrences(true)
+        .required(false)
+}
+
+fn arg_file_env_out_dir() -> Arg<'static> {
+    Arg::new(A_L_FILE_ENV_OUT_DIR)
+        .help("Write the file variables into env files in this directory")
+        .long_help("Write the evaluated values of each file into a file in this directory, one KEY-VALUE pair per line (BASH syntax). The files are named like the repo-relative path of the file, with \".env\" appended, for example \"doc/index.md.env\".")
+        .takes_value(true)
+        .forbid_empty_values(true)
+        .value_name("DIR")
+        .value_hint(ValueHint::DirPath)
+        .short(A_S_FILE_ENV_OUT_DIR)
+        .long(A_L_FILE_ENV_OUT_DIR)
+        .multiple_occurrences(true)
+        .required(false)
+}
+
+fn subcommand_file() -> App<'static> {
+    App::new(SC_FILE)
+        .about("Evaluates values specific to single files of the project")
+        .long_about("Evaluates values specific to single files of the project, in addition to the project wide ones. The options given before this sub-command apply as usual.")
+        .arg(arg_file_path())
+        .arg(arg_file_json_out())
+        .arg(arg_file_env_out_dir())
+        .group(
+            ArgGroup::new("file-out")
+                .args(&[A_L_FILE_JSON_OUT, A_L_FILE_ENV_OUT_DIR])
+                .multiple(true)
+                .required(true),
+        )
+}
+
 lazy_static! {
-    static ref ARGS: [Arg<'static>; 24] = [
+    static ref ARGS: [Arg<'static>; 43] = [
         arg_project_root(),
+        arg_config(),
+        arg_allow_plugins(),
         arg_variable(),
         arg_variables_file(),
         arg_no_env_in(),
         arg_env_out(),
         arg_out_file(),
+        arg_json_out(),
+        arg_release_notes_out(),
+        arg_changelog_prepend(),
+        arg_oci_labels_out(),
+        arg_oci_build_args_out(),
+        arg_oci_dockerfile_out(),
         arg_hosting_type(),
+        arg_hosting_root_path(),
+        arg_pages_domain(),
         arg_verbose(),
         arg_log_level(),
         arg_quiet(),
@@ -439,12 +821,21 @@ lazy_static! {
         arg_require(),
         arg_require_not(),
         arg_only_required(),
+        arg_reproducible(),
+        arg_keep_version_prefix(),
         arg_key_prefix(),
         arg_dry(),
         arg_overwrite(),
         arg_list(),
+        arg_list_sources(),
+        arg_source(),
+        arg_no_source(),
+        arg_source_hierarchy(),
         arg_log_file(),
         arg_date_format(),
+        arg_timezone(),
+        arg_version_scheme(),
+        arg_release_tag_pattern(),
         arg_show_all_retrieved(),
         arg_show_primary_retrieved(),
     ];
@@ -467,7 +858,10 @@ fn find_duplicate_short_options() -> Vec<char> {
 }
 
 fn arg_matcher() -> App<'static> {
-    let app = app_from_crate!().bin_name("osh").args(ARGS.iter());
+    let app = app_from_crate!()
+        .bin_name("osh")
+        .args(ARGS.iter())
+        .subcommand(subcommand_file());
     let duplicate_short_options = find_duplicate_short_options();
     if !duplicate_short_options.is_empty() {
         panic!(
@@ -493,6 +887,16 @@ fn hosting_type(args: &ArgMatches) -> BoxResult<HostingType> {
     Ok(hosting_type)
 }
 
+fn hosting_root_path(args: &ArgMatches) -> Option<String> {
+    let hosting_root_path = args
+        .value_of(A_L_HOSTING_ROOT_PATH)
+        .map(|path| value_conversions::trim_char(path, '/'))
+        .filter(|path| !path.is_empty())
+        .map(|path| format!("/{}", path));
+    log::debug!("Hosting root path setting: {:?}", hosting_root_path);
+    hosting_root_path
+}
+
 /// Returns the logging verbosities to be used.
 /// The first one is for stdout&stderr,
 /// the second one for log-file(s).
@@ -527,37 +931,157 @@ fn repo_path(args: &ArgMatches) -> PathBuf {
     repo_path
 }
 
-fn date_format(args: &ArgMatches) -> &str {
-    let date_format = match args.value_of(A_L_DATE_FORMAT) {
-        Some(date_format) => date_format,
-        None => tools::git::DATE_FORMAT,
+fn config(args: &ArgMatches, repo_path: &Path) -> BoxResult<config::Config> {
+    let mut config = if let Some(config_file) = args.value_of(A_L_CONFIG) {
+        log::debug!("Using config file '{}'.", config_file);
+        config::Config::load(Path::new(config_file))?
+    } else {
+        let default_config_file = repo_path.join(config::DEFAULT_FILE);
+        if default_config_file.is_file() {
+            log::debug!("Using config file '{}'.", default_config_file.display());
+            config::Config::load(&default_config_file)?
+        } else {
+            config::Config::default()
+        }
     };
-    log::debug!("Using date format '{}'.", date_format);
-    date_format
+    if !config.plugins.is_empty() && !args.is_present(A_L_ALLOW_PLUGINS) {
+        log::warn!(
+            "Ignoring the {} plugin(s) defined in the config file; use --{} to run them.",
+            config.plugins.len(),
+            A_L_ALLOW_PLUGINS
+        );
+        config.plugins.clear();
+    }
+    config.apply()?;
+    Ok(config)
+}
+
+fn sources(args: &ArgMatches, repo_path: &Path) -> BoxResult<Vec<Box<dyn sources::VarSource>>> {
+    let enabled: Vec<&str> = args.values_of(A_L_SOURCE).unwrap_or_default().collect();
+    let disabled: Vec<&str> = args.values_of(A_L_NO_SOURCE).unwrap_or_default().collect();
+    let mut hierarchies = HashMap::new();
+    if let Some(source_hierarchies) = args.values_of(A_L_SOURCE_HIERARCHY) {
+        for source_hierarchy in source_hierarchies {
+            let (name, hierarchy) = var::parse_key_value_str(source_hierarchy)?;
+            hierarchies.insert(name.to_owned(), sources::Hierarchy::from_str(hierarchy)?);
+        }
+    }
+    Ok(sources::configure(
+        sources::default_list(repo_path),
+        &enabled,
+        &disabled,
+        &hierarchies,
+    )?)
+}
+
+fn date_formats(
+    key_prefix: Option<&str>,
+    args: &ArgMatches,
+) -> BoxResult<(date::Format, HashMap<Key, date::Format>)> {
+    lazy_static! {
+        static ref R_KEY_FORMAT: Regex =
+            Regex::new(r"^(?P<key>[0-9a-zA-Z_]+)=(?P<format>.+)$").unwrap();
+    }
+    let r_key_prefix = Regex::new(&format!("^{}", key_prefix.unwrap_or(""))).unwrap();
+    let mut date_format = date::Format::default();
+    let mut key_date_formats = HashMap::new();
+    if let Some(formats) = args.values_of(A_L_DATE_FORMAT) {
+        for format in formats {
+            match R_KEY_FORMAT.captures(format) {
+                Some(caps) => {
+                    let key = Key::from_name_or_var_key(&r_key_prefix, &caps["key"])?;
+                    let key_format = date::Format::from_str(&caps["format"])?;
+                    log::debug!("Using date format '{}' for key {:?}.", key_format, key);
+                    key_date_formats.insert(key, key_format);
+                }
+                None => {
+                    date_format = date::Format::from_str(format)?;
+                    log::debug!("Using date format '{}'.", date_format);
+                }
+            }
+        }
+    }
+    Ok((date_format, key_date_formats))
 }
 
 fn sinks_cli(args: &ArgMatches) -> BoxResult<Vec<Box<dyn VarSink>>> {
-    let env_out = args.is_present(A_L_ENV_OUT);
-    let dry = args.is_present(A_L_DRY);
+    let mut options = sinks::CliSinkOptions {
+        env_out: args.is_present(A_L_ENV_OUT),
+        dry: args.is_present(A_L_DRY),
+        ..sinks::CliSinkOptions::default()
+    };
 
-    let mut default_out_file = false;
-    let mut additional_out_files = vec![];
     if args.is_present(A_L_FILE_OUT) {
         if args.occurrences_of(A_L_FILE_OUT) == 0 {
-            default_out_file = true;
+            options.default_out_file = true;
         } else if let Some(out_files) = args.values_of(A_L_FILE_OUT) {
             for out_file in out_files {
-                additional_out_files.push(PathBuf::from_str(out_file)?);
+                options
+                    .additional_out_files
+                    .push(PathBuf::from_str(out_file)?);
+            }
+        }
+    }
+
+    if let Some(out_files) = args.values_of(A_L_JSON_OUT) {
+        for out_file in out_files {
+            options.json_out_files.push(PathBuf::from_str(out_file)?);
+        }
+    }
+
+    if let Some(out_files) = args.values_of(A_L_RELEASE_NOTES_OUT) {
+        for out_file in out_files {
+            options
+                .release_notes_files
+                .push(PathBuf::from_str(out_file)?);
+        }
+    }
+
+    if let Some(out_files) = args.values_of(A_L_CHANGELOG_PREPEND) {
+        for out_file in out_files {
+            options.changelog_files.push(PathBuf::from_str(out_file)?);
+        }
+    }
+
+    for (arg, format) in [
+        (A_L_OCI_LABELS_OUT, sinks::oci_labels::Format::LabelArgs),
+        (A_L_OCI_BUILD_ARGS_OUT, sinks::oci_labels::Format::BuildArgs),
+        (
+            A_L_OCI_DOCKERFILE_OUT,
+            sinks::oci_labels::Format::Dockerfile,
+        ),
+    ] {
+        if let Some(out_files) = args.values_of(arg) {
+            for out_file in out_files {
+                options
+                    .oci_labels_files
+                    .push((PathBuf::from_str(out_file)?, format));
+            }
+        }
+    }
+
+    if let Some(file_args) = args.subcommand_matches(SC_FILE) {
+        let mut file_paths = vec![];
+        if let Some(paths) = file_args.values_of(A_L_FILE_PATH) {
+            for path in paths {
+                file_paths.push(PathBuf::from_str(path)?);
+            }
+        }
+        let mut targets = vec![];
+        if let Some(out_files) = file_args.values_of(A_L_FILE_JSON_OUT) {
+            for out_file in out_files {
+                targets.push(sinks::files::Target::Json(PathBuf::from_str(out_file)?));
+            }
+        }
+        if let Some(out_dirs) = file_args.values_of(A_L_FILE_ENV_OUT_DIR) {
+            for out_dir in out_dirs {
+                targets.push(sinks::files::Target::EnvDir(PathBuf::from_str(out_dir)?));
             }
         }
+        options.files = Some((file_paths, targets));
     }
 
-    Ok(sinks::cli_list(
-        env_out,
-        dry,
-        default_out_file,
-        additional_out_files,
-    ))
+    Ok(sinks::cli_list(options))
 }
 
 fn required_keys(key_prefix: Option<&str>, args: &ArgMatches) -> BoxResult<HashSet<Key>> {
@@ -565,12 +1089,12 @@ fn required_keys(key_prefix: Option<&str>, args: &ArgMatches) -> BoxResult<HashS
     let require_none: bool = args.is_present(A_L_REQUIRE_NONE);
     let mut required_keys = if require_all {
         let mut all = HashSet::<Key>::new();
-        all.extend(Key::iter());
+        all.extend(Key::iter_all());
         all
     } else if require_none {
         HashSet::<Key>::new()
     } else {
-        var::default_keys().clone()
+        var::default_keys()
     };
     let r_key_prefix_str = format!("^{}", key_prefix.unwrap_or(""));
     let r_key_prefix = Regex::new(&r_key_prefix_str).unwrap();
@@ -605,6 +1129,9 @@ fn main() -> BoxResult<()> {
     let log_file = args.value_of(A_L_LOG_FILE).map(Path::new);
     logger::init(log_file, verbosity);
 
+    let repo_path = repo_path(&args);
+    config(&args, &repo_path)?;
+
     if args.is_present(A_L_LIST) {
         let environment = Environment::stub();
         let list = var::list_keys(&environment);
@@ -612,13 +1139,20 @@ fn main() -> BoxResult<()> {
         return Ok(());
     }
 
-    let repo_path = repo_path(&args);
-    let date_format = date_format(&args);
+    if let Some(format) = args.value_of(A_L_LIST_SOURCES) {
+        let format = sources::matrix::Format::from_str(format)?;
+        let environment = Environment::stub();
+        let matrix = sources::matrix::generate(&environment, &sources::full_list(), format);
+        // NOTE We do not use the logger here,
+        //      so the machine-readable formats may be piped into a file
+        print!("{}", matrix);
+        return Ok(());
+    }
 
     let overwrite = settings::Overwrite::from_str(args.value_of(A_L_OVERWRITE).unwrap())?;
     log::debug!("Overwriting output variable values? -> {:?}", overwrite);
 
-    let sources = sources::default_list(&repo_path);
+    let sources = sources(&args, &repo_path)?;
 
     let sinks = sinks_cli(&args)?;
 
@@ -638,18 +1172,41 @@ fn main() -> BoxResult<()> {
     } else {
         settings::ShowRetrieved::No
     };
+    let (date_format, key_date_formats) = date_formats(key_prefix, &args)?;
+    let timezone = date::Timezone::from_str(args.value_of(A_L_TIMEZONE).unwrap())?;
+    log::debug!("Using timezone {:?} for output dates.", timezone);
+    let version_scheme = version::Scheme::from_str(args.value_of(A_L_VERSION_SCHEME).unwrap())?;
+    log::debug!("Using version scheme {:?}.", version_scheme);
+    let release_tag_pattern = args
+        .value_of(A_L_RELEASE_TAG_PATTERN)
+        .map(Regex::new)
+        .transpose()?;
     let hosting_type = hosting_type(&args)?;
+    let hosting_root_path = hosting_root_path(&args);
+    let pages_domain = args
+        .value_of(A_L_PAGES_DOMAIN)
+        .map(|domain| value_conversions::trim_char(domain, '.').to_owned());
     let only_required = args.is_present(A_L_ONLY_REQUIRED);
+    let reproducible = args.is_present(A_L_REPRODUCIBLE);
+    let keep_version_prefix = args.is_present(A_L_KEEP_VERSION_PREFIX);
 
     let settings = Settings {
         repo_path: Some(repo_path),
         required_keys,
-        date_format: date_format.to_owned(),
+        date_format,
+        key_date_formats,
+        timezone,
         overwrite,
         fail_on: settings::FailOn::from(fail_on_missing),
         show_retrieved,
         hosting_type,
+        hosting_root_path,
+        pages_domain,
         only_required,
+        reproducible,
+        keep_version_prefix,
+        version_scheme,
+        release_tag_pattern,
         key_prefix: key_prefix.map(ToOwned::to_owned),
         verbosity,
     };
@@ -679,7 +1236,10 @@ fn main() -> BoxResult<()> {
     // insert CLI supplied variables values
     if let Some(variables) = args.values_of(A_L_VARIABLE) {
         for var in variables {
-            log::trace!("Adding variable from CLI: '{}' ...", var);
+            log::trace!(
+                "Adding variable from CLI: '{}' ...",
+                tools::secrets::redact(var)
+            );
             let (key, value) = var::parse_key_value_str(var)?;
             environment.vars.insert(key.to_owned(), value.to_owned());
         }
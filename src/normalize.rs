@@ -0,0 +1,207 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Maps the values sources supply in the vocabulary of their vendor
+//! (for example "amd64" or "Linux" from CI systems)
+//! to our canonical values (here "x86_64" and "linux"),
+//! and strips the "v" prefix from versions like "v1.2.3",
+//! before they get stored and validated.
+//! Additional mappings may be defined in the configuration file
+//! (see [`crate::config`]).
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use thiserror::Error;
+
+use crate::settings::Settings;
+use crate::var::Key;
+
+/// Mappings from vendor values (in lower-case) to canonical ones.
+type Mappings = HashMap<String, String>;
+
+/// This enumerates all possible errors returned by this module.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Value mappings are defined for the unknown key '{0}'")]
+    UnknownKey(String),
+}
+
+const BUILD_ARCH: &[(&str, &str)] = &[
+    ("386", "x86"),
+    ("aarch64", "arm64"),
+    ("amd64", "x86_64"),
+    ("armhf", "arm"),
+    ("armv6l", "arm"),
+    ("armv7", "arm"),
+    ("armv7l", "arm"),
+    ("i386", "x86"),
+    ("i686", "x86"),
+    ("ppc64le", "powerpc64"),
+    ("x64", "x86_64"),
+    ("x86-64", "x86_64"),
+];
+
+const BUILD_OS: &[(&str, &str)] = &[
+    ("darwin", "macos"),
+    ("mac", "macos"),
+    ("osx", "macos"),
+    ("win", "windows"),
+    ("win32", "windows"),
+];
+
+const BUILD_OS_FAMILY: &[(&str, &str)] = &[("win", "windows"), ("win32", "windows")];
+
+fn mappings(pairs: &[(&str, &str)]) -> Mappings {
+    pairs
+        .iter()
+        .map(|(vendor, canonical)| ((*vendor).to_owned(), (*canonical).to_owned()))
+        .collect()
+}
+
+lazy_static! {
+    static ref REGISTRY: RwLock<HashMap<Key, Mappings>> = RwLock::new(HashMap::from([
+        (Key::BuildArch, mappings(BUILD_ARCH)),
+        (Key::BuildOs, mappings(BUILD_OS)),
+        (Key::BuildOsFamily, mappings(BUILD_OS_FAMILY)),
+    ]));
+}
+
+/// Registers additional mappings from vendor values to canonical ones,
+/// by key name; for example "BuildArch" -> "ppc64el" -> "powerpc64".
+/// These take precedence over the built-in ones.
+/// This has to be called after registering the custom keys
+/// (see [`crate::custom_keys::register`]),
+/// so their values may be normalized too.
+///
+/// # Errors
+///
+/// If a key name is unknown.
+pub fn register(definitions: &HashMap<String, Mappings>) -> Result<(), Error> {
+    let mut registry = REGISTRY.write().unwrap();
+    for (key_name, mappings) in definitions {
+        let key = Key::from_name(key_name).map_err(|_err| Error::UnknownKey(key_name.clone()))?;
+        let key_mappings = registry.entry(key).or_default();
+        for (vendor, canonical) in mappings {
+            log::trace!(
+                "Registered mapping for {:?}: '{}' -> '{}'.",
+                key,
+                vendor,
+                canonical
+            );
+            key_mappings.insert(vendor.to_lowercase(), canonical.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Removes a leading "v" or "V" from a version like "v1.2.3",
+/// as commonly used in git tags.
+/// Other values are returned unchanged.
+#[must_use]
+pub fn strip_version_prefix(value: &str) -> &str {
+    match value.strip_prefix(&['v', 'V'][..]) {
+        Some(stripped) if stripped.starts_with(|chr: char| chr.is_ascii_digit()) => stripped,
+        _ => value,
+    }
+}
+
+/// Extracts the part relevant for `key` from platform strings
+/// like "linux/amd64" or "linux/arm/v7" (as used by docker and GitLab),
+/// and converts it to lower-case.
+/// For the version, it strips the "v" prefix, unless configured otherwise.
+/// Returns `None` for keys without a fixed vocabulary.
+fn prepare(settings: &Settings, key: Key, raw: &str) -> Option<String> {
+    let value = raw.trim();
+    let mut parts = value.split('/');
+    let os = parts.next().unwrap_or(value);
+    let arch = parts.next().unwrap_or(value);
+    match key {
+        Key::BuildOs | Key::BuildOsFamily => Some(os.to_lowercase()),
+        Key::BuildArch => Some(arch.to_lowercase()),
+        Key::Version if !settings.keep_version_prefix => Some(strip_version_prefix(raw).to_owned()),
+        _ => None,
+    }
+}
+
+/// Maps a value supplied by a source for `key` to our canonical value.
+/// For the OS and architecture keys, values are also converted to lower-case.
+/// Values without a mapping are returned unchanged.
+#[must_use]
+pub fn normalize<'v>(settings: &Settings, key: Key, value: &'v str) -> Cow<'v, str> {
+    let registry = REGISTRY.read().unwrap();
+    let key_mappings = registry.get(&key);
+    let prepared = prepare(settings, key, value);
+    if key_mappings.is_none() && prepared.is_none() {
+        return Cow::Borrowed(value);
+    }
+    let lookup = prepared
+        .clone()
+        .unwrap_or_else(|| value.trim().to_lowercase());
+    match key_mappings.and_then(|key_mappings| key_mappings.get(&lookup)) {
+        Some(canonical) => Cow::Owned(canonical.clone()),
+        None => match prepared {
+            Some(prepared) if prepared != value => Cow::Owned(prepared),
+            _ => Cow::Borrowed(value),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let settings = &crate::settings::STUB;
+        assert_eq!(normalize(settings, Key::BuildArch, "amd64"), "x86_64");
+        assert_eq!(normalize(settings, Key::BuildArch, "aarch64"), "arm64");
+        assert_eq!(normalize(settings, Key::BuildArch, "X64"), "x86_64");
+        assert_eq!(normalize(settings, Key::BuildArch, "linux/amd64"), "x86_64");
+        assert_eq!(normalize(settings, Key::BuildArch, "linux/arm/v7"), "arm");
+        assert_eq!(normalize(settings, Key::BuildArch, "x86_64"), "x86_64");
+        assert_eq!(normalize(settings, Key::BuildOs, "Linux"), "linux");
+        assert_eq!(normalize(settings, Key::BuildOs, "macOS"), "macos");
+        assert_eq!(normalize(settings, Key::BuildOs, "darwin"), "macos");
+        assert_eq!(normalize(settings, Key::BuildOs, "linux/amd64"), "linux");
+        assert_eq!(normalize(settings, Key::BuildOsFamily, "unix"), "unix");
+        assert!(matches!(
+            normalize(settings, Key::Name, "My Project"),
+            Cow::Borrowed("My Project")
+        ));
+    }
+
+    #[test]
+    fn test_strip_version_prefix() {
+        let mut settings = crate::settings::STUB.clone();
+        assert_eq!(normalize(&settings, Key::Version, "v1.2.3"), "1.2.3");
+        assert_eq!(
+            normalize(&settings, Key::Version, "V1.2.3-rc.1"),
+            "1.2.3-rc.1"
+        );
+        assert_eq!(normalize(&settings, Key::Version, "1.2.3"), "1.2.3");
+        assert_eq!(normalize(&settings, Key::Version, "very-1.2"), "very-1.2");
+        settings.keep_version_prefix = true;
+        assert_eq!(normalize(&settings, Key::Version, "v1.2.3"), "v1.2.3");
+    }
+
+    #[test]
+    fn test_register() {
+        register(&HashMap::from([(
+            "BuildArch".to_owned(),
+            HashMap::from([("PPC64EL".to_owned(), "powerpc64".to_owned())]),
+        )]))
+        .unwrap();
+        assert_eq!(
+            normalize(&crate::settings::STUB, Key::BuildArch, "ppc64el"),
+            "powerpc64"
+        );
+        assert!(matches!(
+            register(&HashMap::from([("NoSuchKey".to_owned(), HashMap::new())])),
+            Err(Error::UnknownKey(_))
+        ));
+    }
+}
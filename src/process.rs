@@ -3,14 +3,17 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::environment::Environment;
+use crate::normalize;
 use crate::settings::FailOn;
 use crate::sinks::VarSink;
 use crate::sources::VarSource;
-use crate::var::Key;
-use crate::{validator, BoxResult};
+use crate::tools::secrets;
+use crate::value::Value;
+use crate::var::{self, Key};
+use crate::{storage, validator, BoxResult};
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::fs;
-use strum::IntoEnumIterator;
 
 /// Reports the raw values retrieved from the sources, if requested
 fn report_retrieved(environment: &Environment, sources: &[Box<dyn VarSource>]) -> BoxResult<()> {
@@ -38,6 +41,125 @@ fn report_retrieved(environment: &Environment, sources: &[Box<dyn VarSource>]) -
     Ok(())
 }
 
+/// Evaluates the keys we need to fetch values for.
+/// These are the required keys plus all the keys they may be derived from (transitively),
+/// and unless only the required ones are requested,
+/// all other keys except the [`var::HISTORY_KEYS`].
+fn keys_to_fetch(environment: &Environment, sources: &[Box<dyn VarSource>]) -> HashSet<Key> {
+    let mut keys = environment.settings.required_keys.clone();
+    let mut pending: Vec<Key> = keys.iter().copied().collect();
+    while let Some(key) = pending.pop() {
+        for source in sources {
+            for input_key in source.derivation_inputs(key) {
+                if keys.insert(*input_key) {
+                    log::trace!(
+                        "\tFetching {:?} too, because {:?} may be derived from it",
+                        input_key,
+                        key
+                    );
+                    pending.push(*input_key);
+                }
+            }
+        }
+    }
+    if !environment.settings.only_required {
+        keys.extend(Key::iter_all().filter(|key| !var::HISTORY_KEYS.contains(key)));
+    }
+    keys
+}
+
+/// The values to write to the sinks.
+fn output_values(environment: &Environment) -> Vec<storage::Entry<'_>> {
+    let mut values = environment.output.get_wrapup();
+    if environment.settings.only_required {
+        // Drop the values we only fetched to derive required ones from
+        values.retain(|(key, _variable, _value)| environment.settings.required_keys.contains(key));
+    }
+    if environment.settings.reproducible {
+        // These would make the output differ between hosts
+        values.retain(|(key, _variable, _value)| {
+            !var::HOST_SPECIFIC_KEYS.contains(key)
+                || environment.settings.required_keys.contains(key)
+        });
+    }
+    values
+}
+
+/// Whether the source derives values from values gathered before,
+/// instead of fetching them from elsewhere.
+fn is_deriving(source: &dyn VarSource) -> bool {
+    Key::iter_all().any(|key| !source.derivation_inputs(key).is_empty())
+}
+
+/// Fetches the values of all `keys` from a single source,
+/// and adds them to the output storage.
+/// Returns the number of values retrieved.
+fn fetch(
+    environment: &mut Environment,
+    source_index: usize,
+    source: &dyn VarSource,
+    keys: &HashSet<Key>,
+) -> BoxResult<usize> {
+    let mut num_retrieved = 0;
+    for key in Key::iter_all() {
+        if !keys.contains(&key) {
+            log::trace!("\tSkip fetching {:?} because it is not required", key);
+            continue;
+        }
+        if source.supplies(environment, key).is_none() {
+            log::trace!(
+                "\tSkip fetching {:?} because this source never supplies it",
+                key
+            );
+            continue;
+        }
+        if let Some(selected) = source.select(environment, key) {
+            if let Some((confidence, value)) = selected? {
+                log::trace!(
+                    "\tSelected {:?}='{}'",
+                    key,
+                    secrets::redact(&value.to_string())
+                );
+                environment.output.add(key, source_index, confidence, value);
+                num_retrieved += 1;
+            }
+            continue;
+        }
+        let rated_value = source.retrieve(environment, key)?;
+        if let Some((confidence, value)) = rated_value {
+            let value = match secrets::strip_url_credentials(&value) {
+                std::borrow::Cow::Borrowed(_) => value,
+                std::borrow::Cow::Owned(stripped) => {
+                    log::warn!(
+                        "Removed credentials from the value for {:?} fetched from source {}",
+                        key,
+                        source.display()
+                    );
+                    stripped
+                }
+            };
+            log::trace!("\tFetched {:?}='{}'", key, secrets::redact(&value));
+            let value = match normalize::normalize(&environment.settings, key, &value) {
+                std::borrow::Cow::Borrowed(_) => value,
+                std::borrow::Cow::Owned(normalized) => {
+                    log::debug!(
+                        "\tNormalized {:?} from '{}' to '{}'",
+                        key,
+                        secrets::redact(&value),
+                        secrets::redact(&normalized)
+                    );
+                    environment.output.add_raw(key, source_index, value);
+                    normalized
+                }
+            };
+            let value = Value::parse(environment, key, value);
+            environment.output.add(key, source_index, confidence, value);
+            num_retrieved += 1;
+        }
+    }
+    Ok(num_retrieved)
+}
+
 /// The main function of this crate,
 /// gathering data as good as it can,
 /// and making sure it is stored in the appropriate environment variables.
@@ -70,21 +192,28 @@ pub fn run(
         }
     });
 
+    if log::log_enabled!(log::Level::Debug) {
+        log::debug!("Sources, in order of evaluation:");
+        for source in &sources {
+            let hierarchy: &str = source.hierarchy().into();
+            log::debug!("\t{} ({})", source.display(), hierarchy);
+        }
+    }
+
+    let keys = keys_to_fetch(environment, &sources);
     for (source_index, source) in sources.iter().enumerate() {
         if source.is_usable(environment) {
             log::trace!("Trying to fetch from source {} ...", source.display());
-            for key in Key::iter() {
-                if environment.settings.only_required
-                    && !environment.settings.required_keys.contains(&key)
-                {
-                    log::trace!("\tSkip fetching {:?} because it is not required", key);
-                    continue;
-                }
-                let rated_value = source.retrieve(environment, key)?;
-                if let Some((confidence, value)) = rated_value {
-                    log::trace!("\tFetched {:?}='{}'", key, value);
-                    environment.output.add(key, source_index, confidence, value);
+            if is_deriving(source.as_ref()) {
+                // Derived values may serve as input for further derivations,
+                // so we repeat until nothing new comes up
+                let mut pass = 1;
+                while fetch(environment, source_index, source.as_ref(), &keys)? > 0 {
+                    pass += 1;
+                    log::trace!("Deriving values, pass {} ...", pass);
                 }
+            } else {
+                fetch(environment, source_index, source.as_ref(), &keys)?;
             }
         }
     }
@@ -93,12 +222,16 @@ pub fn run(
 
     log::trace!("Validate each variables precense and value ...");
     let output = environment.output.clone();
-    for key in Key::iter() {
+    for key in Key::iter_all() {
         let required = environment.settings.required_keys.contains(&key);
         match output.get(key) {
             Some((_confidence, value)) => {
-                log::trace!("Validating value for key '{:?}': '{}'", key, value);
-                let validation_res = validator::get(key)(environment, value);
+                log::trace!(
+                    "Validating value for key '{:?}': '{}'",
+                    key,
+                    secrets::redact(&value.to_string())
+                );
+                let validation_res = validator::validate(environment, key, value);
                 match validation_res {
                     Ok(validity) => {
                         log::debug!("Validation result for key '{:?}': {:?}", key, validity);
@@ -124,7 +257,7 @@ pub fn run(
     }
 
     log::trace!("Evaluated variables ...");
-    let values = environment.output.get_wrapup();
+    let values = output_values(environment);
     if log::log_enabled!(log::Level::Trace) {
         for (key, variable, (confidence, value)) in &values {
             log::trace!(
@@ -132,7 +265,7 @@ pub fn run(
                 key,
                 variable.key(environment),
                 confidence,
-                &value
+                secrets::redact(&value.to_string())
             );
         }
     }
@@ -149,3 +282,37 @@ pub fn run(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::var::C_HIGH;
+
+    fn output_keys(environment: &Environment) -> Vec<Key> {
+        output_values(environment)
+            .iter()
+            .map(|(key, _variable, _value)| *key)
+            .collect()
+    }
+
+    #[test]
+    fn test_output_values_reproducible() {
+        let mut environment = Environment::stub();
+        environment.settings.required_keys.clear();
+        for key in [Key::BuildArch, Key::BuildOs, Key::Name] {
+            environment
+                .output
+                .add(key, 0, C_HIGH, Value::Text("x".to_owned()));
+        }
+        assert_eq!(
+            output_keys(&environment),
+            vec![Key::BuildArch, Key::BuildOs, Key::Name]
+        );
+        environment.settings.reproducible = true;
+        assert_eq!(output_keys(&environment), vec![Key::Name]);
+        environment.settings.required_keys.insert(Key::BuildOs);
+        assert_eq!(output_keys(&environment), vec![Key::BuildOs, Key::Name]);
+        environment.settings.only_required = true;
+        assert_eq!(output_keys(&environment), vec![Key::BuildOs]);
+    }
+}
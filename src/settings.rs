@@ -3,15 +3,20 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use lazy_static::lazy_static;
-use std::{collections::HashSet, path::PathBuf};
+use regex::Regex;
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
 use strum::IntoEnumIterator;
 use strum_macros::{EnumIter, EnumString, EnumVariantNames, IntoStaticStr};
 use url::Url;
 
 use crate::{
-    constants,
+    constants, date,
     tools::git_hosting_provs::{HostingType, PublicSite},
     var::Key,
+    version,
 };
 
 #[derive(
@@ -121,14 +126,41 @@ pub struct Settings /*<S: ::std::hash::BuildHasher>*/ {
     pub repo_path: Option<PathBuf>,
     pub required_keys: HashSet<Key>,
     pub overwrite: Overwrite,
-    pub date_format: String,
+    /// The format for dates without a format of their own
+    /// (see [`Self::key_date_formats`])
+    pub date_format: date::Format,
+    /// Date formats for specific keys
+    pub key_date_formats: HashMap<Key, date::Format>,
+    pub timezone: date::Timezone,
     pub fail_on: FailOn,
     // vars: Box<HashMap<String, String, S>>,
     // #[builder(default = false)]
     // fail_on_missing: bool,
     pub show_retrieved: ShowRetrieved,
     pub hosting_type: HostingType,
+    /// The path under which a self-hosted forge is installed on its host,
+    /// for example "/gitlab" for "https://example.org/gitlab/group/project".
+    /// It is always stored with a leading and without a trailing '/'.
+    pub hosting_root_path: Option<String>,
+    /// The domain under which a self-hosted GitLab instance
+    /// serves its pages, for example "pages.example.org".
+    /// If `None`, "pages." followed by the instance host is assumed.
+    pub pages_domain: Option<String>,
     pub only_required: bool,
+    /// Whether to produce the same output on every host and every run
+    /// for the same commit, see <https://reproducible-builds.org/>.
+    pub reproducible: bool,
+    /// Whether to keep the "v" prefix of versions like "v1.2.3",
+    /// instead of stripping it.
+    pub keep_version_prefix: bool,
+    /// The scheme the project versions follow,
+    /// which is used to validate the version
+    pub version_scheme: version::Scheme,
+    /// Which tags denote releases, when computing the next version;
+    /// the named capture group "version" (if any) holds the version.
+    /// If `None`, all tags that are semantic versions
+    /// (optionally with a "v" prefix) denote releases.
+    pub release_tag_pattern: Option<Regex>,
     pub key_prefix: Option<String>,
     pub verbosity: (Verbosity, Verbosity),
 }
@@ -141,16 +173,30 @@ impl Settings {
             repo_path: None,
             required_keys: all_keys,
             overwrite: Overwrite::All,
-            date_format: crate::tools::git::DATE_FORMAT.to_string(),
+            date_format: date::Format::default(),
+            key_date_formats: HashMap::new(),
+            timezone: date::Timezone::default(),
             fail_on: FailOn::AnyMissingValue,
             show_retrieved: ShowRetrieved::No,
             hosting_type: HostingType::Unknown,
+            hosting_root_path: None,
+            pages_domain: None,
             only_required: false,
+            reproducible: false,
+            keep_version_prefix: false,
+            version_scheme: version::Scheme::default(),
+            release_tag_pattern: None,
             key_prefix: Some(constants::DEFAULT_KEY_PREFIX.to_owned()),
             verbosity: (Verbosity::None, Verbosity::None),
         }
     }
 
+    /// Returns the format to use for dates of the given key.
+    #[must_use]
+    pub fn date_format(&self, key: Key) -> &date::Format {
+        self.key_date_formats.get(&key).unwrap_or(&self.date_format)
+    }
+
     #[must_use]
     pub fn hosting_type(&self, url: &Url) -> HostingType {
         if let HostingType::Unknown = self.hosting_type {
@@ -0,0 +1,369 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::date::{Format, Preset};
+use crate::environment::Environment;
+use crate::sources::deriver;
+use crate::tools::git::{Commit, Repo};
+use crate::value::Value;
+use crate::value_conversions::{self, SHORT_SHA_LEN};
+use crate::var::Key;
+use crate::version::{self, Conventional};
+use crate::{storage, BoxResult};
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// The sections of the release notes, in order,
+/// by Conventional Commit type.
+const SECTIONS: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance Improvements"),
+    ("revert", "Reverts"),
+    ("docs", "Documentation"),
+    ("refactor", "Code Refactoring"),
+    ("test", "Tests"),
+    ("build", "Build System"),
+    ("ci", "Continuous Integration"),
+    ("style", "Styles"),
+    ("chore", "Chores"),
+];
+/// The section for commits of unknown types,
+/// and those not following the Conventional Commits specification.
+const SECTION_OTHER: &str = "Other Changes";
+
+/// Writes Markdown release notes for the commits
+/// since the last release tag (see [`crate::settings::Settings::release_tag_pattern`])
+/// before HEAD,
+/// grouped by Conventional Commit type,
+/// with links to the commits and to a comparison with the last release,
+/// as far as the hosting type of the repo supports it.
+pub struct VarSink {
+    pub file: PathBuf,
+    /// Whether to insert the release notes as a new section
+    /// at the top of the file (below its title, if any),
+    /// instead of overwriting it.
+    pub prepend: bool,
+}
+
+fn find<'a>(values: &'a [storage::Entry], key: Key) -> Option<&'a Value> {
+    values
+        .iter()
+        .find(|(entry_key, _var, _value)| *entry_key == key)
+        .map(|(_key, _var, (_confidence, value))| value)
+}
+
+fn render(environment: &Environment, values: &[storage::Entry], key: Key) -> Option<String> {
+    find(values, key).map(|value| value.render(&environment.settings, key))
+}
+
+fn entry(environment: &Environment, values: &[storage::Entry], commit: &Commit) -> String {
+    let subject = commit.message.lines().next().unwrap_or_default();
+    let text = match Conventional::parse(&commit.message) {
+        Some(conventional) => {
+            let mut text = String::new();
+            if conventional.breaking {
+                text.push_str("**BREAKING** ");
+            }
+            if let Some(scope) = conventional.scope {
+                text.push_str(&format!("**{}:** ", scope));
+            }
+            text.push_str(conventional.description);
+            text
+        }
+        None => subject.to_owned(),
+    };
+    let short_sha = commit.sha.get(..SHORT_SHA_LEN).unwrap_or(&commit.sha);
+    match render(environment, values, Key::RepoCommitPrefixUrl) {
+        Some(prefix) => {
+            let hosting_type = deriver::hosting_type(environment, &prefix);
+            let commit_url = value_conversions::commit_url(hosting_type, &prefix, &commit.sha);
+            format!("- {} ([{}]({}))\n", text, short_sha, commit_url)
+        }
+        None => format!("- {} ({})\n", text, short_sha),
+    }
+}
+
+/// Creates the heading of the release notes,
+/// linking to a comparison with the previous release, if possible.
+fn heading(
+    environment: &Environment,
+    values: &[storage::Entry],
+    previous_tag: Option<&str>,
+) -> BoxResult<String> {
+    let version = render(environment, values, Key::VersionNext)
+        .or_else(|| render(environment, values, Key::Version))
+        .unwrap_or_else(|| "Unreleased".to_owned());
+    let to = render(environment, values, Key::BuildTag)
+        .or_else(|| render(environment, values, Key::CommitSha))
+        .unwrap_or_else(|| "HEAD".to_owned());
+    let compare_url = match (previous_tag, render(environment, values, Key::RepoWebUrl)) {
+        (Some(from), Some(web_url)) => {
+            value_conversions::web_url_to_compare_url(environment, &web_url, from, &to)?
+        }
+        _ => None,
+    };
+    let mut heading = match compare_url {
+        Some(compare_url) => format!("## [{}]({})", version, compare_url),
+        None => format!("## {}", version),
+    };
+    if let Some(Value::Date(date)) = find(values, Key::VersionDate) {
+        heading.push_str(" - ");
+        heading.push_str(
+            &Format::Preset(Preset::DateOnly).format(date, environment.settings.timezone),
+        );
+    }
+    heading.push('\n');
+    Ok(heading)
+}
+
+/// Generates the release notes for the commits since the last release.
+fn release_notes(environment: &Environment, values: &[storage::Entry]) -> BoxResult<String> {
+    let repo = Repo::try_from(environment.settings.repo_path.as_deref())?;
+    let pattern = environment.settings.release_tag_pattern.as_ref();
+    let is_release_tag = |tag: &str| version::release_version(pattern, tag).is_some();
+    let (tags, commits) = repo.commits_since_release(&is_release_tag)?;
+    let previous_tag = tags
+        .iter()
+        .max_by_key(|tag| version::release_version(pattern, tag));
+
+    let mut sections: Vec<(&str, String)> = SECTIONS
+        .iter()
+        .map(|(_kind, title)| *title)
+        .chain(std::iter::once(SECTION_OTHER))
+        .map(|title| (title, String::new()))
+        .collect();
+    for commit in commits.iter().filter(|commit| !commit.merge) {
+        let index = Conventional::parse(&commit.message)
+            .and_then(|conventional| {
+                SECTIONS
+                    .iter()
+                    .position(|(kind, _title)| *kind == conventional.kind)
+            })
+            .unwrap_or(SECTIONS.len());
+        sections[index]
+            .1
+            .push_str(&entry(environment, values, commit));
+    }
+
+    let mut notes = heading(environment, values, previous_tag.map(String::as_str))?;
+    for (title, entries) in sections {
+        if !entries.is_empty() {
+            notes.push_str(&format!("\n### {}\n\n{}", title, entries));
+        }
+    }
+    Ok(notes)
+}
+
+/// Inserts `section` below the title of `changelog`, if it has one,
+/// or else at the top.
+fn prepend(changelog: &str, section: &str) -> String {
+    let mut title_len = 0;
+    let mut lines = changelog.split_inclusive('\n').peekable();
+    if let Some(first) = lines.peek() {
+        if first.starts_with("# ") {
+            title_len += first.len();
+            lines.next();
+            // Keep any introductory text with the title
+            while let Some(line) = lines.peek() {
+                if line.starts_with("## ") {
+                    break;
+                }
+                title_len += line.len();
+                lines.next();
+            }
+        }
+    }
+    let (title, rest) = changelog.split_at(title_len);
+    let mut result = String::with_capacity(changelog.len() + section.len() + 2);
+    result.push_str(title);
+    if !title.is_empty() && !title.ends_with("\n\n") {
+        result.push('\n');
+    }
+    result.push_str(section);
+    if !rest.is_empty() {
+        result.push('\n');
+        result.push_str(rest);
+    }
+    result
+}
+
+impl super::VarSink for VarSink {
+    fn is_usable(&self, _environment: &Environment) -> bool {
+        true
+    }
+
+    fn store(&self, environment: &Environment, values: &[storage::Entry]) -> BoxResult<()> {
+        let notes = release_notes(environment, values)?;
+        let content = if self.prepend && self.file.exists() {
+            prepend(&fs::read_to_string(&self.file)?, &notes)
+        } else {
+            notes
+        };
+        fs::write(&self.file, content)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for VarSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}(file: {}, prepend: {})",
+            std::any::type_name::<VarSink>(),
+            self.file.as_path().to_str().ok_or(fmt::Error {})?,
+            self.prepend
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::git::tests::{commit, tag, test_repo, workdir};
+    use crate::var::C_HIGH;
+
+    fn add(environment: &mut Environment, key: Key, value: &str) {
+        let value = Value::parse(environment, key, value.to_owned());
+        environment.output.add(key, 0, C_HIGH, value);
+    }
+
+    #[test]
+    fn test_heading() {
+        let mut environment = Environment::stub();
+        let values = environment.output.get_wrapup();
+        assert_eq!(
+            heading(&environment, &values, Some("v1.0.0")).unwrap(),
+            "## Unreleased\n"
+        );
+
+        add(&mut environment, Key::VersionNext, "1.1.0");
+        add(
+            &mut environment,
+            Key::RepoWebUrl,
+            "https://github.com/hoijui/projvar",
+        );
+        add(&mut environment, Key::BuildTag, "v1.1.0");
+        let values = environment.output.get_wrapup();
+        assert_eq!(heading(&environment, &values, None).unwrap(), "## 1.1.0\n");
+        assert_eq!(
+            heading(&environment, &values, Some("v1.0.0")).unwrap(),
+            "## [1.1.0](https://github.com/hoijui/projvar/compare/v1.0.0...v1.1.0)\n"
+        );
+
+        add(
+            &mut environment,
+            Key::VersionDate,
+            "2022-03-04T12:00:00+00:00",
+        );
+        let values = environment.output.get_wrapup();
+        assert_eq!(
+            heading(&environment, &values, None).unwrap(),
+            "## 1.1.0 - 2022-03-04\n"
+        );
+    }
+
+    #[test]
+    fn test_release_notes() {
+        let repo = test_repo("release-notes");
+        commit(&repo, "a.txt", "a\n", "Initial commit", 1_600_000_000);
+        tag(&repo, "v1.0.0");
+        let feat = commit(&repo, "b.txt", "b\n", "feat(cli): Add X", 1_600_000_100);
+        let fix = commit(&repo, "c.txt", "c\n", "fix: Fix Y\n\nBody\n", 1_600_000_200);
+        let other = commit(&repo, "d.txt", "d\n", "Update Z", 1_600_000_300);
+        let breaking = commit(&repo, "e.txt", "e\n", "feat!: Drop W", 1_600_000_400);
+        tag(&repo, "v1.1.0");
+
+        let mut environment = Environment::stub();
+        environment.settings.repo_path = Some(workdir(&repo));
+        add(&mut environment, Key::VersionNext, "1.1.0");
+        add(
+            &mut environment,
+            Key::RepoWebUrl,
+            "https://gitlab.com/hoijui/projvar",
+        );
+        let values = environment.output.get_wrapup();
+        let short = |oid: git2::Oid| oid.to_string()[..SHORT_SHA_LEN].to_owned();
+        assert_eq!(
+            release_notes(&environment, &values).unwrap(),
+            format!(
+                "## [1.1.0](https://gitlab.com/hoijui/projvar/-/compare/v1.0.0...HEAD)\n\
+                \n\
+                ### Features\n\
+                \n\
+                - **BREAKING** Drop W ({})\n\
+                - **cli:** Add X ({})\n\
+                \n\
+                ### Bug Fixes\n\
+                \n\
+                - Fix Y ({})\n\
+                \n\
+                ### Other Changes\n\
+                \n\
+                - Update Z ({})\n",
+                short(breaking),
+                short(feat),
+                short(fix),
+                short(other)
+            )
+        );
+
+        // Girocco commit URLs are not separated from their prefix by a '/'
+        let repo = test_repo("release-notes-girocco");
+        commit(&repo, "a.txt", "a\n", "Initial commit", 1_600_000_000);
+        tag(&repo, "v1.0.0");
+        let fix = commit(&repo, "b.txt", "b\n", "fix: Fix Y", 1_600_000_100);
+
+        let mut environment = Environment::stub();
+        environment.settings.repo_path = Some(workdir(&repo));
+        add(&mut environment, Key::VersionNext, "1.0.1");
+        add(
+            &mut environment,
+            Key::RepoWebUrl,
+            "https://repo.or.cz/girocco.git",
+        );
+        add(
+            &mut environment,
+            Key::RepoCommitPrefixUrl,
+            "https://repo.or.cz/?p=girocco.git;a=commit;h=",
+        );
+        let values = environment.output.get_wrapup();
+        assert_eq!(
+            release_notes(&environment, &values).unwrap(),
+            format!(
+                "## 1.0.1\n\
+                \n\
+                ### Bug Fixes\n\
+                \n\
+                - Fix Y ([{}](https://repo.or.cz/?p=girocco.git;a=commit;h={}))\n",
+                short(fix),
+                fix
+            )
+        );
+    }
+
+    #[test]
+    fn test_prepend() {
+        let section = "## 1.0.0\n\n- Add X\n";
+        assert_eq!(prepend("", section), section);
+        assert_eq!(
+            prepend("## 0.9.0\n\n- Fix Y\n", section),
+            "## 1.0.0\n\n- Add X\n\n## 0.9.0\n\n- Fix Y\n"
+        );
+        assert_eq!(
+            prepend("# Changelog\n\nAll changes.\n\n## 0.9.0\n", section),
+            "# Changelog\n\nAll changes.\n\n## 1.0.0\n\n- Add X\n\n## 0.9.0\n"
+        );
+        assert_eq!(
+            prepend("# Changelog\n## 0.9.0\n", section),
+            "# Changelog\n\n## 1.0.0\n\n- Add X\n\n## 0.9.0\n"
+        );
+        assert_eq!(
+            prepend("# Changelog\n", section),
+            "# Changelog\n\n## 1.0.0\n\n- Add X\n"
+        );
+    }
+}
@@ -14,11 +14,11 @@ impl super::VarSink for VarSink {
         true
     }
 
-    fn store(&self, environment: &Environment, values: &[storage::Value]) -> BoxResult<()> {
-        for (_key, var, (_confidence, value)) in values {
-            let key = var.key(environment);
-            if environment.settings.overwrite.main() || env::var(&*key).is_err() {
-                env::set_var(&*key, value);
+    fn store(&self, environment: &Environment, values: &[storage::Entry]) -> BoxResult<()> {
+        for (key, var, (_confidence, value)) in values {
+            let var_key = var.key(environment);
+            if environment.settings.overwrite.main() || env::var(&*var_key).is_err() {
+                env::set_var(&*var_key, value.render(&environment.settings, *key));
             }
         }
         Ok(())
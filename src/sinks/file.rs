@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::environment::Environment;
-use crate::var::{self, Confidence};
+use crate::var;
 use crate::{storage, BoxResult};
 use std::borrow::Cow;
 use std::collections::HashMap;
@@ -17,6 +17,22 @@ pub struct VarSink {
     pub file: PathBuf,
 }
 
+/// Quotes a value for a BASH compatible file,
+/// escaping the characters that are special within double quotes.
+#[must_use]
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for chr in value.chars() {
+        if matches!(chr, '"' | '$' | '\\' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(chr);
+    }
+    quoted.push('"');
+    quoted
+}
+
 /// Stores evaluated values (output) into a file
 /// in a BASH compatible way ("KEY=VALUE\n").
 impl super::VarSink for VarSink {
@@ -24,7 +40,7 @@ impl super::VarSink for VarSink {
         true
     }
 
-    fn store(&self, environment: &Environment, values: &[storage::Value]) -> BoxResult<()> {
+    fn store(&self, environment: &Environment, values: &[storage::Entry]) -> BoxResult<()> {
         let previous_vars = if self.file.exists() {
             var::parse_vars_file_reader(repvar::tools::create_input_reader(self.file.to_str())?)?
         } else {
@@ -33,14 +49,19 @@ impl super::VarSink for VarSink {
 
         let file = File::create(self.file.as_path())?;
         let mut file = LineWriter::new(file);
-        let mut output_values: Vec<(Cow<str>, &&(Confidence, String))> = values
+        let mut output_values: Vec<(Cow<str>, String)> = values
             .iter()
-            .map(|(_key, var, rated_value)| (var.key(environment), rated_value))
+            .map(|(key, var, (_confidence, value))| {
+                (
+                    var.key(environment),
+                    value.render(&environment.settings, *key),
+                )
+            })
             .collect();
         output_values.sort();
-        for (key, rated_value) in output_values {
+        for (key, value) in output_values {
             if environment.settings.overwrite.main() || previous_vars.contains_key(key.as_ref()) {
-                file.write_fmt(format_args!("{}=\"{}\"\n", key, rated_value.1))?;
+                file.write_fmt(format_args!("{}={}\n", key, quote(&value)))?;
             }
         }
         Ok(())
@@ -57,3 +78,18 @@ impl fmt::Display for VarSink {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("1.2.3"), r#""1.2.3""#);
+        assert_eq!(quote(""), r#""""#);
+        assert_eq!(
+            quote(r#"Say "hi" to $USER, `id` or C:\"#),
+            r#""Say \"hi\" to \$USER, \`id\` or C:\\""#
+        );
+    }
+}
@@ -0,0 +1,265 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::environment::Environment;
+use crate::license;
+use crate::tools::git::Repo;
+use crate::tools::git_hosting_provs::HostingType;
+use crate::value::Value;
+use crate::value_conversions;
+use crate::var::Key;
+use crate::{storage, BoxResult};
+use serde_json::Map;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const KEY_SRC_FILE_PATH: &str = "SRC_FILE_PATH";
+pub const KEY_SRC_FILE_COMMIT_SHA: &str = "SRC_FILE_COMMIT_SHA";
+pub const KEY_SRC_FILE_COMMIT_DATE: &str = "SRC_FILE_COMMIT_DATE";
+pub const KEY_SRC_FILE_VERSIONED_URL: &str = "SRC_FILE_VERSIONED_URL";
+pub const KEY_SRC_FILE_RAW_URL: &str = "SRC_FILE_RAW_URL";
+pub const KEY_SRC_FILE_LICENSE: &str = "SRC_FILE_LICENSE";
+pub const KEY_SRC_FILE_COPYRIGHT: &str = "SRC_FILE_COPYRIGHT";
+
+/// How to write the values of the files.
+#[derive(Debug, Clone)]
+pub enum Target {
+    /// A single JSON file,
+    /// containing an object with one object of values per repo-relative file path
+    Json(PathBuf),
+    /// A directory, containing one file per source file,
+    /// at its repo-relative path with ".env" appended,
+    /// in a BASH compatible way ("KEY=VALUE\n")
+    EnvDir(PathBuf),
+}
+
+/// Writes values specific to single files of the project,
+/// like the repo-relative path, the last commit that changed it,
+/// a permalink to it and its license,
+/// as found in its REUSE/SPDX header.
+pub struct VarSink {
+    pub paths: Vec<PathBuf>,
+    pub target: Target,
+}
+
+type FileValues = Vec<(&'static str, Value)>;
+
+fn find<'a>(values: &'a [storage::Entry], key: Key) -> Option<&'a Value> {
+    values
+        .iter()
+        .find(|(entry_key, _var, _value)| *entry_key == key)
+        .map(|(_key, _var, (_confidence, value))| value)
+}
+
+/// Returns `path` relative to the repo root, with '/' as separator.
+fn repo_relative(repo_root: &Path, path: &Path) -> BoxResult<String> {
+    let abs_path = path
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve file path '{}': {}", path.display(), err))?;
+    let rel_path = abs_path.strip_prefix(repo_root).map_err(|_err| {
+        format!(
+            "File '{}' is not inside the repo at '{}'",
+            path.display(),
+            repo_root.display()
+        )
+    })?;
+    Ok(rel_path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
+
+fn versioned_url(
+    environment: &Environment,
+    values: &[storage::Entry],
+    prefix_key: Key,
+    sha: &str,
+    rel_path: &str,
+) -> Option<Value> {
+    let hosting_type = match find(values, Key::RepoWebUrl) {
+        Some(Value::Url(web_url)) => environment.settings.hosting_type(web_url),
+        _ => HostingType::Unknown,
+    };
+    find(values, prefix_key).map(|prefix| {
+        Value::Text(value_conversions::versioned_url(
+            hosting_type,
+            &prefix.to_string(),
+            sha,
+            rel_path,
+            prefix_key == Key::RepoRawVersionedPrefixUrl,
+        ))
+    })
+}
+
+/// Gathers the values of a single file.
+fn file_values(
+    environment: &Environment,
+    values: &[storage::Entry],
+    repo: &Repo,
+    path: &Path,
+) -> BoxResult<(String, FileValues)> {
+    let rel_path = repo_relative(&repo.local_path(), path)?;
+    let mut file_values = vec![(KEY_SRC_FILE_PATH, Value::Text(rel_path.clone()))];
+
+    if let Some((sha, date)) = repo.last_commit_touching(Path::new(&rel_path))? {
+        for (key, prefix_key) in [
+            (KEY_SRC_FILE_VERSIONED_URL, Key::RepoVersionedFilePrefixUrl),
+            (KEY_SRC_FILE_RAW_URL, Key::RepoRawVersionedPrefixUrl),
+        ] {
+            if let Some(url) = versioned_url(environment, values, prefix_key, &sha, &rel_path) {
+                file_values.push((key, url));
+            }
+        }
+        file_values.push((KEY_SRC_FILE_COMMIT_SHA, Value::Text(sha)));
+        file_values.push((KEY_SRC_FILE_COMMIT_DATE, Value::Date(date)));
+    } else {
+        log::warn!(
+            "File '{}' was never committed, so it has no permalink",
+            rel_path
+        );
+    }
+
+    let header = license::read_file_header(path)?;
+    if let Some(license) = header.license {
+        file_values.push((KEY_SRC_FILE_LICENSE, Value::Text(license)));
+    }
+    if !header.copyright.is_empty() {
+        file_values.push((KEY_SRC_FILE_COPYRIGHT, Value::List(header.copyright)));
+    }
+
+    file_values.sort_by_key(|(key, _value)| *key);
+    Ok((rel_path, file_values))
+}
+
+fn var_key(environment: &Environment, key: &str) -> String {
+    match &environment.settings.key_prefix {
+        Some(prefix) => prefix.clone() + key,
+        None => key.to_owned(),
+    }
+}
+
+fn render(environment: &Environment, value: &Value) -> String {
+    match value {
+        Value::Date(date) => environment
+            .settings
+            .date_format
+            .format(date, environment.settings.timezone),
+        _ => value.to_string(),
+    }
+}
+
+impl super::VarSink for VarSink {
+    fn is_usable(&self, _environment: &Environment) -> bool {
+        true
+    }
+
+    fn store(&self, environment: &Environment, values: &[storage::Entry]) -> BoxResult<()> {
+        let repo = Repo::try_from(environment.settings.repo_path.as_deref())?;
+        let mut files = vec![];
+        for path in &self.paths {
+            files.push(file_values(environment, values, &repo, path)?);
+        }
+
+        match &self.target {
+            Target::Json(file) => {
+                let mut object = Map::new();
+                for (rel_path, file_values) in files {
+                    let mut file_object = Map::new();
+                    for (key, value) in file_values {
+                        file_object.insert(
+                            var_key(environment, key),
+                            value.to_json(environment.settings.timezone),
+                        );
+                    }
+                    object.insert(rel_path, serde_json::Value::Object(file_object));
+                }
+                fs::write(file, format!("{:#}\n", serde_json::Value::Object(object)))?;
+            }
+            Target::EnvDir(dir) => {
+                for (rel_path, file_values) in files {
+                    let env_file = dir.join(format!("{}.env", rel_path));
+                    if let Some(parent) = env_file.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    let mut content = String::new();
+                    for (key, value) in file_values {
+                        content.push_str(&format!(
+                            "{}={}\n",
+                            var_key(environment, key),
+                            super::file::quote(&render(environment, &value))
+                        ));
+                    }
+                    fs::write(env_file, content)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for VarSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}(paths: {:?}, target: {:?})",
+            std::any::type_name::<VarSink>(),
+            self.paths,
+            self.target
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sinks::VarSink as _;
+    use crate::tools::git::tests::{commit, test_repo, workdir};
+
+    #[test]
+    fn test_repo_relative() {
+        let repo = test_repo("repo-relative");
+        let root = repo.local_path();
+        fs::create_dir_all(root.join("src/sub")).unwrap();
+        fs::write(root.join("src/sub/main.rs"), "").unwrap();
+        assert_eq!(
+            repo_relative(&root, &root.join("src/sub/main.rs")).unwrap(),
+            "src/sub/main.rs"
+        );
+        assert_eq!(
+            repo_relative(&root, &root.join("src/sub/../sub/main.rs")).unwrap(),
+            "src/sub/main.rs"
+        );
+        assert!(repo_relative(&root, &root.join("missing.rs")).is_err());
+        assert!(repo_relative(&root, &std::env::temp_dir()).is_err());
+    }
+
+    #[test]
+    fn test_store_env_dir() {
+        let repo = test_repo("files-env-dir");
+        commit(
+            &repo,
+            "src/main.rs",
+            "// SPDX-FileCopyrightText: 2022 \"Jane\" $USER\n\
+            // SPDX-License-Identifier: MIT\n",
+            "Add main",
+            1_600_000_000,
+        );
+        let root = workdir(&repo);
+        let out_dir = root.join("target/files");
+        let mut environment = Environment::stub();
+        environment.settings.repo_path = Some(root.clone());
+        let sink = VarSink {
+            paths: vec![root.join("src/main.rs")],
+            target: Target::EnvDir(out_dir.clone()),
+        };
+        sink.store(&environment, &[]).unwrap();
+        let content = fs::read_to_string(out_dir.join("src/main.rs.env")).unwrap();
+        assert!(content.contains("PROJECT_SRC_FILE_PATH=\"src/main.rs\"\n"));
+        assert!(content.contains("PROJECT_SRC_FILE_LICENSE=\"MIT\"\n"));
+        assert!(content.contains("PROJECT_SRC_FILE_COPYRIGHT=\"2022 \\\"Jane\\\" \\$USER\"\n"));
+    }
+}
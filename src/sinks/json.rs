@@ -0,0 +1,46 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::environment::Environment;
+use crate::{storage, BoxResult};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// Writes all the values into a JSON object,
+/// with lists as arrays, booleans as such,
+/// and dates in RFC 3339 format (ignoring the date format setting).
+pub struct VarSink {
+    pub file: PathBuf,
+}
+
+impl super::VarSink for VarSink {
+    fn is_usable(&self, _environment: &Environment) -> bool {
+        true
+    }
+
+    fn store(&self, environment: &Environment, values: &[storage::Entry]) -> BoxResult<()> {
+        let mut object = Map::new();
+        for (_key, var, (_confidence, value)) in values {
+            object.insert(
+                var.key(environment).into_owned(),
+                value.to_json(environment.settings.timezone),
+            );
+        }
+        fs::write(&self.file, format!("{:#}\n", Value::Object(object)))?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for VarSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}(file: {})",
+            std::any::type_name::<VarSink>(),
+            self.file.as_path().to_str().ok_or(fmt::Error {})?
+        )
+    }
+}
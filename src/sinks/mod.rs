@@ -2,8 +2,12 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+pub mod changelog;
 pub mod env;
 pub mod file;
+pub mod files;
+pub mod json;
+pub mod oci_labels;
 
 use std::fmt;
 use std::path::PathBuf;
@@ -31,37 +35,86 @@ pub trait VarSink: fmt::Display {
     fn store(
         &self,
         environment: &Environment,
-        values: &[storage::Value],
+        values: &[storage::Entry],
         // values: Box<dyn Iterator<Item = (Key, &Variable, &(Confidence, String))>>,
     ) -> BoxResult<()>;
 }
 
+/// Which sinks to use when using this crate as CLI tool,
+/// see [`cli_list`].
+#[derive(Debug, Default)]
+pub struct CliSinkOptions {
+    /// Whether to write the values directly into the environment
+    pub env_out: bool,
+    /// Whether to not store the values anywhere
+    pub dry: bool,
+    /// Whether to write the values to [`DEFAULT_FILE_OUT`]
+    pub default_out_file: bool,
+    /// Files to write the values to, BASH style ("KEY=VALUE\n")
+    pub additional_out_files: Vec<PathBuf>,
+    /// Files to write the values to, as a single JSON object
+    pub json_out_files: Vec<PathBuf>,
+    /// Files to write the release notes to
+    pub release_notes_files: Vec<PathBuf>,
+    /// Changelog files to prepend the release notes to
+    pub changelog_files: Vec<PathBuf>,
+    /// Files to write the standard OCI image labels to, each in its format
+    pub oci_labels_files: Vec<(PathBuf, oci_labels::Format)>,
+    /// The files to evaluate the file specific values for,
+    /// and where to write those to (see [`files`])
+    pub files: Option<(Vec<PathBuf>, Vec<files::Target>)>,
+}
+
 /// Returns a list of sinks commonly used when using this crate as CLI tool
 ///
 /// # Panics
 ///
 /// if [`DEFAULT_FILE_OUT`] fails to be parsed as a valid file-system path
 #[must_use]
-pub fn cli_list(
-    env_out: bool,
-    dry: bool,
-    default_out_file: bool,
-    additional_out_files: Vec<PathBuf>,
-) -> Vec<Box<dyn VarSink>> {
+pub fn cli_list(options: CliSinkOptions) -> Vec<Box<dyn VarSink>> {
     let mut sinks: Vec<Box<dyn VarSink>> = vec![];
-    if env_out {
+    if options.env_out {
         sinks.push(Box::new(env::VarSink {}));
     }
-    if default_out_file {
+    if options.default_out_file {
         log::info!("Using the default out file: {}", DEFAULT_FILE_OUT);
         sinks.push(Box::new(file::VarSink {
             file: PathBuf::from_str(DEFAULT_FILE_OUT).unwrap(),
         }));
     }
-    for out_file in additional_out_files {
+    for out_file in options.additional_out_files {
         sinks.push(Box::new(file::VarSink { file: out_file }));
     }
-    if dry {
+    for out_file in options.json_out_files {
+        sinks.push(Box::new(json::VarSink { file: out_file }));
+    }
+    for out_file in options.release_notes_files {
+        sinks.push(Box::new(changelog::VarSink {
+            file: out_file,
+            prepend: false,
+        }));
+    }
+    for out_file in options.changelog_files {
+        sinks.push(Box::new(changelog::VarSink {
+            file: out_file,
+            prepend: true,
+        }));
+    }
+    for (out_file, format) in options.oci_labels_files {
+        sinks.push(Box::new(oci_labels::VarSink {
+            file: out_file,
+            format,
+        }));
+    }
+    if let Some((paths, targets)) = options.files {
+        for target in targets {
+            sinks.push(Box::new(files::VarSink {
+                paths: paths.clone(),
+                target,
+            }));
+        }
+    }
+    if options.dry {
         sinks.clear();
     } else if sinks.is_empty() {
         log::warn!("No sinks registered! The results of this run will not be stored anywhere.");
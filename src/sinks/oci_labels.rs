@@ -0,0 +1,217 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::environment::Environment;
+use crate::value::Value;
+use crate::var::{Key, Variable};
+use crate::{storage, BoxResult};
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+/// The standard OCI image annotations we write,
+/// each with the keys to take the value from, in order of preference.
+/// See <https://github.com/opencontainers/image-spec/blob/main/annotations.md>.
+/// The licenses are an SPDX expression,
+/// which is how [`Value::Licenses`] renders.
+const LABELS: &[(&str, &[Key])] = &[
+    ("org.opencontainers.image.created", &[Key::BuildDate]),
+    (
+        "org.opencontainers.image.licenses",
+        &[Key::Licenses, Key::License],
+    ),
+    ("org.opencontainers.image.revision", &[Key::CommitSha]),
+    ("org.opencontainers.image.source", &[Key::RepoWebUrl]),
+    ("org.opencontainers.image.title", &[Key::Name]),
+    (
+        "org.opencontainers.image.url",
+        &[Key::BuildHostingUrl, Key::RepoWebUrl],
+    ),
+    ("org.opencontainers.image.version", &[Key::Version]),
+];
+
+/// How to write the labels.
+/// The argument formats are meant to be read line by line,
+/// each line being one argument as is, without any shell quoting,
+/// for example with `mapfile -t args < FILE; docker build "${args[@]}" .`.
+/// Values spanning multiple lines can not be written like that,
+/// so they are left out in all formats.
+#[derive(Debug, Clone, Copy)]
+pub enum Format {
+    /// One "--label=<label>=<value>" argument per line,
+    /// for `docker build` or `podman build`
+    LabelArgs,
+    /// One "--build-arg=<VARIABLE>=<value>" argument per line,
+    /// for `docker build` or `podman build`,
+    /// using the variable names of the keys the values come from
+    BuildArgs,
+    /// A Dockerfile `LABEL` instruction
+    Dockerfile,
+}
+
+/// Writes the standard OCI image labels
+/// (`org.opencontainers.image.*`),
+/// for the values available.
+pub struct VarSink {
+    pub file: PathBuf,
+    pub format: Format,
+}
+
+fn find<'a>(values: &'a [storage::Entry], keys: &[Key]) -> Option<(&'static Variable, &'a Value)> {
+    keys.iter().find_map(|key| {
+        values
+            .iter()
+            .find(|(entry_key, _var, _value)| entry_key == key)
+            .map(|(_key, var, (_confidence, value))| (*var, value))
+    })
+}
+
+fn render(environment: &Environment, value: &Value) -> String {
+    match value {
+        Value::Date(date) => environment.settings.timezone.apply(date).to_rfc3339(),
+        _ => value.to_string(),
+    }
+}
+
+/// Escapes a value for use in a double-quoted Dockerfile string.
+fn escape_dockerfile(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$")
+}
+
+impl VarSink {
+    /// Renders the labels for the values available, in our format.
+    fn labels(&self, environment: &Environment, values: &[storage::Entry]) -> String {
+        let mut lines = vec![];
+        for (label, keys) in LABELS {
+            if let Some((var, value)) = find(values, keys) {
+                let value = render(environment, value);
+                if value.contains(['\n', '\r']) {
+                    log::warn!(
+                        "Leaving out the OCI label {}, as its value spans multiple lines: {:?}",
+                        label,
+                        value
+                    );
+                    continue;
+                }
+                let line = match self.format {
+                    Format::LabelArgs => format!("--label={}={}", label, value),
+                    Format::BuildArgs => {
+                        format!("--build-arg={}={}", var.key(environment), value)
+                    }
+                    Format::Dockerfile => {
+                        format!("    {}=\"{}\"", label, escape_dockerfile(&value))
+                    }
+                };
+                // Two labels may use the same build argument
+                if !lines.contains(&line) {
+                    lines.push(line);
+                }
+            }
+        }
+        match self.format {
+            Format::LabelArgs | Format::BuildArgs => lines
+                .iter()
+                .map(|line| format!("{}\n", line))
+                .collect::<String>(),
+            Format::Dockerfile if lines.is_empty() => String::new(),
+            Format::Dockerfile => format!("LABEL \\\n{}\n", lines.join(" \\\n")),
+        }
+    }
+}
+
+impl super::VarSink for VarSink {
+    fn is_usable(&self, _environment: &Environment) -> bool {
+        true
+    }
+
+    fn store(&self, environment: &Environment, values: &[storage::Entry]) -> BoxResult<()> {
+        fs::write(&self.file, self.labels(environment, values))?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for VarSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}(file: {}, format: {:?})",
+            std::any::type_name::<VarSink>(),
+            self.file.as_path().to_str().ok_or(fmt::Error {})?,
+            self.format
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::var::C_HIGH;
+
+    fn labels(format: Format, name: &str) -> String {
+        let mut environment = Environment::stub();
+        for (key, value) in [
+            (Key::Licenses, "MIT, Apache-2.0"),
+            (Key::License, "MIT"),
+            (Key::Name, name),
+            (Key::RepoWebUrl, "https://github.com/hoijui/projvar"),
+            (Key::Version, "1.2.3"),
+        ] {
+            let value = Value::parse(&environment, key, value.to_owned());
+            environment.output.add(key, 0, C_HIGH, value);
+        }
+        let sink = VarSink {
+            file: PathBuf::new(),
+            format,
+        };
+        sink.labels(&environment, &environment.output.get_wrapup())
+    }
+
+    #[test]
+    fn test_label_args() {
+        // Each line is one argument as is, so nothing gets quoted
+        assert_eq!(
+            labels(Format::LabelArgs, r#"My "$HOME" \ App"#),
+            "--label=org.opencontainers.image.licenses=MIT AND Apache-2.0\n\
+            --label=org.opencontainers.image.source=https://github.com/hoijui/projvar\n\
+            --label=org.opencontainers.image.title=My \"$HOME\" \\ App\n\
+            --label=org.opencontainers.image.url=https://github.com/hoijui/projvar\n\
+            --label=org.opencontainers.image.version=1.2.3\n"
+        );
+        assert!(!labels(Format::LabelArgs, "My\nApp").contains(".title="));
+    }
+
+    #[test]
+    fn test_build_args() {
+        // The source and url labels share a build argument
+        assert_eq!(
+            labels(Format::BuildArgs, r#"My "$HOME" \ App"#),
+            "--build-arg=PROJECT_LICENSES=MIT AND Apache-2.0\n\
+            --build-arg=PROJECT_REPO_WEB_URL=https://github.com/hoijui/projvar\n\
+            --build-arg=PROJECT_NAME=My \"$HOME\" \\ App\n\
+            --build-arg=PROJECT_VERSION=1.2.3\n"
+        );
+        assert!(!labels(Format::BuildArgs, "My\r\nApp").contains("PROJECT_NAME="));
+    }
+
+    #[test]
+    fn test_dockerfile() {
+        assert_eq!(
+            labels(Format::Dockerfile, r#"My "$HOME" \ App"#),
+            "LABEL \\\n    \
+            org.opencontainers.image.licenses=\"MIT AND Apache-2.0\" \\\n    \
+            org.opencontainers.image.source=\"https://github.com/hoijui/projvar\" \\\n    \
+            org.opencontainers.image.title=\"My \\\"\\$HOME\\\" \\\\ App\" \\\n    \
+            org.opencontainers.image.url=\"https://github.com/hoijui/projvar\" \\\n    \
+            org.opencontainers.image.version=\"1.2.3\"\n"
+        );
+        let sink = VarSink {
+            file: PathBuf::new(),
+            format: Format::Dockerfile,
+        };
+        assert_eq!(sink.labels(&Environment::stub(), &[]), "");
+    }
+}
@@ -7,6 +7,7 @@ use crate::var::Key;
 use crate::var::C_HIGH;
 use crate::var::C_LOW;
 
+use super::inputs;
 use super::var;
 use super::Hierarchy;
 use super::RetrieveRes;
@@ -32,34 +33,133 @@ impl super::VarSource for VarSource {
         &super::NO_PROPS
     }
 
+    #[remain::check]
+    fn supplies(&self, _environment: &Environment, key: Key) -> Option<Vec<String>> {
+        #[remain::sorted]
+        match key {
+            Key::BuildArch
+            | Key::AuthorCount
+            | Key::BuildDate
+            | Key::BuildHostingUrl
+            | Key::BuildOs
+            | Key::BuildOsFamily
+            | Key::CommitCount
+            | Key::CommitShaShort
+            | Key::CommitsSinceTag
+            | Key::ContainerImageName
+            | Key::ContainerImageTag
+            | Key::Custom(_)
+            | Key::Dirty
+            | Key::FirstCommitDate
+            | Key::IsRelease
+            | Key::License
+            | Key::Licenses
+            | Key::NameMachineReadable
+            | Key::PreviousTag
+            | Key::RepoCommitPrefixUrl
+            | Key::RepoCommitUrl
+            | Key::RepoCompareUrl
+            | Key::RepoIssuesUrl
+            | Key::RepoNewMergeRequestUrl
+            | Key::RepoPipelinesUrl
+            | Key::RepoRawUrlBase
+            | Key::RepoRawVersionedPrefixUrl
+            | Key::RepoReleasesUrl
+            | Key::RepoTagUrl
+            | Key::RepoTreeUrl
+            | Key::RepoVersionedDirPrefixUrl
+            | Key::RepoVersionedFilePrefixUrl
+            | Key::RepoVersionedFileUrlBase
+            | Key::RepoWikiUrl
+            | Key::SourceDateEpoch
+            | Key::VersionBump
+            | Key::VersionDate
+            | Key::VersionDebian
+            | Key::VersionMajor
+            | Key::VersionMinor
+            | Key::VersionNext
+            | Key::VersionPatch
+            | Key::VersionPep440
+            | Key::VersionPrerelease => None,
+            Key::BuildBranch => inputs(&["BITBUCKET_BRANCH"]),
+            Key::BuildNumber => inputs(&["BITBUCKET_BUILD_NUMBER"]),
+            Key::BuildTag => inputs(&["BITBUCKET_TAG"]),
+            Key::Ci => inputs(&["CI"]),
+            Key::CommitSha => inputs(&["BITBUCKET_COMMIT"]),
+            Key::Name => inputs(&["BITBUCKET_PROJECT_KEY"]),
+            Key::RepoCloneUrl => inputs(&["BITBUCKET_GIT_HTTP_ORIGIN"]),
+            Key::RepoCloneUrlSsh => inputs(&["BITBUCKET_GIT_SSH_ORIGIN"]),
+            Key::RepoNamespace | Key::RepoOwner => inputs(&["BITBUCKET_WORKSPACE"]),
+            Key::RepoSlug => inputs(&["BITBUCKET_REPO_FULL_NAME"]),
+            Key::RepoWebUrl => inputs(&["BITBUCKET_REPO_FULL_NAME"]),
+            Key::Version => inputs(&["BITBUCKET_COMMIT"]),
+        }
+    }
+
     #[remain::check]
     fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes {
         Ok(
             #[remain::sorted]
             match key {
                 Key::BuildArch
+                | Key::AuthorCount
                 | Key::BuildHostingUrl
                 | Key::BuildDate
                 | Key::BuildOs
                 | Key::BuildOsFamily
+                | Key::CommitCount
+                | Key::CommitShaShort
+                | Key::CommitsSinceTag
+                | Key::ContainerImageName
+                | Key::ContainerImageTag
+                | Key::Custom(_)
+                | Key::Dirty
+                | Key::FirstCommitDate
+                | Key::IsRelease
                 | Key::Licenses
                 | Key::License
+                | Key::PreviousTag
+                | Key::RepoCommitUrl
+                | Key::RepoCompareUrl
+                | Key::RepoNewMergeRequestUrl
+                | Key::RepoPipelinesUrl
+                | Key::RepoRawUrlBase
+                | Key::RepoReleasesUrl
+                | Key::RepoTagUrl
+                | Key::RepoTreeUrl
+                | Key::RepoVersionedFileUrlBase
+                | Key::RepoWikiUrl
+                | Key::SourceDateEpoch
+                | Key::VersionBump
                 | Key::VersionDate
                 | Key::NameMachineReadable
                 | Key::RepoCommitPrefixUrl
                 | Key::RepoIssuesUrl
                 | Key::RepoRawVersionedPrefixUrl
                 | Key::RepoVersionedDirPrefixUrl
-                | Key::RepoVersionedFilePrefixUrl => None,
+                | Key::RepoVersionedFilePrefixUrl
+                | Key::VersionDebian
+                | Key::VersionMajor
+                | Key::VersionMinor
+                | Key::VersionNext
+                | Key::VersionPatch
+                | Key::VersionPep440
+                | Key::VersionPrerelease => None,
                 Key::BuildBranch => var(environment, "BITBUCKET_BRANCH", C_HIGH),
                 Key::BuildNumber => var(environment, "BITBUCKET_BUILD_NUMBER", C_HIGH),
                 Key::BuildTag => var(environment, "BITBUCKET_TAG", C_HIGH),
                 Key::Ci => {
                     var(environment, "CI", C_HIGH).or_else(|| Some((C_LOW, "false".to_owned())))
                 }
+                Key::CommitSha => var(environment, "BITBUCKET_COMMIT", C_HIGH),
                 Key::Name => var(environment, "BITBUCKET_PROJECT_KEY", C_HIGH),
                 Key::RepoCloneUrl => var(environment, "BITBUCKET_GIT_HTTP_ORIGIN", C_HIGH),
                 Key::RepoCloneUrlSsh => var(environment, "BITBUCKET_GIT_SSH_ORIGIN", C_HIGH),
+                // On BitBucket, the namespace always consists of just the workspace
+                Key::RepoNamespace | Key::RepoOwner => {
+                    var(environment, "BITBUCKET_WORKSPACE", C_HIGH)
+                }
+                Key::RepoSlug => var(environment, "BITBUCKET_REPO_FULL_NAME", C_HIGH),
                 Key::RepoWebUrl => {
                     // BITBUCKET_REPO_FULL_NAME = The full name of the repository
                     // (everything that comes after http://bitbucket.org/).
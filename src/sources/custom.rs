@@ -0,0 +1,97 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::fs;
+
+use crate::custom_keys::{self, Source};
+use crate::environment::Environment;
+use crate::var::{Key, C_HIGH, C_LOW};
+
+use super::var;
+use super::Hierarchy;
+use super::RetrieveRes;
+
+/// Sources the values of custom keys (see [`crate::custom_keys`])
+/// as declared in the configuration file,
+/// from static values, input variables and files.
+/// Values of custom keys declared through templates
+/// are generated by [`super::deriver::VarSource`].
+pub struct VarSource;
+
+/// Extracts a value from a file (relative to the project root) through a regex,
+/// using the capture group named "value", or the first one, or the whole match.
+fn from_file(
+    environment: &Environment,
+    file: &std::path::Path,
+    regex: &regex::Regex,
+) -> RetrieveRes {
+    let file = match &environment.settings.repo_path {
+        Some(repo_path) => repo_path.join(file),
+        None => file.to_path_buf(),
+    };
+    if !file.is_file() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&file)?;
+    Ok(regex.captures(&content).and_then(|caps| {
+        caps.name("value")
+            .or_else(|| caps.get(1))
+            .or_else(|| caps.get(0))
+            .map(|mtch| (C_HIGH, mtch.as_str().to_owned()))
+    }))
+}
+
+impl super::VarSource for VarSource {
+    fn is_usable(&self, _environment: &mut Environment) -> bool {
+        !custom_keys::keys().is_empty()
+    }
+
+    fn hierarchy(&self) -> Hierarchy {
+        Hierarchy::High
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<VarSource>()
+    }
+
+    fn properties(&self) -> &Vec<String> {
+        &super::NO_PROPS
+    }
+
+    fn supplies(&self, _environment: &Environment, key: Key) -> Option<Vec<String>> {
+        if let Key::Custom(id) = key {
+            let inputs: Vec<String> = custom_keys::get(id)
+                .sources
+                .iter()
+                .filter_map(|source| match source {
+                    Source::File { file, regex: _ } => Some(format!("file: {}", file.display())),
+                    Source::Template { template: _ } => None,
+                    Source::Var { var } => Some(var.clone()),
+                    Source::Value { value: _ } => Some("static value".to_owned()),
+                })
+                .collect();
+            if !inputs.is_empty() {
+                return Some(inputs);
+            }
+        }
+        None
+    }
+
+    fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes {
+        if let Key::Custom(id) = key {
+            for source in &custom_keys::get(id).sources {
+                let value = match source {
+                    Source::File { file, regex } => from_file(environment, file, regex)?,
+                    Source::Template { template: _ } => None,
+                    Source::Var { var: var_name } => var(environment, var_name, C_HIGH),
+                    Source::Value { value } => Some((C_LOW, value.clone())),
+                };
+                if value.is_some() {
+                    return Ok(value);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
@@ -2,10 +2,15 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::custom_keys;
 use crate::environment::Environment;
+use crate::tools::git_hosting_provs::HostingType;
+use crate::value::Value;
 use crate::value_conversions;
 use crate::value_conversions::Protocol;
-use crate::var::Key;
+use crate::var::{Confidence, Key};
+use crate::version;
+use url::Url;
 
 use super::Hierarchy;
 use super::RetrieveRes;
@@ -30,7 +35,7 @@ macro_rules! conv_val_with_env {
                 .get(Key::$in_key)
                 .and_then(|(confidence, in_val)| {
                     Some(
-                        value_conversions::$conv_fun($environment, in_val, $($extra_arg),*)
+                        value_conversions::$conv_fun($environment, &in_val.to_string(), $($extra_arg),*)
                             .map(|val_opt| val_opt.map(|val| (*confidence, val))),
                     )
                 })
@@ -40,6 +45,619 @@ macro_rules! conv_val_with_env {
 }
 // pub(crate) use conv_val_with_env;
 
+/// Extracts the part for `key` from the semantic version,
+/// if the version is one.
+/// A "git describe" version (for example "1.2.3-4-gabcdef0-dirty")
+/// is split first, and only its tag part is considered;
+/// it is a release only if it denotes exactly a non-pre-release tag.
+fn version_component(environment: &Environment, key: Key) -> Option<(Confidence, String)> {
+    let (confidence, value) = environment.output.get(Key::Version)?;
+    let value = value.to_string();
+    let describe = version::Describe::parse(&value)?;
+    let version = semver::Version::parse(describe.base).ok()?;
+    let component = match key {
+        Key::IsRelease => (describe.is_exact() && version.pre.is_empty()).to_string(),
+        Key::VersionMajor => version.major.to_string(),
+        Key::VersionMinor => version.minor.to_string(),
+        Key::VersionPatch => version.patch.to_string(),
+        Key::VersionPrerelease if !version.pre.is_empty() => version.pre.to_string(),
+        _ => return None,
+    };
+    Some((*confidence, component))
+}
+
+/// Evaluates the hosting type of the repo,
+/// preferably from its web URL, or else from `url`.
+pub(crate) fn hosting_type(environment: &Environment, url: &str) -> HostingType {
+    match environment.output.get(Key::RepoWebUrl) {
+        Some((_confidence, Value::Url(web_url))) => environment.settings.hosting_type(web_url),
+        _ => Url::parse(url).map_or(HostingType::Unknown, |url| {
+            environment.settings.hosting_type(&url)
+        }),
+    }
+}
+
+/// Completes the versioned prefix URL of `prefix_key`
+/// with the tag being built, if any, or else the SHA of the commit being built.
+/// Gitea and Azure DevOps use different URLs for tags than for commits,
+/// and the prefix URLs we derive for them are the ones for commits,
+/// so for those we always use the SHA.
+/// Allura has no raw base URL, as it expects the raw format
+/// to be requested after the file path (see [`value_conversions::versioned_url`]).
+fn pinned_url(environment: &Environment, prefix_key: Key) -> Option<(Confidence, String)> {
+    let (prefix_confidence, prefix) = environment.output.get(prefix_key)?;
+    let prefix = prefix.to_string();
+    let hosting_type = hosting_type(environment, &prefix);
+    if prefix_key == Key::RepoRawVersionedPrefixUrl && hosting_type == HostingType::Allura {
+        return None;
+    }
+    let tag = match hosting_type {
+        HostingType::Gitea | HostingType::AzureDevOps => None,
+        _ => environment.output.get(Key::BuildTag),
+    };
+    let (version_confidence, version) = tag.or_else(|| environment.output.get(Key::CommitSha))?;
+    Some((
+        *prefix_confidence.min(version_confidence),
+        value_conversions::versioned_base_url(hosting_type, &prefix, &version.to_string()),
+    ))
+}
+
+/// Completes the commit prefix URL
+/// with the SHA of the commit being built.
+fn commit_url(environment: &Environment) -> Option<(Confidence, String)> {
+    let (prefix_confidence, prefix) = environment.output.get(Key::RepoCommitPrefixUrl)?;
+    let (sha_confidence, sha) = environment.output.get(Key::CommitSha)?;
+    let prefix = prefix.to_string();
+    Some((
+        *prefix_confidence.min(sha_confidence),
+        value_conversions::commit_url(
+            hosting_type(environment, &prefix),
+            &prefix,
+            &sha.to_string(),
+        ),
+    ))
+}
+
+/// Constructs the web URL of the tag being built, if any.
+fn tag_url(environment: &Environment) -> RetrieveRes {
+    let inputs = environment
+        .output
+        .get(Key::RepoWebUrl)
+        .zip(environment.output.get(Key::BuildTag));
+    Ok(match inputs {
+        Some(((web_url_confidence, web_url), (tag_confidence, tag))) => {
+            value_conversions::web_url_to_tag_url(
+                environment,
+                &web_url.to_string(),
+                &tag.to_string(),
+            )?
+            .map(|url| (*web_url_confidence.min(tag_confidence), url))
+        }
+        None => None,
+    })
+}
+
+/// Constructs the web URL comparing the previous release tag
+/// with the tag being built, if any, or else the SHA of the commit being built.
+fn compare_url(environment: &Environment) -> RetrieveRes {
+    let to = environment
+        .output
+        .get(Key::BuildTag)
+        .or_else(|| environment.output.get(Key::CommitSha));
+    let inputs = environment
+        .output
+        .get(Key::RepoWebUrl)
+        .zip(environment.output.get(Key::PreviousTag))
+        .zip(to);
+    Ok(match inputs {
+        Some((((web_url_confidence, web_url), (from_confidence, from)), (to_confidence, to))) => {
+            value_conversions::web_url_to_compare_url(
+                environment,
+                &web_url.to_string(),
+                &from.to_string(),
+                &to.to_string(),
+            )?
+            .map(|url| {
+                (
+                    *web_url_confidence.min(from_confidence).min(to_confidence),
+                    url,
+                )
+            })
+        }
+        None => None,
+    })
+}
+
+/// Applies the conversion `conv` to the repo web URL,
+/// with its path replaced by the repo slug.
+/// This leaves out any hosting root path
+/// (see [`crate::settings::Settings::hosting_root_path`]),
+/// and prefers the slug as supplied by a CI,
+/// if it is available.
+/// If no slug can be evaluated, the web URL is used as is.
+fn from_slug_web_url(
+    environment: &Environment,
+    conv: fn(&Environment, &str) -> Result<Option<String>, value_conversions::Error>,
+) -> RetrieveRes {
+    let (web_url_confidence, web_url) = match environment.output.get(Key::RepoWebUrl) {
+        Some((confidence, web_url)) => (*confidence, web_url.to_string()),
+        None => return Ok(None),
+    };
+    let slug = match environment.output.get(Key::RepoSlug) {
+        Some((confidence, slug)) => Some((*confidence, slug.to_string())),
+        None => value_conversions::web_url_to_slug(environment, &web_url)
+            .unwrap_or_else(|err| {
+                log::debug!("Using the repo web URL as is, as it has no slug: {}", err);
+                None
+            })
+            .map(|slug| (web_url_confidence, slug)),
+    };
+    let (confidence, slug_web_url) = match (slug, Url::parse(&web_url)) {
+        (Some((slug_confidence, slug)), Ok(mut url)) => {
+            url.set_path(&slug);
+            (web_url_confidence.min(slug_confidence), url.to_string())
+        }
+        _ => (web_url_confidence, web_url),
+    };
+    Ok(conv(environment, &slug_web_url)?.map(|value| (confidence, value)))
+}
+
+/// Fills in the first template source of a custom key,
+/// for which all the referred to values are available.
+fn from_template(environment: &Environment, key: Key) -> Option<(Confidence, String)> {
+    let id = match key {
+        Key::Custom(id) => id,
+        _ => return None,
+    };
+    custom_keys::get(id)
+        .sources
+        .iter()
+        .find_map(|source| match source {
+            custom_keys::Source::Template { template } => {
+                custom_keys::fill_template(environment, template)
+            }
+            _ => None,
+        })
+}
+
+/// How the value of a key is derived:
+/// from the values of which other keys,
+/// and by which function.
+struct Derivation {
+    inputs: &'static [Key],
+    derive: fn(&mut Environment, Key) -> RetrieveRes,
+}
+
+/// The derivation of each key, if it is derived at all.
+/// Keeping the inputs and the function in one table
+/// makes sure they do not drift apart.
+#[remain::check]
+fn derivation(key: Key) -> Option<Derivation> {
+    #[remain::sorted]
+    match key {
+        Key::AuthorCount
+        | Key::BuildArch
+        | Key::BuildBranch
+        | Key::BuildNumber
+        | Key::BuildOsFamily
+        | Key::BuildOs
+        | Key::BuildTag
+        | Key::Ci
+        | Key::CommitCount
+        | Key::CommitSha
+        | Key::CommitsSinceTag
+        | Key::Dirty
+        | Key::FirstCommitDate
+        | Key::License
+        | Key::Licenses
+        | Key::PreviousTag
+        | Key::Version
+        | Key::VersionBump
+        | Key::VersionDate
+        | Key::VersionNext => None,
+        Key::BuildDate => Some(Derivation {
+            inputs: &[Key::VersionDate],
+            derive: |environment, key| {
+                // In reproducible mode, the build date must not depend
+                // on when the build happens, so we use the commit date.
+                Ok(if environment.settings.reproducible {
+                    overwrite_guard!(
+                        environment,
+                        key,
+                        environment
+                            .output
+                            .get(Key::VersionDate)
+                            .map(|(confidence, value)| (*confidence, value.to_string()))
+                    )
+                } else {
+                    None
+                })
+            },
+        }),
+        Key::BuildHostingUrl => Some(Derivation {
+            inputs: &[Key::RepoWebUrl, Key::RepoSlug],
+            derive: |environment, key| {
+                Ok(overwrite_guard!(
+                    environment,
+                    key,
+                    from_slug_web_url(
+                        environment,
+                        value_conversions::web_url_to_build_hosting_url
+                    )?
+                ))
+            },
+        }),
+        Key::CommitShaShort => Some(Derivation {
+            inputs: &[Key::CommitSha],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    CommitSha,
+                    key,
+                    commit_sha_to_short_sha
+                ))
+            },
+        }),
+        Key::ContainerImageName => Some(Derivation {
+            inputs: &[Key::RepoWebUrl, Key::RepoSlug],
+            derive: |environment, key| {
+                Ok(overwrite_guard!(
+                    environment,
+                    key,
+                    from_slug_web_url(
+                        environment,
+                        value_conversions::web_url_to_container_image_name
+                    )?
+                ))
+            },
+        }),
+        Key::ContainerImageTag => Some(Derivation {
+            inputs: &[Key::Version, Key::BuildBranch],
+            derive: |environment, key| {
+                let from_version =
+                    conv_val_with_env!(environment, Version, key, to_container_image_tag);
+                Ok(if from_version.is_some() {
+                    from_version
+                } else {
+                    conv_val_with_env!(environment, BuildBranch, key, to_container_image_tag)
+                })
+            },
+        }),
+        Key::Custom(id) => Some(Derivation {
+            inputs: custom_keys::get(id).template_inputs,
+            derive: |environment, key| {
+                Ok(overwrite_guard!(
+                    environment,
+                    key,
+                    from_template(environment, key)
+                ))
+            },
+        }),
+        Key::IsRelease
+        | Key::VersionMajor
+        | Key::VersionMinor
+        | Key::VersionPatch
+        | Key::VersionPrerelease => Some(Derivation {
+            inputs: &[Key::Version],
+            derive: |environment, key| {
+                Ok(overwrite_guard!(
+                    environment,
+                    key,
+                    version_component(environment, key)
+                ))
+            },
+        }),
+        Key::Name => Some(Derivation {
+            inputs: &[Key::NameMachineReadable],
+            derive: |environment, key| {
+                Ok(overwrite_guard!(
+                    environment,
+                    key,
+                    environment
+                        .output
+                        .get(Key::NameMachineReadable)
+                        .map(|(confidence, value)| (*confidence, value.to_string()))
+                ))
+            },
+        }),
+        Key::NameMachineReadable => Some(Derivation {
+            inputs: &[Key::Name, Key::RepoWebUrl],
+            derive: |environment, key| {
+                let from_name =
+                    conv_val_with_env!(environment, Name, key, name_to_machine_readable_name);
+                Ok(if from_name.is_some() {
+                    from_name
+                } else {
+                    conv_val_with_env!(
+                        environment,
+                        RepoWebUrl,
+                        key,
+                        web_url_to_machine_readable_name
+                    )
+                })
+            },
+        }),
+        Key::RepoCloneUrl => Some(Derivation {
+            inputs: &[Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoWebUrl,
+                    key,
+                    web_url_to_clone_url,
+                    Protocol::Https
+                ))
+            },
+        }),
+        Key::RepoCloneUrlSsh => Some(Derivation {
+            inputs: &[Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoWebUrl,
+                    key,
+                    web_url_to_clone_url,
+                    Protocol::Ssh
+                ))
+            },
+        }),
+        Key::RepoCommitPrefixUrl => Some(Derivation {
+            inputs: &[Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoWebUrl,
+                    key,
+                    web_url_to_commit_prefix_url
+                ))
+            },
+        }),
+        Key::RepoCommitUrl => Some(Derivation {
+            inputs: &[Key::RepoCommitPrefixUrl, Key::CommitSha, Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(overwrite_guard!(environment, key, commit_url(environment)))
+            },
+        }),
+        Key::RepoCompareUrl => Some(Derivation {
+            inputs: &[
+                Key::PreviousTag,
+                Key::BuildTag,
+                Key::CommitSha,
+                Key::RepoWebUrl,
+            ],
+            derive: |environment, key| {
+                Ok(overwrite_guard!(
+                    environment,
+                    key,
+                    compare_url(environment)?
+                ))
+            },
+        }),
+        Key::RepoIssuesUrl => Some(Derivation {
+            inputs: &[Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoWebUrl,
+                    key,
+                    web_url_to_issues_url
+                ))
+            },
+        }),
+        Key::RepoNamespace => Some(Derivation {
+            inputs: &[Key::RepoSlug],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoSlug,
+                    key,
+                    slug_to_namespace
+                ))
+            },
+        }),
+        Key::RepoNewMergeRequestUrl => Some(Derivation {
+            inputs: &[Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoWebUrl,
+                    key,
+                    web_url_to_new_merge_request_url
+                ))
+            },
+        }),
+        Key::RepoOwner => Some(Derivation {
+            inputs: &[Key::RepoSlug],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoSlug,
+                    key,
+                    slug_to_owner
+                ))
+            },
+        }),
+        Key::RepoPipelinesUrl => Some(Derivation {
+            inputs: &[Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoWebUrl,
+                    key,
+                    web_url_to_pipelines_url
+                ))
+            },
+        }),
+        Key::RepoRawUrlBase => Some(Derivation {
+            inputs: &[
+                Key::RepoRawVersionedPrefixUrl,
+                Key::BuildTag,
+                Key::CommitSha,
+                Key::RepoWebUrl,
+            ],
+            derive: |environment, key| {
+                Ok(overwrite_guard!(
+                    environment,
+                    key,
+                    pinned_url(environment, Key::RepoRawVersionedPrefixUrl)
+                ))
+            },
+        }),
+        Key::RepoRawVersionedPrefixUrl => Some(Derivation {
+            inputs: &[Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoWebUrl,
+                    key,
+                    web_url_to_raw_prefix_url
+                ))
+            },
+        }),
+        Key::RepoReleasesUrl => Some(Derivation {
+            inputs: &[Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoWebUrl,
+                    key,
+                    web_url_to_releases_url
+                ))
+            },
+        }),
+        Key::RepoSlug => Some(Derivation {
+            inputs: &[Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoWebUrl,
+                    key,
+                    web_url_to_slug
+                ))
+            },
+        }),
+        Key::RepoTagUrl => Some(Derivation {
+            inputs: &[Key::BuildTag, Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(overwrite_guard!(environment, key, tag_url(environment)?))
+            },
+        }),
+        Key::RepoTreeUrl => Some(Derivation {
+            inputs: &[
+                Key::RepoVersionedDirPrefixUrl,
+                Key::BuildTag,
+                Key::CommitSha,
+                Key::RepoWebUrl,
+            ],
+            derive: |environment, key| {
+                Ok(overwrite_guard!(
+                    environment,
+                    key,
+                    pinned_url(environment, Key::RepoVersionedDirPrefixUrl)
+                ))
+            },
+        }),
+        Key::RepoVersionedDirPrefixUrl => Some(Derivation {
+            inputs: &[Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoWebUrl,
+                    key,
+                    web_url_to_versioned_dir_prefix_url
+                ))
+            },
+        }),
+        Key::RepoVersionedFilePrefixUrl => Some(Derivation {
+            inputs: &[Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoWebUrl,
+                    key,
+                    web_url_to_versioned_file_prefix_url
+                ))
+            },
+        }),
+        Key::RepoVersionedFileUrlBase => Some(Derivation {
+            inputs: &[
+                Key::RepoVersionedFilePrefixUrl,
+                Key::BuildTag,
+                Key::CommitSha,
+                Key::RepoWebUrl,
+            ],
+            derive: |environment, key| {
+                Ok(overwrite_guard!(
+                    environment,
+                    key,
+                    pinned_url(environment, Key::RepoVersionedFilePrefixUrl)
+                ))
+            },
+        }),
+        Key::RepoWebUrl => Some(Derivation {
+            inputs: &[Key::RepoCloneUrl, Key::RepoCloneUrlSsh],
+            derive: |environment, key| {
+                let from_http_clone_url =
+                    conv_val_with_env!(environment, RepoCloneUrl, key, clone_url_to_web_url);
+                Ok(if from_http_clone_url.is_some() {
+                    from_http_clone_url
+                } else {
+                    conv_val_with_env!(environment, RepoCloneUrlSsh, key, clone_url_to_web_url)
+                })
+            },
+        }),
+        Key::RepoWikiUrl => Some(Derivation {
+            inputs: &[Key::RepoWebUrl],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    RepoWebUrl,
+                    key,
+                    web_url_to_wiki_url
+                ))
+            },
+        }),
+        Key::SourceDateEpoch => Some(Derivation {
+            inputs: &[Key::BuildDate],
+            derive: |environment, key| {
+                Ok(overwrite_guard!(
+                    environment,
+                    key,
+                    match environment.output.get(Key::BuildDate) {
+                        // SOURCE_DATE_EPOCH can not represent dates before the epoch
+                        Some((confidence, Value::Date(date))) => u64::try_from(date.timestamp())
+                            .ok()
+                            .map(|seconds| (*confidence, seconds.to_string())),
+                        _ => None,
+                    }
+                ))
+            },
+        }),
+        Key::VersionDebian => Some(Derivation {
+            inputs: &[Key::Version],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    Version,
+                    key,
+                    version_to_debian
+                ))
+            },
+        }),
+        Key::VersionPep440 => Some(Derivation {
+            inputs: &[Key::Version],
+            derive: |environment, key| {
+                Ok(conv_val_with_env!(
+                    environment,
+                    Version,
+                    key,
+                    version_to_pep440
+                ))
+            },
+        }),
+    }
+}
+
 /// Does not source any new values,
 /// but derives them from other values, already sourced before.
 /// For example, it might derieve the [`Key::RepoWebUrl`]
@@ -63,94 +681,139 @@ impl super::VarSource for VarSource {
         &super::NO_PROPS
     }
 
-    #[remain::check]
+    fn derivation_inputs(&self, key: Key) -> &'static [Key] {
+        derivation(key).map_or(&[], |derivation| derivation.inputs)
+    }
+
+    fn supplies(&self, _environment: &Environment, key: Key) -> Option<Vec<String>> {
+        let derivation_inputs = self.derivation_inputs(key);
+        if derivation_inputs.is_empty() {
+            None
+        } else {
+            Some(
+                derivation_inputs
+                    .iter()
+                    .map(|input| format!("{:?}", input))
+                    .collect(),
+            )
+        }
+    }
+
     fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes {
-        Ok(
-            #[remain::sorted]
-            match key {
-                Key::BuildArch
-                | Key::BuildBranch
-                | Key::BuildDate
-                | Key::BuildNumber
-                | Key::BuildOsFamily
-                | Key::BuildOs
-                | Key::BuildTag
-                | Key::Ci
-                | Key::License
-                | Key::Licenses
-                | Key::Version
-                | Key::VersionDate => None,
-                Key::BuildHostingUrl => {
-                    conv_val_with_env!(environment, RepoWebUrl, key, web_url_to_build_hosting_url)
-                }
-                Key::Name => overwrite_guard!(
-                    environment,
-                    key,
-                    environment.output.get(Key::NameMachineReadable).cloned()
-                ),
-                Key::NameMachineReadable => {
-                    let from_name =
-                        conv_val_with_env!(environment, Name, key, name_to_machine_readable_name);
-                    if from_name.is_some() {
-                        from_name
-                    } else {
-                        conv_val_with_env!(
-                            environment,
-                            RepoWebUrl,
-                            key,
-                            web_url_to_machine_readable_name
-                        )
-                    }
-                }
-                Key::RepoCloneUrl => conv_val_with_env!(
-                    environment,
-                    RepoWebUrl,
-                    key,
-                    web_url_to_clone_url,
-                    Protocol::Https
-                ),
-                Key::RepoCloneUrlSsh => {
-                    conv_val_with_env!(
-                        environment,
-                        RepoWebUrl,
-                        key,
-                        web_url_to_clone_url,
-                        Protocol::Ssh
-                    )
-                }
-                Key::RepoCommitPrefixUrl => {
-                    conv_val_with_env!(environment, RepoWebUrl, key, web_url_to_commit_prefix_url)
-                }
-                Key::RepoIssuesUrl => {
-                    conv_val_with_env!(environment, RepoWebUrl, key, web_url_to_issues_url)
-                }
-                Key::RepoRawVersionedPrefixUrl => {
-                    conv_val_with_env!(environment, RepoWebUrl, key, web_url_to_raw_prefix_url)
-                }
-                Key::RepoVersionedDirPrefixUrl => {
-                    conv_val_with_env!(
-                        environment,
-                        RepoWebUrl,
-                        key,
-                        web_url_to_versioned_dir_prefix_url
-                    )
-                }
-                Key::RepoVersionedFilePrefixUrl => conv_val_with_env!(
-                    environment,
-                    RepoWebUrl,
-                    key,
-                    web_url_to_versioned_file_prefix_url
-                ),
-                Key::RepoWebUrl => {
-                    let from_http_clone_url =
-                        conv_val_with_env!(environment, RepoCloneUrl, key, clone_url_to_web_url);
-                    if from_http_clone_url.is_some() {
-                        from_http_clone_url
-                    } else {
-                        conv_val_with_env!(environment, RepoCloneUrlSsh, key, clone_url_to_web_url)
-                    }
-                }
-            },
-        )
+        derivation(key).map_or(Ok(None), |derivation| (derivation.derive)(environment, key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::var::C_HIGH;
+
+    fn component(version: &str, key: Key) -> Option<String> {
+        let mut environment = Environment::stub();
+        environment
+            .output
+            .add(Key::Version, 0, C_HIGH, Value::Text(version.to_owned()));
+        version_component(&environment, key).map(|(_confidence, component)| component)
+    }
+
+    #[test]
+    fn test_version_component() {
+        assert_eq!(component("1.2.3", Key::IsRelease).as_deref(), Some("true"));
+        assert_eq!(component("1.2.3", Key::VersionPrerelease), None);
+        assert_eq!(
+            component("1.2.3-4-gabcdef0", Key::IsRelease).as_deref(),
+            Some("false")
+        );
+        assert_eq!(component("1.2.3-4-gabcdef0", Key::VersionPrerelease), None);
+        assert_eq!(
+            component("1.2.3-4-gabcdef0", Key::VersionPatch).as_deref(),
+            Some("3")
+        );
+        assert_eq!(
+            component("1.2.3-dirty", Key::IsRelease).as_deref(),
+            Some("false")
+        );
+        assert_eq!(component("1.2.3-dirty", Key::VersionPrerelease), None);
+        assert_eq!(
+            component("1.2.3-rc.1-4-gabcdef0-dirty", Key::VersionPrerelease).as_deref(),
+            Some("rc.1")
+        );
+        assert_eq!(
+            component("1.2.3-rc.1", Key::IsRelease).as_deref(),
+            Some("false")
+        );
+        assert_eq!(component("gabcdef0", Key::VersionMajor), None);
+    }
+
+    #[test]
+    fn test_pinned_url() {
+        let mut environment = Environment::stub();
+        environment.output.add(
+            Key::CommitSha,
+            0,
+            C_HIGH,
+            Value::Text("1a2b3c4d".to_owned()),
+        );
+        environment.output.add(
+            Key::RepoRawVersionedPrefixUrl,
+            0,
+            C_HIGH,
+            Value::Text("https://raw.githubusercontent.com/hoijui/nim-ci".to_owned()),
+        );
+        assert_eq!(
+            pinned_url(&environment, Key::RepoRawVersionedPrefixUrl),
+            Some((
+                C_HIGH,
+                "https://raw.githubusercontent.com/hoijui/nim-ci/1a2b3c4d".to_owned()
+            ))
+        );
+
+        environment.output.add(
+            Key::RepoWebUrl,
+            0,
+            C_HIGH,
+            Value::Text("https://sourceforge.net/p/sevenzip/code".to_owned()),
+        );
+        environment.output.add(
+            Key::RepoRawVersionedPrefixUrl,
+            0,
+            C_HIGH,
+            Value::Text("https://sourceforge.net/p/sevenzip/code/ci".to_owned()),
+        );
+        assert_eq!(
+            pinned_url(&environment, Key::RepoRawVersionedPrefixUrl),
+            None
+        );
+    }
+
+    #[test]
+    fn test_from_slug_web_url() {
+        let as_is = |_environment: &Environment, url: &str| Ok(Some(url.to_owned()));
+        let mut environment = Environment::stub();
+        assert_eq!(from_slug_web_url(&environment, as_is).unwrap(), None);
+
+        environment.output.add(
+            Key::RepoWebUrl,
+            0,
+            C_HIGH,
+            Value::Text("https://github.com/hoijui/projvar/".to_owned()),
+        );
+        assert_eq!(
+            from_slug_web_url(&environment, as_is).unwrap(),
+            Some((C_HIGH, "https://github.com/hoijui/projvar".to_owned()))
+        );
+
+        // Without a valid slug, the web URL is used as is
+        environment.output.add(
+            Key::RepoWebUrl,
+            0,
+            C_HIGH,
+            Value::Text("https://github.com/projvar".to_owned()),
+        );
+        assert_eq!(
+            from_slug_web_url(&environment, as_is).unwrap(),
+            Some((C_HIGH, "https://github.com/projvar".to_owned()))
+        );
     }
 }
@@ -35,6 +35,10 @@ impl super::VarSource for VarSource {
         &super::NO_PROPS
     }
 
+    fn supplies(&self, environment: &Environment, key: Key) -> Option<Vec<String>> {
+        Some(vec![var::get(key).key(environment).into_owned()])
+    }
+
     fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes {
         Ok(var(environment, &var::get(key).key(environment), C_HIGH))
     }
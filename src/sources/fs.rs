@@ -2,7 +2,7 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use chrono::Local;
+use chrono::{DateTime, Local, SecondsFormat, TimeZone, Utc};
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -13,7 +13,7 @@ use crate::var::{Confidence, Key, C_HIGH, C_LOW, C_MIDDLE};
 use std::path::{Path, PathBuf};
 use std::{env, fs};
 
-use super::{Hierarchy, RetrieveRes};
+use super::{inputs, Error, Hierarchy, RetrieveRes};
 
 /// Sources values from the file-system and OS supplied environment variables.
 pub struct VarSource;
@@ -127,16 +127,55 @@ fn name(environment: &mut Environment) -> RetrieveRes {
     })
 }
 
-fn build_date(environment: &mut Environment) -> String {
-    let now = Local::now();
-    now.format(&environment.settings.date_format).to_string()
+/// The name of the input variable that fixes the build date,
+/// see <https://reproducible-builds.org/specs/source-date-epoch/>.
+const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// Parses the value of `SOURCE_DATE_EPOCH`,
+/// which has to be a non-negative integer.
+fn parse_source_date_epoch(epoch: &str) -> Result<DateTime<Utc>, Error> {
+    let bad_value = |msg: String| Error::BadLowLevelValue {
+        msg,
+        low_level_value: epoch.to_owned(),
+    };
+    let seconds = epoch.trim().parse::<u64>().map_err(|err| {
+        bad_value(format!(
+            "{} has to be a non-negative integer (seconds since the Unix epoch): {}",
+            SOURCE_DATE_EPOCH, err
+        ))
+    })?;
+    i64::try_from(seconds)
+        .ok()
+        .and_then(|seconds| Utc.timestamp_opt(seconds, 0).single())
+        .ok_or_else(|| bad_value(format!("{} is out of range", SOURCE_DATE_EPOCH)))
+}
+
+/// Uses `SOURCE_DATE_EPOCH` if set, or else the current time.
+/// In reproducible mode, we never use the current time;
+/// the build date is then derived from the version date instead
+/// (see [`super::deriver::VarSource`]).
+fn build_date(environment: &mut Environment) -> RetrieveRes {
+    if let Some(epoch) = environment.vars.get(SOURCE_DATE_EPOCH) {
+        let date = parse_source_date_epoch(epoch)?;
+        return Ok(Some((
+            C_HIGH,
+            date.to_rfc3339_opts(SecondsFormat::Secs, true),
+        )));
+    }
+    if environment.settings.reproducible {
+        return Ok(None);
+    }
+    Ok(Some((
+        C_HIGH,
+        Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+    )))
 }
 
 fn build_os(_environment: &mut Environment) -> (Confidence, String) {
     // See here for possible values:
     // <https://doc.rust-lang.org/std/env/consts/constant.OS.html>
     // Most common values: "linux", "macos", "windows"
-    (C_LOW, env::consts::OS.to_owned()) // TODO Maybe move to a new source "env.rs"? AND Map to our own values!
+    (C_LOW, env::consts::OS.to_owned()) // TODO Maybe move to a new source "env.rs"?
 }
 
 fn build_os_family(_environment: &mut Environment) -> (Confidence, String) {
@@ -149,7 +188,7 @@ fn build_os_family(_environment: &mut Environment) -> (Confidence, String) {
 fn build_arch(_environment: &mut Environment) -> (Confidence, String) {
     // See here for possible values:
     // <https://doc.rust-lang.org/std/env/consts/constant.ARCH.html>
-    // Most common values: "x86", "x86_64"
+    // Most common values: "x86", "x86_64", "aarch64" (normalized to "arm64")
     (C_LOW, env::consts::ARCH.to_owned()) // TODO Maybe move to a new source "env.rs"?
 }
 
@@ -173,6 +212,71 @@ impl super::VarSource for VarSource {
         &super::NO_PROPS
     }
 
+    #[remain::check]
+    fn supplies(&self, _environment: &Environment, key: Key) -> Option<Vec<String>> {
+        #[remain::sorted]
+        match key {
+            Key::BuildArch | Key::BuildOs | Key::BuildOsFamily => {
+                inputs(&["rust: std::env::consts"])
+            }
+            Key::BuildBranch
+            | Key::AuthorCount
+            | Key::BuildHostingUrl
+            | Key::BuildNumber
+            | Key::BuildTag
+            | Key::Ci
+            | Key::CommitCount
+            | Key::CommitSha
+            | Key::CommitShaShort
+            | Key::CommitsSinceTag
+            | Key::ContainerImageName
+            | Key::ContainerImageTag
+            | Key::Custom(_)
+            | Key::Dirty
+            | Key::FirstCommitDate
+            | Key::IsRelease
+            | Key::NameMachineReadable
+            | Key::PreviousTag
+            | Key::RepoCloneUrl
+            | Key::RepoCloneUrlSsh
+            | Key::RepoCommitPrefixUrl
+            | Key::RepoCommitUrl
+            | Key::RepoCompareUrl
+            | Key::RepoIssuesUrl
+            | Key::RepoNamespace
+            | Key::RepoNewMergeRequestUrl
+            | Key::RepoOwner
+            | Key::RepoPipelinesUrl
+            | Key::RepoRawUrlBase
+            | Key::RepoRawVersionedPrefixUrl
+            | Key::RepoReleasesUrl
+            | Key::RepoSlug
+            | Key::RepoTagUrl
+            | Key::RepoTreeUrl
+            | Key::RepoVersionedDirPrefixUrl
+            | Key::RepoVersionedFilePrefixUrl
+            | Key::RepoVersionedFileUrlBase
+            | Key::RepoWebUrl
+            | Key::RepoWikiUrl
+            | Key::SourceDateEpoch
+            | Key::VersionBump
+            | Key::VersionDate
+            | Key::VersionDebian
+            | Key::VersionMajor
+            | Key::VersionMinor
+            | Key::VersionNext
+            | Key::VersionPatch
+            | Key::VersionPep440
+            | Key::VersionPrerelease => None,
+            Key::BuildDate => inputs(&[SOURCE_DATE_EPOCH, "system clock"]),
+            Key::License | Key::Licenses => {
+                inputs(&["fs: LICENSES/*.txt", "fs: LICENSE*, COPYING*"])
+            }
+            Key::Name => inputs(&["fs: project root dir name"]),
+            Key::Version => inputs(&["fs: VERSION"]),
+        }
+    }
+
     #[remain::check]
     fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes {
         Ok(
@@ -180,28 +284,97 @@ impl super::VarSource for VarSource {
             match key {
                 Key::BuildArch => Some(build_arch(environment)),
                 Key::BuildBranch
+                | Key::AuthorCount
                 | Key::BuildHostingUrl
                 | Key::BuildNumber
                 | Key::BuildTag
                 | Key::Ci
+                | Key::CommitCount
+                | Key::CommitSha
+                | Key::CommitShaShort
+                | Key::CommitsSinceTag
+                | Key::ContainerImageName
+                | Key::ContainerImageTag
+                | Key::Custom(_)
+                | Key::Dirty
+                | Key::FirstCommitDate
+                | Key::IsRelease
+                | Key::PreviousTag
                 | Key::RepoCloneUrl
                 | Key::RepoCloneUrlSsh
                 | Key::RepoCommitPrefixUrl
+                | Key::RepoCommitUrl
+                | Key::RepoCompareUrl
                 | Key::RepoIssuesUrl
+                | Key::RepoNamespace
+                | Key::RepoNewMergeRequestUrl
+                | Key::RepoOwner
+                | Key::RepoPipelinesUrl
+                | Key::RepoRawUrlBase
                 | Key::RepoRawVersionedPrefixUrl
+                | Key::RepoReleasesUrl
+                | Key::RepoSlug
+                | Key::RepoTagUrl
+                | Key::RepoTreeUrl
                 | Key::RepoVersionedDirPrefixUrl
                 | Key::RepoVersionedFilePrefixUrl
+                | Key::RepoVersionedFileUrlBase
                 | Key::RepoWebUrl
+                | Key::RepoWikiUrl
+                | Key::SourceDateEpoch
+                | Key::VersionBump
                 | Key::VersionDate
-                | Key::NameMachineReadable => None,
-                Key::BuildDate => Some((C_HIGH, build_date(environment))),
+                | Key::NameMachineReadable
+                | Key::VersionDebian
+                | Key::VersionMajor
+                | Key::VersionMinor
+                | Key::VersionNext
+                | Key::VersionPatch
+                | Key::VersionPep440
+                | Key::VersionPrerelease => None,
+                Key::BuildDate => build_date(environment)?,
                 Key::BuildOs => Some(build_os(environment)),
                 Key::BuildOsFamily => Some(build_os_family(environment)),
                 Key::License => license(environment)?.map(|val| (C_HIGH, val)),
-                Key::Licenses => licenses(environment, false)?.map(|lv| (C_HIGH, lv.join(", "))), // TODO Later on, rather create an SPDX expressions, maybe by using OR instead of ',' to join ... but can we really?
+                Key::Licenses => licenses(environment, false)?.map(|lv| (C_HIGH, lv.join(", "))), // This gets parsed back into a list, see `crate::value`
                 Key::Name => name(environment)?,
                 Key::Version => version(environment)?,
             },
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_source_date_epoch() {
+        assert_eq!(
+            parse_source_date_epoch("1640995199").unwrap().to_rfc3339(),
+            "2021-12-31T23:59:59+00:00"
+        );
+        assert_eq!(parse_source_date_epoch(" 0\n").unwrap().timestamp(), 0);
+        assert!(parse_source_date_epoch("-1").is_err());
+        assert!(parse_source_date_epoch("1.5").is_err());
+        assert!(parse_source_date_epoch("").is_err());
+        assert!(parse_source_date_epoch(&u64::MAX.to_string()).is_err());
+        assert!(parse_source_date_epoch(&i64::MAX.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_build_date() {
+        let mut environment = Environment::stub();
+        environment
+            .vars
+            .insert(SOURCE_DATE_EPOCH.to_owned(), "1640995199".to_owned());
+        assert_eq!(
+            build_date(&mut environment).unwrap(),
+            Some((C_HIGH, "2021-12-31T23:59:59Z".to_owned()))
+        );
+        environment.vars.clear();
+        assert!(build_date(&mut environment).unwrap().is_some());
+        environment.settings.reproducible = true;
+        assert!(build_date(&mut environment).unwrap().is_none());
+    }
+}
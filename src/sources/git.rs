@@ -2,10 +2,12 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::tools::git;
 use crate::var::{Key, C_HIGH};
-use crate::{environment::Environment, value_conversions};
+use crate::version::{self, Bump};
+use crate::{environment::Environment, value_conversions, BoxResult};
 
-use super::{Hierarchy, RetrieveRes};
+use super::{inputs, Hierarchy, RetrieveRes};
 
 /// Sources values by querrying the `git` CLI tool.
 /// In reality, we use a git library, but the effect is the same.
@@ -25,6 +27,43 @@ fn version(environment: &mut Environment) -> RetrieveRes {
     })
 }
 
+/// Computes the bump and the next version
+/// from the Conventional Commits since the last release tag,
+/// walking the history only once for both.
+fn next_version(environment: &mut Environment) -> BoxResult<Option<(Bump, semver::Version)>> {
+    if let Some(next_version) = &environment.next_version {
+        return Ok(next_version.clone());
+    }
+    let next_version = walk_next_version(environment)?;
+    environment.next_version = Some(next_version.clone());
+    Ok(next_version)
+}
+
+fn walk_next_version(environment: &mut Environment) -> BoxResult<Option<(Bump, semver::Version)>> {
+    let pattern = environment.settings.release_tag_pattern.clone();
+    let is_release_tag = |tag: &str| version::release_version(pattern.as_ref(), tag).is_some();
+    Ok(match environment.repo() {
+        Some(repo) => {
+            let (tags, commits) = repo.commits_since_release(&is_release_tag)?;
+            let last_release = tags
+                .iter()
+                .filter_map(|tag| version::release_version(pattern.as_ref(), tag))
+                .max();
+            let messages: Vec<String> = commits.into_iter().map(|commit| commit.message).collect();
+            Some(version::next(last_release.as_ref(), &messages))
+        }
+        None => None,
+    })
+}
+
+fn version_bump(environment: &mut Environment) -> RetrieveRes {
+    Ok(next_version(environment)?.map(|(bump, _next)| (C_HIGH, <&str>::from(bump).to_owned())))
+}
+
+fn version_next(environment: &mut Environment) -> RetrieveRes {
+    Ok(next_version(environment)?.map(|(_bump, next)| (C_HIGH, next.to_string())))
+}
+
 fn branch(environment: &mut Environment) -> RetrieveRes {
     Ok(match environment.repo() {
         Some(repo) => {
@@ -45,6 +84,93 @@ fn tag(environment: &mut Environment) -> RetrieveRes {
     })
 }
 
+fn sha(environment: &mut Environment) -> RetrieveRes {
+    Ok(match environment.repo() {
+        Some(repo) => repo.sha()?.map(|val| (C_HIGH, val)),
+        None => None,
+    })
+}
+
+fn sha_short(environment: &mut Environment) -> RetrieveRes {
+    Ok(match environment.repo() {
+        Some(repo) => repo.sha_short()?.map(|val| (C_HIGH, val)),
+        None => None,
+    })
+}
+
+fn commits_since_tag(environment: &mut Environment) -> RetrieveRes {
+    Ok(match environment.repo() {
+        Some(repo) => repo
+            .commits_since_tag()
+            .unwrap_or_else(|err| {
+                log::debug!(
+                    "Failed to git describe (\"{}\"), so no commits since tag",
+                    err
+                );
+                None
+            })
+            .map(|val| (C_HIGH, val.to_string())),
+        None => None,
+    })
+}
+
+fn dirty(environment: &mut Environment) -> RetrieveRes {
+    Ok(match environment.repo() {
+        Some(repo) => Some((C_HIGH, repo.is_dirty()?.to_string())),
+        None => None,
+    })
+}
+
+fn previous_tag(environment: &mut Environment) -> RetrieveRes {
+    let pattern = environment.settings.release_tag_pattern.clone();
+    let is_release_tag = |tag: &str| version::release_version(pattern.as_ref(), tag).is_some();
+    Ok(match environment.repo() {
+        Some(repo) => repo
+            .previous_release_tags(&is_release_tag)?
+            .into_iter()
+            .max_by_key(|tag| version::release_version(pattern.as_ref(), tag))
+            .map(|val| (C_HIGH, val)),
+        None => None,
+    })
+}
+
+/// Returns the repo, if its whole history is available.
+/// In shallow clones (as commonly used in CI),
+/// values evaluated from the history would be wrong,
+/// so we rather not evaluate them at all.
+fn full_history_repo(environment: &mut Environment, key: Key) -> Option<&git::Repo> {
+    let repo = environment.repo()?;
+    if repo.is_shallow() {
+        log::warn!(
+            "Not evaluating {:?} from git, as the repo is a shallow clone",
+            key
+        );
+        return None;
+    }
+    Some(repo)
+}
+
+fn commit_count(environment: &mut Environment) -> RetrieveRes {
+    Ok(match full_history_repo(environment, Key::CommitCount) {
+        Some(repo) => Some((C_HIGH, repo.commit_count()?.to_string())),
+        None => None,
+    })
+}
+
+fn author_count(environment: &mut Environment) -> RetrieveRes {
+    Ok(match full_history_repo(environment, Key::AuthorCount) {
+        Some(repo) => Some((C_HIGH, repo.author_count()?.to_string())),
+        None => None,
+    })
+}
+
+fn first_commit_date(environment: &mut Environment) -> RetrieveRes {
+    Ok(match full_history_repo(environment, Key::FirstCommitDate) {
+        Some(repo) => Some((C_HIGH, repo.first_commit_date()?.to_rfc3339())),
+        None => None,
+    })
+}
+
 fn clone_url(environment: &mut Environment) -> RetrieveRes {
     Ok(match environment.repo() {
         Some(repo) => {
@@ -59,9 +185,8 @@ fn clone_url(environment: &mut Environment) -> RetrieveRes {
 }
 
 fn version_date(environment: &mut Environment) -> RetrieveRes {
-    let date_format = environment.settings.date_format.clone();
     Ok(match &environment.repo() {
-        Some(repo) => Some((C_HIGH, repo.commit_date(&date_format)?)),
+        Some(repo) => Some((C_HIGH, repo.commit_date()?.to_rfc3339())),
         None => None,
     })
 }
@@ -86,47 +211,178 @@ impl super::VarSource for VarSource {
         &super::NO_PROPS
     }
 
+    #[remain::check]
+    fn supplies(&self, _environment: &Environment, key: Key) -> Option<Vec<String>> {
+        #[remain::sorted]
+        match key {
+            Key::AuthorCount => inputs(&["git: commit authors", "git: .mailmap"]),
+            Key::BuildArch
+            | Key::BuildDate
+            | Key::BuildHostingUrl
+            | Key::BuildNumber
+            | Key::BuildOs
+            | Key::BuildOsFamily
+            | Key::Ci
+            | Key::ContainerImageName
+            | Key::ContainerImageTag
+            | Key::Custom(_)
+            | Key::IsRelease
+            | Key::License
+            | Key::Licenses
+            | Key::Name
+            | Key::NameMachineReadable
+            | Key::RepoCommitPrefixUrl
+            | Key::RepoCommitUrl
+            | Key::RepoCompareUrl
+            | Key::RepoIssuesUrl
+            | Key::RepoNamespace
+            | Key::RepoNewMergeRequestUrl
+            | Key::RepoOwner
+            | Key::RepoPipelinesUrl
+            | Key::RepoRawUrlBase
+            | Key::RepoRawVersionedPrefixUrl
+            | Key::RepoReleasesUrl
+            | Key::RepoSlug
+            | Key::RepoTagUrl
+            | Key::RepoTreeUrl
+            | Key::RepoVersionedDirPrefixUrl
+            | Key::RepoVersionedFilePrefixUrl
+            | Key::RepoVersionedFileUrlBase
+            | Key::RepoWebUrl
+            | Key::RepoWikiUrl
+            | Key::SourceDateEpoch
+            | Key::VersionDebian
+            | Key::VersionMajor
+            | Key::VersionMinor
+            | Key::VersionPatch
+            | Key::VersionPep440
+            | Key::VersionPrerelease => None,
+            Key::BuildBranch => inputs(&["git: current branch"]),
+            Key::BuildTag => inputs(&["git: tag on HEAD"]),
+            Key::CommitCount => inputs(&["git: commits"]),
+            Key::CommitSha | Key::CommitShaShort => inputs(&["git: HEAD commit SHA"]),
+            Key::CommitsSinceTag => inputs(&["git: describe"]),
+            Key::Dirty => inputs(&["git: status"]),
+            Key::FirstCommitDate => inputs(&["git: root commit date"]),
+            Key::PreviousTag => inputs(&["git: tags"]),
+            Key::RepoCloneUrl | Key::RepoCloneUrlSsh => inputs(&["git: remote URL"]),
+            Key::Version => inputs(&["git: describe"]),
+            Key::VersionBump | Key::VersionNext => inputs(&[
+                "git: tags",
+                "git: commit messages since the last release tag",
+            ]),
+            Key::VersionDate => inputs(&["git: HEAD commit date"]),
+        }
+    }
+
     #[remain::check]
     fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes {
         Ok(
             #[remain::sorted]
             match key {
+                Key::AuthorCount => author_count(environment)?,
                 Key::BuildArch
                 | Key::BuildDate
                 | Key::BuildNumber
                 | Key::BuildOs
                 | Key::BuildOsFamily
                 | Key::Ci
+                | Key::ContainerImageName
+                | Key::ContainerImageTag
+                | Key::Custom(_)
+                | Key::IsRelease
                 | Key::License
                 | Key::Licenses
                 | Key::BuildHostingUrl
                 | Key::Name
                 | Key::NameMachineReadable
                 | Key::RepoCommitPrefixUrl
+                | Key::RepoCommitUrl
+                | Key::RepoCompareUrl
                 | Key::RepoIssuesUrl
+                | Key::RepoNamespace
+                | Key::RepoNewMergeRequestUrl
+                | Key::RepoOwner
+                | Key::RepoPipelinesUrl
+                | Key::RepoRawUrlBase
                 | Key::RepoRawVersionedPrefixUrl
+                | Key::RepoReleasesUrl
+                | Key::RepoSlug
+                | Key::RepoTagUrl
+                | Key::RepoTreeUrl
                 | Key::RepoVersionedDirPrefixUrl
                 | Key::RepoVersionedFilePrefixUrl
-                | Key::RepoWebUrl => None,
+                | Key::RepoVersionedFileUrlBase
+                | Key::RepoWebUrl
+                | Key::RepoWikiUrl
+                | Key::SourceDateEpoch
+                | Key::VersionDebian
+                | Key::VersionMajor
+                | Key::VersionMinor
+                | Key::VersionPatch
+                | Key::VersionPep440
+                | Key::VersionPrerelease => None,
                 Key::BuildBranch => branch(environment)?,
                 Key::BuildTag => tag(environment)?,
-                Key::RepoCloneUrl => value_conversions::clone_url_conversion_option(
-                    clone_url(environment)?
-                        .map(|rated_value| rated_value.1)
-                        .as_ref(),
-                    value_conversions::Protocol::Https,
-                )?
-                .map(|val| (C_HIGH, val)),
-                Key::RepoCloneUrlSsh => value_conversions::clone_url_conversion_option(
-                    clone_url(environment)?
-                        .map(|rated_value| rated_value.1)
-                        .as_ref(),
-                    value_conversions::Protocol::Ssh,
-                )?
-                .map(|val| (C_HIGH, val)),
+                Key::CommitCount => commit_count(environment)?,
+                Key::CommitSha => sha(environment)?,
+                Key::CommitShaShort => sha_short(environment)?,
+                Key::CommitsSinceTag => commits_since_tag(environment)?,
+                Key::Dirty => dirty(environment)?,
+                Key::FirstCommitDate => first_commit_date(environment)?,
+                Key::PreviousTag => previous_tag(environment)?,
+                Key::RepoCloneUrl => {
+                    let clone_url = clone_url(environment)?.map(|rated_value| rated_value.1);
+                    value_conversions::clone_url_conversion_option(
+                        environment,
+                        clone_url.as_ref(),
+                        value_conversions::Protocol::Https,
+                    )?
+                    .map(|val| (C_HIGH, val))
+                }
+                Key::RepoCloneUrlSsh => {
+                    let clone_url = clone_url(environment)?.map(|rated_value| rated_value.1);
+                    value_conversions::clone_url_conversion_option(
+                        environment,
+                        clone_url.as_ref(),
+                        value_conversions::Protocol::Ssh,
+                    )?
+                    .map(|val| (C_HIGH, val))
+                }
                 Key::Version => version(environment)?,
+                Key::VersionBump => version_bump(environment)?,
                 Key::VersionDate => version_date(environment)?,
+                Key::VersionNext => version_next(environment)?,
             },
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::git::tests::{commit, test_repo, workdir};
+
+    #[test]
+    fn test_shallow_history() {
+        let repo = test_repo("shallow-history");
+        let oid = commit(&repo, "a.txt", "a\n", "First", 1_600_000_000);
+        let mut environment = Environment::stub();
+        environment.settings.repo_path = Some(workdir(&repo));
+        assert_eq!(
+            commit_count(&mut environment).unwrap(),
+            Some((C_HIGH, "1".to_owned()))
+        );
+
+        std::fs::write(
+            workdir(&repo).join(".git").join("shallow"),
+            format!("{}\n", oid),
+        )
+        .unwrap();
+        let mut environment = Environment::stub();
+        environment.settings.repo_path = Some(workdir(&repo));
+        assert_eq!(commit_count(&mut environment).unwrap(), None);
+        assert_eq!(author_count(&mut environment).unwrap(), None);
+        assert_eq!(first_commit_date(&mut environment).unwrap(), None);
+    }
+}
@@ -9,6 +9,7 @@ use crate::var::Key;
 use crate::var::C_HIGH;
 use crate::var::C_LOW;
 
+use super::inputs;
 use super::var;
 use super::Hierarchy;
 use super::RetrieveRes;
@@ -68,18 +69,105 @@ impl super::VarSource for VarSource {
         &super::NO_PROPS
     }
 
+    #[remain::check]
+    fn supplies(&self, _environment: &Environment, key: Key) -> Option<Vec<String>> {
+        #[remain::sorted]
+        match key {
+            Key::BuildArch => inputs(&["RUNNER_ARCH"]),
+            Key::BuildDate
+            | Key::AuthorCount
+            | Key::BuildHostingUrl
+            | Key::BuildNumber
+            | Key::BuildOsFamily
+            | Key::CommitCount
+            | Key::CommitShaShort
+            | Key::CommitsSinceTag
+            | Key::ContainerImageName
+            | Key::ContainerImageTag
+            | Key::Custom(_)
+            | Key::Dirty
+            | Key::FirstCommitDate
+            | Key::IsRelease
+            | Key::License
+            | Key::Licenses
+            | Key::NameMachineReadable
+            | Key::PreviousTag
+            | Key::RepoCloneUrl
+            | Key::RepoCloneUrlSsh
+            | Key::RepoCommitPrefixUrl
+            | Key::RepoCommitUrl
+            | Key::RepoCompareUrl
+            | Key::RepoIssuesUrl
+            | Key::RepoNamespace
+            | Key::RepoNewMergeRequestUrl
+            | Key::RepoPipelinesUrl
+            | Key::RepoRawUrlBase
+            | Key::RepoRawVersionedPrefixUrl
+            | Key::RepoReleasesUrl
+            | Key::RepoTagUrl
+            | Key::RepoTreeUrl
+            | Key::RepoVersionedDirPrefixUrl
+            | Key::RepoVersionedFilePrefixUrl
+            | Key::RepoVersionedFileUrlBase
+            | Key::RepoWikiUrl
+            | Key::SourceDateEpoch
+            | Key::VersionBump
+            | Key::VersionDate
+            | Key::VersionDebian
+            | Key::VersionMajor
+            | Key::VersionMinor
+            | Key::VersionNext
+            | Key::VersionPatch
+            | Key::VersionPep440
+            | Key::VersionPrerelease => None,
+            Key::BuildBranch | Key::BuildTag => inputs(&["GITHUB_REF"]),
+            Key::BuildOs => inputs(&["RUNNER_OS"]),
+            Key::Ci => inputs(&["CI"]),
+            Key::CommitSha => inputs(&["GITHUB_SHA"]),
+            Key::Name => inputs(&["GITHUB_REPOSITORY"]),
+            Key::RepoOwner => inputs(&["GITHUB_REPOSITORY_OWNER"]),
+            Key::RepoSlug => inputs(&["GITHUB_REPOSITORY"]),
+            Key::RepoWebUrl => inputs(&["GITHUB_SERVER_URL", "GITHUB_REPOSITORY"]),
+            Key::Version => inputs(&["GITHUB_SHA"]),
+        }
+    }
+
     #[remain::check]
     fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes {
         Ok(
             #[remain::sorted]
             match key {
-                Key::BuildArch
-                | Key::BuildDate
+                Key::BuildArch => var(environment, "RUNNER_ARCH", C_LOW),
+                Key::BuildDate
+                | Key::AuthorCount
                 | Key::BuildHostingUrl
                 | Key::BuildNumber
                 | Key::BuildOsFamily
+                | Key::CommitCount
+                | Key::CommitShaShort
+                | Key::CommitsSinceTag
+                | Key::ContainerImageName
+                | Key::ContainerImageTag
+                | Key::Custom(_)
+                | Key::Dirty
+                | Key::FirstCommitDate
+                | Key::IsRelease
                 | Key::License
                 | Key::Licenses
+                | Key::PreviousTag
+                | Key::RepoCommitUrl
+                | Key::RepoCompareUrl
+                | Key::RepoNamespace
+                | Key::RepoNewMergeRequestUrl
+                | Key::RepoPipelinesUrl
+                | Key::RepoRawUrlBase
+                | Key::RepoReleasesUrl
+                | Key::RepoTagUrl
+                | Key::RepoTreeUrl
+                | Key::RepoVersionedFileUrlBase
+                | Key::RepoWikiUrl
+                | Key::SourceDateEpoch
+                | Key::VersionBump
                 | Key::VersionDate
                 | Key::NameMachineReadable
                 | Key::RepoCommitPrefixUrl
@@ -88,19 +176,29 @@ impl super::VarSource for VarSource {
                 | Key::RepoIssuesUrl
                 | Key::RepoRawVersionedPrefixUrl
                 | Key::RepoVersionedDirPrefixUrl
-                | Key::RepoVersionedFilePrefixUrl => None,
+                | Key::RepoVersionedFilePrefixUrl
+                | Key::VersionDebian
+                | Key::VersionMajor
+                | Key::VersionMinor
+                | Key::VersionNext
+                | Key::VersionPatch
+                | Key::VersionPep440
+                | Key::VersionPrerelease => None,
                 Key::BuildBranch => build_branch(environment)?,
-                Key::BuildOs => var(environment, "RUNNER_OS", C_LOW), // TODO PRIO Not sure if this makes sense ... have to check in practise, and probably map values to our set of accepted values!
+                Key::BuildOs => var(environment, "RUNNER_OS", C_LOW),
                 Key::BuildTag => build_tag(environment)?,
                 Key::Ci => {
                     var(environment, "CI", C_HIGH).or_else(|| Some((C_LOW, "false".to_owned())))
                 }
+                Key::CommitSha => var(environment, "GITHUB_SHA", C_HIGH),
                 Key::Name => match var(environment, "GITHUB_REPOSITORY", C_HIGH) {
                     Some(rated_val) => {
                         slug_to_proj_name(Some(&rated_val.1))?.map(|val| (rated_val.0, val))
                     }
                     None => None,
                 }, // usually: GITHUB_REPOSITORY="user/project"
+                Key::RepoOwner => var(environment, "GITHUB_REPOSITORY_OWNER", C_HIGH),
+                Key::RepoSlug => var(environment, "GITHUB_REPOSITORY", C_HIGH),
                 Key::RepoWebUrl => repo_web_url(environment),
                 Key::Version => var(environment, "GITHUB_SHA", C_LOW),
             },
@@ -6,6 +6,7 @@ use crate::environment::Environment;
 use crate::var::Key;
 use crate::var::C_HIGH;
 
+use super::inputs;
 use super::var;
 use super::Hierarchy;
 use super::RetrieveRes;
@@ -30,32 +31,130 @@ impl super::VarSource for VarSource {
         &super::NO_PROPS
     }
 
+    #[remain::check]
+    fn supplies(&self, _environment: &Environment, key: Key) -> Option<Vec<String>> {
+        #[remain::sorted]
+        match key {
+            Key::BuildArch
+            | Key::AuthorCount
+            | Key::BuildDate
+            | Key::BuildHostingUrl
+            | Key::BuildOs
+            | Key::BuildOsFamily
+            | Key::BuildTag
+            | Key::Ci
+            | Key::CommitCount
+            | Key::CommitShaShort
+            | Key::CommitsSinceTag
+            | Key::ContainerImageName
+            | Key::ContainerImageTag
+            | Key::Custom(_)
+            | Key::Dirty
+            | Key::FirstCommitDate
+            | Key::IsRelease
+            | Key::License
+            | Key::Licenses
+            | Key::NameMachineReadable
+            | Key::PreviousTag
+            | Key::RepoCloneUrl
+            | Key::RepoCloneUrlSsh
+            | Key::RepoCommitPrefixUrl
+            | Key::RepoCommitUrl
+            | Key::RepoCompareUrl
+            | Key::RepoIssuesUrl
+            | Key::RepoNamespace
+            | Key::RepoNewMergeRequestUrl
+            | Key::RepoOwner
+            | Key::RepoPipelinesUrl
+            | Key::RepoRawUrlBase
+            | Key::RepoRawVersionedPrefixUrl
+            | Key::RepoReleasesUrl
+            | Key::RepoSlug
+            | Key::RepoTagUrl
+            | Key::RepoTreeUrl
+            | Key::RepoVersionedDirPrefixUrl
+            | Key::RepoVersionedFilePrefixUrl
+            | Key::RepoVersionedFileUrlBase
+            | Key::RepoWebUrl
+            | Key::RepoWikiUrl
+            | Key::SourceDateEpoch
+            | Key::VersionBump
+            | Key::VersionDate
+            | Key::VersionDebian
+            | Key::VersionMajor
+            | Key::VersionMinor
+            | Key::VersionNext
+            | Key::VersionPatch
+            | Key::VersionPep440
+            | Key::VersionPrerelease => None,
+            Key::BuildBranch => inputs(&["BRANCH_NAME"]),
+            Key::BuildNumber => inputs(&["BUILD_NUMBER"]),
+            Key::CommitSha => inputs(&["GIT_COMMIT"]),
+            Key::Name => inputs(&["APP_NAME"]),
+            Key::Version => inputs(&["VERSION"]),
+        }
+    }
+
     #[remain::check]
     fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes {
         Ok(
             #[remain::sorted]
             match key {
                 Key::BuildArch
+                | Key::AuthorCount
                 | Key::BuildDate
                 | Key::BuildHostingUrl
                 | Key::BuildOs
                 | Key::BuildOsFamily
                 | Key::BuildTag
                 | Key::Ci
+                | Key::CommitCount
+                | Key::CommitShaShort
+                | Key::CommitsSinceTag
+                | Key::ContainerImageName
+                | Key::ContainerImageTag
+                | Key::Custom(_)
+                | Key::Dirty
+                | Key::FirstCommitDate
+                | Key::IsRelease
                 | Key::License
                 | Key::Licenses
                 | Key::NameMachineReadable
+                | Key::PreviousTag
                 | Key::RepoCloneUrl
                 | Key::RepoCloneUrlSsh
                 | Key::RepoCommitPrefixUrl
+                | Key::RepoCommitUrl
+                | Key::RepoCompareUrl
                 | Key::RepoIssuesUrl
+                | Key::RepoNamespace
+                | Key::RepoNewMergeRequestUrl
+                | Key::RepoOwner
+                | Key::RepoPipelinesUrl
+                | Key::RepoRawUrlBase
                 | Key::RepoRawVersionedPrefixUrl
+                | Key::RepoReleasesUrl
+                | Key::RepoSlug
+                | Key::RepoTagUrl
+                | Key::RepoTreeUrl
                 | Key::RepoVersionedDirPrefixUrl
                 | Key::RepoVersionedFilePrefixUrl
+                | Key::RepoVersionedFileUrlBase
                 | Key::RepoWebUrl
-                | Key::VersionDate => None,
+                | Key::RepoWikiUrl
+                | Key::SourceDateEpoch
+                | Key::VersionBump
+                | Key::VersionDate
+                | Key::VersionDebian
+                | Key::VersionMajor
+                | Key::VersionMinor
+                | Key::VersionNext
+                | Key::VersionPatch
+                | Key::VersionPep440
+                | Key::VersionPrerelease => None,
                 Key::BuildBranch => var(environment, "BRANCH_NAME", C_HIGH),
                 Key::BuildNumber => var(environment, "BUILD_NUMBER", C_HIGH),
+                Key::CommitSha => var(environment, "GIT_COMMIT", C_HIGH),
                 Key::Name => var(environment, "APP_NAME", C_HIGH),
                 Key::Version => var(environment, "VERSION", C_HIGH), // Alternatively (but makes no sense to use): var(environment, "PULL_BASE_SHA")
             },
@@ -0,0 +1,159 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Generates an overview of which sources supply which keys,
+//! and which raw inputs (mostly CI environment variables)
+//! they consult to do so.
+
+use serde_json::{json, Map, Value};
+use strum_macros::{EnumString, EnumVariantNames, IntoStaticStr};
+
+use crate::environment::Environment;
+use crate::var::{self, Key};
+
+use super::VarSource;
+
+/// The cell content for a source that supplies a key
+/// without consulting any raw inputs of its own
+/// (like the selector).
+const SUPPLIED_WITHOUT_INPUTS: &str = "✓";
+/// The cell content for a source that never supplies a key.
+const NOT_SUPPLIED: &str = "-";
+
+#[derive(Debug, EnumString, EnumVariantNames, IntoStaticStr, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    /// A table in Markdown syntax
+    Markdown,
+    /// Comma separated values, with a header row
+    Csv,
+    /// A JSON object, listing the sources and the inputs for each key
+    Json,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self::Markdown
+    }
+}
+
+fn cell(inputs: Option<&Vec<String>>, quote: &str) -> String {
+    match inputs {
+        None => NOT_SUPPLIED.to_owned(),
+        Some(inputs) if inputs.is_empty() => SUPPLIED_WITHOUT_INPUTS.to_owned(),
+        Some(inputs) => inputs
+            .iter()
+            .map(|input| format!("{}{}{}", quote, input, quote))
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+fn to_markdown(environment: &Environment, sources: &[Box<dyn VarSource>]) -> String {
+    let mut table = String::new();
+    table.push_str("| Key | Env-Key | Description |");
+    for source in sources {
+        table.push(' ');
+        table.push_str(&source.short_name());
+        table.push_str(" |");
+    }
+    table.push_str("\n|");
+    for _table_sep_index in 0..(sources.len() + 3) {
+        table.push_str(" --- |");
+    }
+    table.push('\n');
+    for key in Key::iter_all() {
+        let variable = var::get(key);
+        table.push_str(&format!(
+            "| {:?} | `{}` | {} |",
+            key,
+            variable.key(environment),
+            variable.description.replace('|', "\\|")
+        ));
+        for source in sources {
+            table.push(' ');
+            table.push_str(&cell(source.supplies(environment, key).as_ref(), "`"));
+            table.push_str(" |");
+        }
+        table.push('\n');
+    }
+    table
+}
+
+fn to_csv(environment: &Environment, sources: &[Box<dyn VarSource>]) -> String {
+    let mut table = String::new();
+    table.push_str("Key,Env-Key,Description");
+    for source in sources {
+        table.push(',');
+        table.push_str(&csv_field(&source.short_name()));
+    }
+    table.push('\n');
+    for key in Key::iter_all() {
+        let variable = var::get(key);
+        table.push_str(&format!(
+            "{:?},{},{}",
+            key,
+            csv_field(&variable.key(environment)),
+            csv_field(variable.description)
+        ));
+        for source in sources {
+            table.push(',');
+            table.push_str(&csv_field(&cell(
+                source.supplies(environment, key).as_ref(),
+                "",
+            )));
+        }
+        table.push('\n');
+    }
+    table
+}
+
+fn to_json(environment: &Environment, sources: &[Box<dyn VarSource>]) -> String {
+    let keys: Vec<Value> = Key::iter_all()
+        .map(|key| {
+            let variable = var::get(key);
+            let mut supplied_by = Map::new();
+            for source in sources {
+                if let Some(inputs) = source.supplies(environment, key) {
+                    supplied_by.insert(source.short_name(), json!(inputs));
+                }
+            }
+            json!({
+                "key": format!("{:?}", key),
+                "env_key": variable.key(environment),
+                "description": variable.description,
+                "sources": supplied_by,
+            })
+        })
+        .collect();
+    let source_names: Vec<String> = sources.iter().map(|source| source.short_name()).collect();
+    let matrix = json!({
+        "sources": source_names,
+        "keys": keys,
+    });
+    format!("{:#}\n", matrix)
+}
+
+/// Creates a matrix of all the keys (y) and the given sources (x),
+/// containing the raw inputs each source consults
+/// to supply the value for each key.
+#[must_use]
+pub fn generate(
+    environment: &Environment,
+    sources: &[Box<dyn VarSource>],
+    format: Format,
+) -> String {
+    match format {
+        Format::Markdown => to_markdown(environment, sources),
+        Format::Csv => to_csv(environment, sources),
+        Format::Json => to_json(environment, sources),
+    }
+}
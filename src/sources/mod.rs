@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 pub mod bitbucket_ci;
+pub mod custom;
 pub mod deriver;
 pub mod env;
 pub mod fs;
@@ -10,20 +11,38 @@ pub mod git;
 pub mod github_ci;
 pub mod gitlab_ci;
 pub mod jenkins_ci;
+pub mod matrix;
+pub mod plugin;
 pub mod selector;
 pub mod travis_ci;
 
+use std::collections::HashMap;
 use std::path::Path;
 
+use serde::Deserialize;
+use strum_macros::{EnumString, EnumVariantNames, IntoStaticStr};
 use thiserror::Error;
 
 use lazy_static::lazy_static;
 
 use crate::environment::Environment;
+use crate::value::Value;
 use crate::var::{Confidence, Key, C_HIGH};
 use crate::{std_error, tools, value_conversions};
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
+#[derive(
+    Debug,
+    EnumString,
+    EnumVariantNames,
+    IntoStaticStr,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Deserialize,
+)]
 pub enum Hierarchy {
     Low,
     Middle,
@@ -54,6 +73,12 @@ pub enum Error {
     #[error(transparent)]
     IO(#[from] std::io::Error),
 
+    #[error(transparent)]
+    Plugin(#[from] plugin::Error),
+
+    #[error("Unknown source '{0}'; see --list-sources for the available ones")]
+    UnknownSource(String),
+
     /// Represents all other cases of `std_error::Error`.
     #[error(transparent)]
     Std(#[from] std_error::Error),
@@ -64,6 +89,7 @@ pub enum Error {
 }
 
 type RetrieveRes = Result<Option<(Confidence, String)>, Error>;
+type SelectRes = Result<Option<(Confidence, Value)>, Error>;
 
 pub trait VarSource {
     /// Indicates whether this source of variables is usable.
@@ -80,6 +106,15 @@ pub trait VarSource {
     /// This is used for display and sorting.
     fn type_name(&self) -> &'static str;
 
+    /// A short name of the source, like "git" or "travis_ci",
+    /// as used on the command-line and as the sources matrix column header.
+    fn short_name(&self) -> String {
+        self.type_name()
+            .trim_start_matches("projvar::sources::")
+            .trim_end_matches("::VarSource")
+            .to_owned()
+    }
+
     /// The properties (usually parameters to `Self::new`)
     /// of the particular instance of an object of this trait.
     /// This is used for display and sorting.
@@ -91,6 +126,24 @@ pub trait VarSource {
         format!("{}{:?}", self.type_name(), self.properties())
     }
 
+    /// The keys whose values this source uses
+    /// to derive the value for `key` from,
+    /// if it derives values from values gathered before
+    /// (see [`deriver::VarSource`]).
+    /// Sources that fetch values from elsewhere return an empty list.
+    fn derivation_inputs(&self, _key: Key) -> &'static [Key] {
+        &[]
+    }
+
+    /// Whether this source is able to supply a value for `key`,
+    /// and if so, the raw inputs it consults to do so
+    /// (usually names of environment variables).
+    /// Returns `None` if this source never supplies a value for `key`,
+    /// in which case we do not bother calling [`Self::retrieve`] for it.
+    /// This is also used to generate the sources matrix
+    /// (see [`matrix`]).
+    fn supplies(&self, environment: &Environment, key: Key) -> Option<Vec<String>>;
+
     /// Tries to retrieve the value of a single `key`.
     ///
     /// # Errors
@@ -100,6 +153,65 @@ pub trait VarSource {
     /// or innumerable other kinds of problems,
     /// depending on the kind of the source.
     fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes;
+
+    /// Tries to select the value of a single `key`
+    /// out of the (already typed) values gathered before,
+    /// which saves rendering and re-parsing them.
+    /// Only sources that do not retrieve new values,
+    /// but choose among the existing ones (see [`selector::VarSource`]),
+    /// do this; all others return `None`,
+    /// and are asked through [`Self::retrieve`] instead.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::retrieve`].
+    fn select(&self, _environment: &mut Environment, _key: Key) -> Option<SelectRes> {
+        None
+    }
+}
+
+/// Wraps a source, overriding its hierarchy.
+struct Prioritized {
+    source: Box<dyn VarSource>,
+    hierarchy: Hierarchy,
+}
+
+impl VarSource for Prioritized {
+    fn is_usable(&self, environment: &mut Environment) -> bool {
+        self.source.is_usable(environment)
+    }
+
+    fn hierarchy(&self) -> Hierarchy {
+        self.hierarchy
+    }
+
+    fn type_name(&self) -> &'static str {
+        self.source.type_name()
+    }
+
+    fn short_name(&self) -> String {
+        self.source.short_name()
+    }
+
+    fn properties(&self) -> &Vec<String> {
+        self.source.properties()
+    }
+
+    fn derivation_inputs(&self, key: Key) -> &'static [Key] {
+        self.source.derivation_inputs(key)
+    }
+
+    fn supplies(&self, environment: &Environment, key: Key) -> Option<Vec<String>> {
+        self.source.supplies(environment, key)
+    }
+
+    fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes {
+        self.source.retrieve(environment, key)
+    }
+
+    fn select(&self, environment: &mut Environment, key: Key) -> Option<SelectRes> {
+        self.source.select(environment, key)
+    }
 }
 
 #[must_use]
@@ -114,6 +226,13 @@ pub fn var(
         .map(|val| (confidence, val.clone()))
 }
 
+/// Converts a list of raw input names into the format
+/// returned by [`VarSource::supplies`].
+#[must_use]
+pub fn inputs(names: &[&str]) -> Option<Vec<String>> {
+    Some(names.iter().map(|name| (*name).to_owned()).collect())
+}
+
 fn ref_ok_or_err<'t>(refr: &str, part: Option<&'t str>) -> Result<&'t str, Error> {
     part.ok_or_else(|| Error::BadLowLevelValue {
         msg: "Invalid git reference, should be 'refs/<TYPE>/<NAME>'".to_owned(),
@@ -167,24 +286,82 @@ fn is_git_repo_root(repo_path: Option<&Path>) -> bool {
     tools::git::Repo::try_from(repo_path).is_ok()
 }
 
+/// All the sources we know of, in the order they are evaluated,
+/// regardless of whether they are usable in the current environment.
+#[must_use]
+pub fn full_list() -> Vec<Box<dyn VarSource>> {
+    let mut sources: Vec<Box<dyn VarSource>> = vec![Box::new(git::VarSource {})];
+    sources.extend(non_git_list());
+    sources
+}
+
+fn non_git_list() -> Vec<Box<dyn VarSource>> {
+    let mut sources: Vec<Box<dyn VarSource>> = vec![
+        Box::new(fs::VarSource {}),
+        Box::new(bitbucket_ci::VarSource {}),
+        Box::new(github_ci::VarSource {}),
+        Box::new(gitlab_ci::VarSource {}),
+        Box::new(jenkins_ci::VarSource {}),
+        Box::new(travis_ci::VarSource {}),
+        Box::new(custom::VarSource {}),
+        Box::new(env::VarSource {}),
+        Box::new(selector::VarSource {}),
+        Box::new(deriver::VarSource {}),
+    ];
+    sources.extend(plugin::list());
+    sources
+}
+
+/// Filters the given sources and overrides their hierarchies,
+/// each identified by its short name (see [`VarSource::short_name`]).
+/// If `enabled` is not empty, only those sources are kept;
+/// those in `disabled` are removed in any case.
+///
+/// # Errors
+///
+/// If any of the given names does not refer to a known source.
+pub fn configure(
+    sources: Vec<Box<dyn VarSource>>,
+    enabled: &[&str],
+    disabled: &[&str],
+    hierarchies: &HashMap<String, Hierarchy>,
+) -> Result<Vec<Box<dyn VarSource>>, Error> {
+    let known = full_list();
+    let known_names: Vec<String> = known.iter().map(|source| source.short_name()).collect();
+    for name in enabled
+        .iter()
+        .chain(disabled.iter())
+        .copied()
+        .chain(hierarchies.keys().map(String::as_str))
+    {
+        if !known_names.iter().any(|known_name| known_name == name) {
+            return Err(Error::UnknownSource(name.to_owned()));
+        }
+    }
+    Ok(sources
+        .into_iter()
+        .filter(|source| {
+            let name = source.short_name();
+            (enabled.is_empty() || enabled.contains(&name.as_str()))
+                && !disabled.contains(&name.as_str())
+        })
+        .map(|source| match hierarchies.get(&source.short_name()) {
+            Some(hierarchy) => Box::new(Prioritized {
+                source,
+                hierarchy: *hierarchy,
+            }),
+            None => source,
+        })
+        .collect())
+}
+
 #[must_use]
 pub fn default_list(repo_path: &Path) -> Vec<Box<dyn VarSource>> {
     let mut sources: Vec<Box<dyn VarSource>> = vec![];
     if is_git_repo_root(Some(repo_path)) {
         sources.push(Box::new(git::VarSource {}));
     }
-    sources.push(Box::new(fs::VarSource {}));
-    sources.push(Box::new(bitbucket_ci::VarSource {}));
-    sources.push(Box::new(github_ci::VarSource {}));
-    sources.push(Box::new(gitlab_ci::VarSource {}));
-    sources.push(Box::new(jenkins_ci::VarSource {}));
-    sources.push(Box::new(travis_ci::VarSource {}));
-    sources.push(Box::new(env::VarSource {}));
-    sources.push(Box::new(selector::VarSource {}));
-    sources.push(Box::new(deriver::VarSource {}));
-    // NOTE We add the deriver a second time,
-    //      so it may derive from values created in the first run.
-    sources.push(Box::new(deriver::VarSource {}));
+    sources.extend(non_git_list());
     if log::log_enabled!(log::Level::Trace) {
         for source in &sources {
             log::trace!("Registered source {}.", source.display());
@@ -0,0 +1,645 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Sources values from external executables ("plugins"),
+//! which allows to supply values from in-house systems
+//! (for example a local artifact database)
+//! without changing projvar itself.
+//! As they are defined in the configuration file,
+//! which usually comes with the (possibly untrusted) project,
+//! they are only run if allowed on the command-line (`--allow-plugins`).
+//!
+//! The plugin is run once, with the project root as working directory.
+//! It receives a JSON request on stdin,
+//! which contains only the input variables the plugin declared
+//! (see [`Definition::inputs`]),
+//! so it never gets to see secrets it did not ask for:
+//!
+//! ```json
+//! {
+//!   "protocol": 1,
+//!   "project_root": "/home/user/src/my-project",
+//!   "keys": ["Version", "BuildNumber"],
+//!   "inputs": { "CI": "true", "BUILD_ID": "42" }
+//! }
+//! ```
+//!
+//! It answers with a JSON response on stdout,
+//! leaving out the keys it has no value for.
+//! The confidence (0 to 255) is optional, and defaults to 50:
+//!
+//! ```json
+//! {
+//!   "values": {
+//!     "Version": { "value": "1.2.3", "confidence": 75 },
+//!     "BuildNumber": { "value": "42" }
+//!   }
+//! }
+//! ```
+//!
+//! A non-zero exit status is considered a failure.
+//! Failures, including timeouts and invalid responses,
+//! are logged as warnings, and leave the plugin without values,
+//! so they do not abort the run.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::RwLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::environment::Environment;
+use crate::var::{Confidence, Key, C_MIDDLE};
+
+use super::Hierarchy;
+use super::RetrieveRes;
+
+/// The version of the protocol spoken with plugins.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+const fn default_hierarchy() -> Hierarchy {
+    Hierarchy::Middle
+}
+
+const fn default_timeout() -> u64 {
+    10
+}
+
+const fn default_confidence() -> Confidence {
+    C_MIDDLE
+}
+
+/// A plugin, as declared in the configuration file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Definition {
+    /// Used to identify the plugin in logs and error messages.
+    pub name: String,
+    /// The executable, followed by its arguments,
+    /// for example `["scripts/projvar-artifacts.sh", "--quick"]`.
+    /// A relative executable path is resolved against the project root.
+    pub command: Vec<String>,
+    /// How much we trust the values of this plugin
+    /// over the ones of other sources.
+    #[serde(default = "default_hierarchy")]
+    pub hierarchy: Hierarchy,
+    /// Seconds after which we give up on the plugin.
+    #[serde(default = "default_timeout")]
+    pub timeout: u64,
+    /// The names of the keys this plugin supplies values for.
+    /// If empty, it is asked for all keys.
+    #[serde(default)]
+    pub keys: Vec<String>,
+    /// The names of the input variables this plugin gets to see,
+    /// for example `["CI", "BUILD_ID"]`.
+    /// No others are sent to it, as they might contain secrets.
+    #[serde(default)]
+    pub inputs: Vec<String>,
+}
+
+/// A registered plugin.
+#[derive(Debug, Clone)]
+struct Plugin {
+    name: String,
+    command: Vec<String>,
+    hierarchy: Hierarchy,
+    timeout: Duration,
+    keys: Option<Vec<Key>>,
+    inputs: Vec<String>,
+}
+
+/// This enumerates all possible errors returned by this module.
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("The plugin '{name}' has an empty command")]
+    EmptyCommand { name: String },
+
+    #[error("The plugin '{name}' exited with {status}: {stderr}")]
+    Failed {
+        name: String,
+        status: std::process::ExitStatus,
+        stderr: String,
+    },
+
+    #[error("The plugin '{name}' sent an invalid response: {source}")]
+    InvalidResponse {
+        name: String,
+        source: serde_json::Error,
+    },
+
+    #[error("Failed to run the plugin '{name}': {source}")]
+    IO {
+        name: String,
+        source: std::io::Error,
+    },
+
+    #[error("The plugin '{name}' did not finish within {timeout} seconds")]
+    Timeout { name: String, timeout: u64 },
+
+    #[error("The plugin '{name}' declares the unknown key '{key}'")]
+    UnknownKey { name: String, key: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseValue {
+    value: String,
+    #[serde(default = "default_confidence")]
+    confidence: Confidence,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    #[serde(default)]
+    values: HashMap<String, ResponseValue>,
+}
+
+lazy_static! {
+    static ref REGISTRY: RwLock<Vec<Plugin>> = RwLock::new(vec![]);
+}
+
+/// Registers plugins, which makes them part of the default sources
+/// (see [`super::default_list`]).
+/// This has to be called after registering the custom keys
+/// (see [`crate::custom_keys::register`]),
+/// so plugins may supply values for them.
+///
+/// # Errors
+///
+/// If a plugin has an empty command,
+/// or declares an unknown key.
+pub fn register(definitions: &[Definition]) -> Result<(), Error> {
+    let mut plugins = Vec::with_capacity(definitions.len());
+    for definition in definitions {
+        if definition.command.is_empty() {
+            return Err(Error::EmptyCommand {
+                name: definition.name.clone(),
+            });
+        }
+        let keys = if definition.keys.is_empty() {
+            None
+        } else {
+            let mut keys = Vec::with_capacity(definition.keys.len());
+            for key_name in &definition.keys {
+                keys.push(Key::from_name(key_name).map_err(|_err| Error::UnknownKey {
+                    name: definition.name.clone(),
+                    key: key_name.clone(),
+                })?);
+            }
+            Some(keys)
+        };
+        plugins.push(Plugin {
+            name: definition.name.clone(),
+            command: definition.command.clone(),
+            hierarchy: definition.hierarchy,
+            timeout: Duration::from_secs(definition.timeout),
+            keys,
+            inputs: definition.inputs.clone(),
+        });
+    }
+    let mut registry = REGISTRY.write().unwrap();
+    for plugin in plugins {
+        log::trace!("Registered plugin {}.", plugin.name);
+        registry.push(plugin);
+    }
+    Ok(())
+}
+
+/// Runs `task` in a new thread,
+/// and returns a receiver for its result,
+/// which - unlike joining the thread - allows to wait with a time limit.
+fn spawn_with_result<T, F>(task: F) -> Receiver<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // Fails if we gave up waiting for the result already
+        let _ignored = sender.send(task());
+    });
+    receiver
+}
+
+/// Creates a source for each registered plugin.
+#[must_use]
+pub fn list() -> Vec<Box<dyn super::VarSource>> {
+    REGISTRY
+        .read()
+        .unwrap()
+        .iter()
+        .map(|plugin| Box::new(VarSource::new(plugin.clone())) as Box<dyn super::VarSource>)
+        .collect()
+}
+
+/// Sources values from an external executable,
+/// see the [module documentation](self).
+pub struct VarSource {
+    plugin: Plugin,
+    properties: Vec<String>,
+    /// The values supplied by the plugin,
+    /// once it was run
+    values: RefCell<Option<HashMap<Key, (Confidence, String)>>>,
+}
+
+impl VarSource {
+    fn new(plugin: Plugin) -> Self {
+        let properties = vec![plugin.name.clone(), plugin.command.join(" ")];
+        Self {
+            plugin,
+            properties,
+            values: RefCell::new(None),
+        }
+    }
+
+    fn supplies_key(&self, key: Key) -> bool {
+        match &self.plugin.keys {
+            None => true,
+            Some(keys) => keys.contains(&key),
+        }
+    }
+
+    fn io_err(&self, source: std::io::Error) -> Error {
+        Error::IO {
+            name: self.plugin.name.clone(),
+            source,
+        }
+    }
+
+    fn timeout_err(&self) -> Error {
+        Error::Timeout {
+            name: self.plugin.name.clone(),
+            timeout: self.plugin.timeout.as_secs(),
+        }
+    }
+
+    /// Waits for the output of the plugin on one of its pipes,
+    /// but not beyond `deadline`.
+    fn receive(
+        &self,
+        receiver: &Receiver<io::Result<String>>,
+        deadline: Instant,
+        pipe: &str,
+    ) -> Result<String, Error> {
+        match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            Ok(output) => output.map_err(|err| self.io_err(err)),
+            Err(RecvTimeoutError::Timeout) => Err(self.timeout_err()),
+            Err(RecvTimeoutError::Disconnected) => Err(self.io_err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                format!("The thread reading the plugin {} died", pipe),
+            ))),
+        }
+    }
+
+    fn request(&self, environment: &Environment) -> String {
+        let keys: Vec<&str> = match &self.plugin.keys {
+            None => Key::iter_all().map(Key::name).collect(),
+            Some(keys) => keys.iter().map(|key| key.name()).collect(),
+        };
+        let inputs: HashMap<&str, &str> = self
+            .plugin
+            .inputs
+            .iter()
+            .filter_map(|name| {
+                environment
+                    .vars
+                    .get(name)
+                    .map(|value| (name.as_str(), value.as_str()))
+            })
+            .collect();
+        json!({
+            "protocol": PROTOCOL_VERSION,
+            "project_root": environment.settings.repo_path,
+            "keys": keys,
+            "inputs": inputs,
+        })
+        .to_string()
+    }
+
+    /// Runs the plugin, and returns what it wrote to stdout.
+    fn run(&self, environment: &Environment) -> Result<String, Error> {
+        let (executable, args) = self.plugin.command.split_first().expect(
+            "The command was checked to be non-empty when registering the plugin; should never happen",
+        );
+        let mut executable = PathBuf::from(executable);
+        let repo_path = environment.settings.repo_path.as_ref();
+        if let Some(repo_path) = repo_path {
+            // Only paths like "scripts/plugin.sh" are resolved against the project root;
+            // bare names like "plugin.sh" are looked up in the `PATH`.
+            if executable.is_relative() && executable.components().count() > 1 {
+                executable = repo_path.join(executable);
+            }
+        }
+        let mut command = Command::new(&executable);
+        if let Some(repo_path) = repo_path {
+            command.current_dir(repo_path);
+        }
+        let mut child = command
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| self.io_err(err))?;
+
+        // Write and read in separate threads,
+        // so a plugin that does not consume its input
+        // or produces a lot of output can not block us.
+        let mut stdin = child.stdin.take().expect("stdin was requested to be piped");
+        let request = self.request(environment);
+        let writer = spawn_with_result(move || stdin.write_all(request.as_bytes()));
+        let mut stdout = child
+            .stdout
+            .take()
+            .expect("stdout was requested to be piped");
+        let stdout_reader = spawn_with_result(move || {
+            let mut output = String::new();
+            stdout.read_to_string(&mut output).map(|_len| output)
+        });
+        let mut stderr = child
+            .stderr
+            .take()
+            .expect("stderr was requested to be piped");
+        let stderr_reader = spawn_with_result(move || {
+            let mut output = String::new();
+            stderr.read_to_string(&mut output).map(|_len| output)
+        });
+
+        let deadline = Instant::now() + self.plugin.timeout;
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(|err| self.io_err(err))? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                // The plugin might have terminated just now
+                let _ignored = child.kill();
+                let _ignored = child.wait();
+                return Err(self.timeout_err());
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+
+        // The pipes may outlive the plugin,
+        // for example if it left a child process running in the background,
+        // so we only wait for the output until the deadline.
+        let stdout = self.receive(&stdout_reader, deadline, "stdout")?;
+        let stderr = self.receive(&stderr_reader, deadline, "stderr")?;
+        if let Ok(Err(err)) = writer.try_recv() {
+            // A plugin that does not need its input may well exit
+            // before reading it, so this is no reason to fail.
+            log::debug!(
+                "Failed to write the request to plugin '{}': {}",
+                self.plugin.name,
+                err
+            );
+        }
+        if !status.success() {
+            return Err(Error::Failed {
+                name: self.plugin.name.clone(),
+                status,
+                stderr: stderr.trim().to_owned(),
+            });
+        }
+        if !stderr.is_empty() {
+            log::debug!("Plugin '{}' stderr:\n{}", self.plugin.name, stderr.trim());
+        }
+        Ok(stdout)
+    }
+
+    fn parse_response(&self, response: &str) -> Result<HashMap<Key, (Confidence, String)>, Error> {
+        let response: Response =
+            serde_json::from_str(response).map_err(|source| Error::InvalidResponse {
+                name: self.plugin.name.clone(),
+                source,
+            })?;
+        let mut values = HashMap::new();
+        for (key_name, value) in response.values {
+            match Key::from_name(&key_name) {
+                Ok(key) if self.supplies_key(key) => {
+                    values.insert(key, (value.confidence, value.value));
+                }
+                _ => log::warn!(
+                    "Plugin '{}' supplied a value for the unexpected key '{}'; ignoring it",
+                    self.plugin.name,
+                    key_name
+                ),
+            }
+        }
+        Ok(values)
+    }
+}
+
+impl super::VarSource for VarSource {
+    fn is_usable(&self, _environment: &mut Environment) -> bool {
+        true
+    }
+
+    fn hierarchy(&self) -> Hierarchy {
+        self.plugin.hierarchy
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<VarSource>()
+    }
+
+    /// Plugins are told apart by their configured name,
+    /// for example "plugin:artifacts".
+    fn short_name(&self) -> String {
+        format!("plugin:{}", self.plugin.name)
+    }
+
+    fn properties(&self) -> &Vec<String> {
+        &self.properties
+    }
+
+    fn supplies(&self, _environment: &Environment, key: Key) -> Option<Vec<String>> {
+        if self.supplies_key(key) {
+            Some(vec![format!("plugin: {}", self.plugin.name)])
+        } else {
+            None
+        }
+    }
+
+    fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes {
+        if self.values.borrow().is_none() {
+            log::debug!("Running plugin '{}' ...", self.plugin.name);
+            let values = self
+                .run(environment)
+                .and_then(|response| self.parse_response(&response))
+                .unwrap_or_else(|err| {
+                    log::warn!("{}; ignoring this plugin", err);
+                    HashMap::new()
+                });
+            self.values.replace(Some(values));
+        }
+        Ok(self
+            .values
+            .borrow()
+            .as_ref()
+            .and_then(|values| values.get(&key).cloned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::VarSource as _;
+    use super::*;
+
+    fn source(command: &[&str], timeout: Duration, keys: Option<Vec<Key>>) -> VarSource {
+        VarSource::new(Plugin {
+            name: "test".to_owned(),
+            command: command.iter().map(|part| (*part).to_owned()).collect(),
+            hierarchy: default_hierarchy(),
+            timeout,
+            keys,
+            inputs: vec!["BUILD_ID".to_owned()],
+        })
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let source = source(
+            &["true"],
+            Duration::from_secs(1),
+            Some(vec![Key::Version, Key::BuildNumber]),
+        );
+        let values = source
+            .parse_response(
+                r#"{ "values": {
+                    "Version": { "value": "1.2.3", "confidence": 75 },
+                    "BuildNumber": { "value": "42" },
+                    "Name": { "value": "undeclared" }
+                } }"#,
+            )
+            .unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[&Key::Version], (75, "1.2.3".to_owned()));
+        assert_eq!(values[&Key::BuildNumber], (C_MIDDLE, "42".to_owned()));
+
+        assert!(source.parse_response("{}").unwrap().is_empty());
+        assert!(matches!(
+            source.parse_response("not JSON"),
+            Err(Error::InvalidResponse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_short_name() {
+        let source = source(&["true"], Duration::from_secs(1), None);
+        assert_eq!(source.short_name(), "plugin:test");
+    }
+
+    #[test]
+    fn test_request_only_declared_inputs() {
+        let mut environment = Environment::stub();
+        environment
+            .vars
+            .insert("BUILD_ID".to_owned(), "42".to_owned());
+        environment
+            .vars
+            .insert("CI_JOB_TOKEN".to_owned(), "secret".to_owned());
+        let request = source(&["true"], Duration::from_secs(1), None).request(&environment);
+        let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+        assert_eq!(request["inputs"], json!({ "BUILD_ID": "42" }));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run() {
+        let environment = Environment::stub();
+        let output = source(
+            &["sh", "-c", r#"cat > /dev/null; echo '{ "values": {} }'"#],
+            Duration::from_secs(10),
+            None,
+        )
+        .run(&environment)
+        .unwrap();
+        assert_eq!(output.trim(), r#"{ "values": {} }"#);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_failure() {
+        let environment = Environment::stub();
+        let result = source(
+            &["sh", "-c", "echo broken >&2; exit 3"],
+            Duration::from_secs(10),
+            None,
+        )
+        .run(&environment);
+        assert!(matches!(result, Err(Error::Failed { stderr, .. }) if stderr == "broken"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_timeout() {
+        let environment = Environment::stub();
+        let result = source(&["sleep", "10"], Duration::from_millis(100), None).run(&environment);
+        assert!(matches!(result, Err(Error::Timeout { .. })));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_timeout_output() {
+        // The background process keeps stdout open after the plugin exits
+        let environment = Environment::stub();
+        let started = Instant::now();
+        let result = source(
+            &["sh", "-c", "sleep 10 & echo '{}'"],
+            Duration::from_millis(200),
+            None,
+        )
+        .run(&environment);
+        assert!(matches!(result, Err(Error::Timeout { .. })));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_retrieve_ignores_failures() {
+        let mut environment = Environment::stub();
+        for command in [
+            &["sh", "-c", "exit 1"][..],
+            &["sh", "-c", "echo 'not JSON'"],
+            &["sleep", "10"],
+        ] {
+            let source = source(command, Duration::from_millis(100), None);
+            assert!(source
+                .retrieve(&mut environment, Key::Version)
+                .unwrap()
+                .is_none());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_retrieve() {
+        let mut environment = Environment::stub();
+        let source = source(
+            &[
+                "sh",
+                "-c",
+                r#"echo '{ "values": { "Version": { "value": "1.2.3" } } }'"#,
+            ],
+            Duration::from_secs(10),
+            None,
+        );
+        assert_eq!(
+            source.retrieve(&mut environment, Key::Version).unwrap(),
+            Some((C_MIDDLE, "1.2.3".to_owned()))
+        );
+        assert!(source
+            .retrieve(&mut environment, Key::Name)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -3,12 +3,14 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use crate::environment::Environment;
+use crate::tools::secrets;
 use crate::validator;
 use crate::var::Confidence;
 use crate::var::Key;
 
 use super::Hierarchy;
 use super::RetrieveRes;
+use super::SelectRes;
 
 use std::convert::TryFrom;
 
@@ -53,14 +55,26 @@ impl super::VarSource for VarSource {
         &super::NO_PROPS
     }
 
+    fn supplies(&self, _environment: &Environment, _key: Key) -> Option<Vec<String>> {
+        // We select from the values supplied by the other sources
+        Some(vec![])
+    }
+
     fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes {
+        Ok(self
+            .select(environment, key)
+            .transpose()?
+            .flatten()
+            .map(|(confidence, value)| (confidence, value.to_string())))
+    }
+
+    fn select(&self, environment: &mut Environment, key: Key) -> Option<SelectRes> {
         let values = &environment.output.get_all(key);
-        Ok(match values {
+        Some(Ok(match values {
             Some(values) => {
                 let mut enriched_values = vec![];
                 for (src_index, (confidence, value)) in (*values).clone() {
-                    let specific_validator = validator::get(key);
-                    let validity = specific_validator(environment, &value);
+                    let validity = validator::validate(environment, key, &value);
                     enriched_values.push((src_index, (confidence, value), validity));
                 }
                 enriched_values.sort_by_cached_key(|entry| {
@@ -68,7 +82,7 @@ impl super::VarSource for VarSource {
                     log::trace!("Valor evaluated for {:?} from source {}, value '{}' is {:?}.",
                         key,
                         entry.0,
-                        entry.1.1,
+                        secrets::redact(&entry.1.1.to_string()),
                         valor
                     );
                     log::trace!("    ... evaluated from (validity, confidence, source_index): ({:?}, {}, {})",
@@ -78,9 +92,13 @@ impl super::VarSource for VarSource {
                     );
                     valor
                 });
-                enriched_values.last().map(|entry| entry.1.clone())
+                enriched_values
+                    .last()
+                    .map(|(_src_index, (confidence, value), _validity)| {
+                        (*confidence, value.clone())
+                    })
             }
             None => None,
-        })
+        }))
     }
 }
@@ -6,6 +6,7 @@ use crate::environment::Environment;
 use crate::var::Key;
 use crate::var::C_HIGH;
 
+use super::inputs;
 use super::var;
 use super::Hierarchy;
 use super::RetrieveRes;
@@ -33,36 +34,134 @@ impl super::VarSource for VarSource {
         &super::NO_PROPS
     }
 
+    #[remain::check]
+    fn supplies(&self, _environment: &Environment, key: Key) -> Option<Vec<String>> {
+        #[remain::sorted]
+        match key {
+            Key::BuildArch => inputs(&["TRAVIS_CPU_ARCH"]),
+            Key::BuildDate
+            | Key::AuthorCount
+            | Key::BuildHostingUrl
+            | Key::BuildOsFamily
+            | Key::Ci
+            | Key::CommitCount
+            | Key::CommitShaShort
+            | Key::CommitsSinceTag
+            | Key::ContainerImageName
+            | Key::ContainerImageTag
+            | Key::Custom(_)
+            | Key::Dirty
+            | Key::FirstCommitDate
+            | Key::IsRelease
+            | Key::License
+            | Key::Licenses
+            | Key::NameMachineReadable
+            | Key::PreviousTag
+            | Key::RepoCloneUrl
+            | Key::RepoCloneUrlSsh
+            | Key::RepoCommitPrefixUrl
+            | Key::RepoCommitUrl
+            | Key::RepoCompareUrl
+            | Key::RepoIssuesUrl
+            | Key::RepoNamespace
+            | Key::RepoNewMergeRequestUrl
+            | Key::RepoOwner
+            | Key::RepoPipelinesUrl
+            | Key::RepoRawUrlBase
+            | Key::RepoRawVersionedPrefixUrl
+            | Key::RepoReleasesUrl
+            | Key::RepoTagUrl
+            | Key::RepoTreeUrl
+            | Key::RepoVersionedDirPrefixUrl
+            | Key::RepoVersionedFilePrefixUrl
+            | Key::RepoVersionedFileUrlBase
+            | Key::RepoWebUrl
+            | Key::RepoWikiUrl
+            | Key::SourceDateEpoch
+            | Key::VersionBump
+            | Key::VersionDate
+            | Key::VersionDebian
+            | Key::VersionMajor
+            | Key::VersionMinor
+            | Key::VersionNext
+            | Key::VersionPatch
+            | Key::VersionPep440
+            | Key::VersionPrerelease => None,
+            Key::BuildBranch => inputs(&["TRAVIS_BRANCH"]),
+            Key::BuildNumber => inputs(&["TRAVIS_BUILD_NUMBER"]),
+            Key::BuildOs => inputs(&["TRAVIS_OS_NAME"]),
+            Key::BuildTag => inputs(&["TRAVIS_TAG"]),
+            Key::CommitSha => inputs(&["TRAVIS_COMMIT"]),
+            Key::Name => inputs(&["TRAVIS_REPO_SLUG"]),
+            Key::RepoSlug => inputs(&["TRAVIS_REPO_SLUG"]),
+            Key::Version => inputs(&["TRAVIS_COMMIT"]),
+        }
+    }
+
     #[remain::check]
     fn retrieve(&self, environment: &mut Environment, key: Key) -> RetrieveRes {
         Ok(
             #[remain::sorted]
             match key {
-                Key::BuildArch
-                | Key::BuildHostingUrl
+                Key::BuildArch => var(environment, "TRAVIS_CPU_ARCH", C_HIGH),
+                Key::BuildHostingUrl
+                | Key::AuthorCount
                 | Key::BuildDate
                 | Key::BuildOsFamily
                 | Key::Ci
+                | Key::CommitCount
+                | Key::CommitShaShort
+                | Key::CommitsSinceTag
+                | Key::ContainerImageName
+                | Key::ContainerImageTag
+                | Key::Custom(_)
+                | Key::Dirty
+                | Key::FirstCommitDate
+                | Key::IsRelease
                 | Key::Licenses
                 | Key::License
                 | Key::NameMachineReadable
+                | Key::PreviousTag
+                | Key::RepoCommitUrl
+                | Key::RepoCompareUrl
                 | Key::RepoIssuesUrl
                 | Key::RepoCloneUrl
                 | Key::RepoCloneUrlSsh
                 | Key::RepoCommitPrefixUrl
+                | Key::RepoNamespace
+                | Key::RepoNewMergeRequestUrl
+                | Key::RepoOwner
+                | Key::RepoPipelinesUrl
+                | Key::RepoRawUrlBase
                 | Key::RepoRawVersionedPrefixUrl
+                | Key::RepoReleasesUrl
+                | Key::RepoTagUrl
+                | Key::RepoTreeUrl
                 | Key::RepoVersionedDirPrefixUrl
                 | Key::RepoVersionedFilePrefixUrl
+                | Key::RepoVersionedFileUrlBase
                 | Key::RepoWebUrl
-                | Key::VersionDate => None,
+                | Key::RepoWikiUrl
+                | Key::SourceDateEpoch
+                | Key::VersionBump
+                | Key::VersionDate
+                | Key::VersionDebian
+                | Key::VersionMajor
+                | Key::VersionMinor
+                | Key::VersionNext
+                | Key::VersionPatch
+                | Key::VersionPep440
+                | Key::VersionPrerelease => None,
                 Key::BuildBranch => var(environment, "TRAVIS_BRANCH", C_HIGH),
                 Key::BuildNumber => var(environment, "TRAVIS_BUILD_NUMBER", C_HIGH),
                 Key::BuildOs => var(environment, "TRAVIS_OS_NAME", C_HIGH),
                 Key::BuildTag => var(environment, "TRAVIS_TAG", C_HIGH),
+                Key::CommitSha => var(environment, "TRAVIS_COMMIT", C_HIGH),
                 Key::Name => crate::value_conversions::slug_to_proj_name(
                     environment.vars.get("TRAVIS_REPO_SLUG"),
                 )?
                 .map(|val| (C_HIGH, val)), // usually: TRAVIS_REPO_SLUG="user/project"
+                Key::RepoSlug => var(environment, "TRAVIS_REPO_SLUG", C_HIGH),
                 Key::Version => var(environment, "TRAVIS_COMMIT", C_HIGH),
             },
         )
@@ -6,23 +6,27 @@ use std::collections::HashMap;
 
 use lazy_static::lazy_static;
 use regex::Regex;
-use strum::IntoEnumIterator;
 
 use crate::{
     environment::Environment,
     sources::VarSource,
+    value::Value,
     var::{self, Confidence, Key, Variable},
 };
 
 /// Key, associated variable meta-data, confidence, primary value.
-pub type Value<'a> = (Key, &'static Variable, &'a (Confidence, String));
+pub type Entry<'a> = (Key, &'static Variable, &'a (Confidence, Value));
 
 /// Stores the property values gathered from all the sources.
 #[derive(Clone)]
 pub struct Storage {
     // key_values: HashMap<Key, Vec<(usize, String)>>,
-    key_values: HashMap<Key, HashMap<usize, (Confidence, String)>>,
-    key_primary: HashMap<Key, (Confidence, String)>,
+    key_values: HashMap<Key, HashMap<usize, (Confidence, Value)>>,
+    key_primary: HashMap<Key, (Confidence, Value)>,
+    /// The values as supplied by the sources,
+    /// for those that were normalized before storing them
+    /// (see [`crate::normalize`])
+    key_raw_values: HashMap<Key, HashMap<usize, String>>,
 }
 
 impl Storage {
@@ -31,6 +35,7 @@ impl Storage {
         Storage {
             key_values: HashMap::new(),
             key_primary: HashMap::new(),
+            key_raw_values: HashMap::new(),
         }
     }
 
@@ -78,23 +83,30 @@ impl Storage {
         }
         table.push('\n');
 
-        // table content (`Key::iter()` is sorted)
-        for key in Key::iter() {
+        // table content (`Key::iter_all()` is sorted)
+        for key in Key::iter_all() {
             let values = self.key_values.get(&key);
             if let Some(values) = values {
                 let variable = var::get(key);
                 table.push_str("| ");
-                table.push_str(key.into());
+                table.push_str(key.name());
                 table.push_str(" | `");
                 table.push_str(&variable.key(environment));
                 table.push_str("` |");
                 for source_index in 0..sources.len() {
                     table.push(' ');
-                    table.push_str(values.get(&source_index).map_or("", |(_c, v)| v));
+                    if let Some((_confidence, value)) = values.get(&source_index) {
+                        table.push_str(&value.render(&environment.settings, key));
+                        if let Some(raw) = self.get_raw(key, source_index) {
+                            table.push_str(&format!(" (from \"{}\")", raw));
+                        }
+                    }
                     table.push_str(" |");
                 }
                 table.push_str(" **");
-                table.push_str(self.get(key).map_or("", |(_c, v)| v));
+                if let Some((_confidence, value)) = self.get(key) {
+                    table.push_str(&value.render(&environment.settings, key));
+                }
                 table.push_str("** |");
                 table.push('\n');
             }
@@ -110,19 +122,21 @@ impl Storage {
     pub fn to_list(&self, environment: &Environment) -> String {
         let values = self.get_wrapup();
         let mut key_strs: HashMap<Key, String> = HashMap::with_capacity(values.len());
-        for (key, variable, _value) in &values {
+        let mut value_strs: HashMap<Key, String> = HashMap::with_capacity(values.len());
+        for (key, variable, (_confidence, value)) in &values {
             let key_str = variable.key(environment);
             key_strs.insert(*key, key_str.as_ref().to_owned());
+            value_strs.insert(*key, value.render(&environment.settings, *key));
         }
         // because the loop below adds 7 strings for each entry
         let mut list = Vec::with_capacity(values.len() * 7);
-        for (key, _variable, (_confidence, value)) in &values {
+        for (key, _variable, _value) in &values {
             list.push("* ");
-            list.push(key.into());
+            list.push(key.name());
             list.push(" - `");
             list.push(&key_strs[key]);
             list.push("` - ");
-            list.push(value);
+            list.push(&value_strs[key]);
             list.push("\n");
         }
         list.concat()
@@ -130,7 +144,7 @@ impl Storage {
 
     /// Returns the primary value associated to a specific key,
     /// if it is in store.
-    pub fn get(&self, key: Key) -> Option<&(Confidence, String)> {
+    pub fn get(&self, key: Key) -> Option<&(Confidence, Value)> {
         // The last entry contains the value of the source
         // with the highest `sources::Hierarchy`
         // that provided a value at all.
@@ -140,14 +154,22 @@ impl Storage {
 
     /// Returns all value by any source
     /// which is associated to the provided key.
-    pub fn get_all(&self, key: Key) -> Option<&HashMap<usize, (Confidence, String)>> {
+    pub fn get_all(&self, key: Key) -> Option<&HashMap<usize, (Confidence, Value)>> {
         self.key_values.get(&key)
     }
 
+    /// Returns the value as supplied by a certain source for the provided key,
+    /// if it was normalized before storing it.
+    pub fn get_raw(&self, key: Key, source_index: usize) -> Option<&String> {
+        self.key_raw_values
+            .get(&key)
+            .and_then(|raw_values| raw_values.get(&source_index))
+    }
+
     /// Builds a sorted list of all the keys with associated:
     /// variable meta-data, confidence, primary value
-    pub fn get_wrapup(&self) -> Vec<Value> {
-        let mut wrapup: Vec<Value> = self
+    pub fn get_wrapup(&self) -> Vec<Entry> {
+        let mut wrapup: Vec<Entry> = self
             .key_primary
             .iter()
             .map(|key_value| {
@@ -162,7 +184,7 @@ impl Storage {
     }
 
     /// Adds the value found for a specific key by a certain source.
-    pub fn add(&mut self, key: Key, source_index: usize, confidence: Confidence, value: String) {
+    pub fn add(&mut self, key: Key, source_index: usize, confidence: Confidence, value: Value) {
         // ... PUH! :O
         // This returns the Vec for key,
         // or creates, inserts and returns a new one,
@@ -173,6 +195,15 @@ impl Storage {
         // here, the last to add, wins (should be the source with the highest hierarchy)
         self.key_primary.insert(key, (confidence, value));
     }
+
+    /// Adds the value supplied for a specific key by a certain source,
+    /// before it was normalized (see [`crate::normalize`]).
+    pub fn add_raw(&mut self, key: Key, source_index: usize, raw_value: String) {
+        self.key_raw_values
+            .entry(key)
+            .or_default()
+            .insert(source_index, raw_value);
+    }
 }
 
 impl Default for Storage {
@@ -3,11 +3,12 @@
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
 use chrono::DateTime;
+use chrono::FixedOffset;
 use chrono::NaiveDateTime;
-use chrono::Utc;
 use git2::{self, Repository};
 use lazy_static::lazy_static;
 use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::path::Path;
 use std::path::PathBuf;
@@ -15,11 +16,6 @@ use std::str;
 
 use crate::BoxResult;
 
-/// The default date format.
-/// For formatting specifiers, see:
-/// <https://docs.rs/chrono/latest/chrono/format/strftime/index.html>
-pub const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
-
 /// Checks whether a given version string is a git dirty version.
 /// Dirty means, there are uncommitted changes.
 #[must_use]
@@ -46,10 +42,32 @@ fn _version(repo: &git2::Repository) -> BoxResult<String> {
         ))?)
 }
 
+/// Converts a git commit time into a chrono date-time,
+/// keeping the time zone it was recorded in.
+fn to_date_time(time: git2::Time) -> BoxResult<DateTime<FixedOffset>> {
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60)
+        .ok_or("Invalid commit time zone offset")?;
+    Ok(DateTime::<FixedOffset>::from_utc(
+        NaiveDateTime::from_timestamp(time.seconds(), 0),
+        offset,
+    ))
+}
+
 pub struct Repo {
     repo: git2::Repository,
 }
 
+/// The parts of a commit we are interested in.
+#[derive(Debug, Clone)]
+pub struct Commit {
+    /// The full SHA, in lower-case hex
+    pub sha: String,
+    /// The full message, including the subject line
+    pub message: String,
+    /// Whether this commit has more than one parent
+    pub merge: bool,
+}
+
 impl TryFrom<Option<&str>> for Repo {
     // type Error = Box<&'static str>;
     type Error = git2::Error;
@@ -151,6 +169,285 @@ impl Repo {
         ) //)
     }
 
+    /// Returns the abbreviated SHA of the currently checked-out commit,
+    /// cut to [`crate::value_conversions::SHORT_SHA_LEN`] characters,
+    /// the same as when deriving it from the full SHA,
+    /// if any.
+    //
+    /// # Errors
+    ///
+    /// If some git-related magic goes south,
+    /// or there is no commit.
+    pub fn sha_short(&self) -> BoxResult<Option<String>> {
+        Ok(self.sha()?.map(|sha| {
+            sha.chars()
+                .take(crate::value_conversions::SHORT_SHA_LEN)
+                .collect()
+        }))
+    }
+
+    /// Returns whether there are uncommitted changes to tracked files,
+    /// which is what "git describe --dirty" considers.
+    //
+    /// # Errors
+    ///
+    /// If some git-related magic goes south.
+    pub fn is_dirty(&self) -> BoxResult<bool> {
+        let statuses = self.repo.statuses(Some(
+            git2::StatusOptions::new()
+                .include_untracked(false)
+                .include_ignored(false)
+                .exclude_submodules(true),
+        ))?;
+        Ok(!statuses.is_empty())
+    }
+
+    /// Maps commits to the names of the tags on them
+    /// that are accepted by `is_release_tag`.
+    /// Tags that do not point to a commit (but to a tree or blob)
+    /// are skipped.
+    fn release_tags(
+        &self,
+        is_release_tag: &dyn Fn(&str) -> bool,
+    ) -> BoxResult<HashMap<git2::Oid, Vec<String>>> {
+        let mut release_tags = HashMap::<git2::Oid, Vec<String>>::new();
+        for name in self.repo.tag_names(None)?.iter().flatten() {
+            if is_release_tag(name) {
+                let commit = match self
+                    .repo
+                    .revparse_single(&format!("refs/tags/{}", name))?
+                    .peel_to_commit()
+                {
+                    Ok(commit) => commit,
+                    Err(err) => {
+                        log::debug!("Skipping tag '{}', as it is not on a commit: {}", name, err);
+                        continue;
+                    }
+                };
+                release_tags
+                    .entry(commit.id())
+                    .or_default()
+                    .push(name.to_owned());
+            }
+        }
+        Ok(release_tags)
+    }
+
+    /// Returns a walk over all the commits reachable from HEAD,
+    /// newest first.
+    fn revwalk_head(&self) -> BoxResult<git2::Revwalk> {
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        revwalk.push_head()?;
+        Ok(revwalk)
+    }
+
+    /// Returns the names of the tags accepted by `is_release_tag`
+    /// on the last commit before HEAD that has any,
+    /// and all the commits since, including HEAD (newest first).
+    /// Tags on HEAD itself are not considered,
+    /// so HEAD may be the release these commits make up.
+    /// If there is no such tag, all commits are returned.
+    //
+    /// # Errors
+    ///
+    /// If some git-related magic goes south.
+    pub fn commits_since_release(
+        &self,
+        is_release_tag: &dyn Fn(&str) -> bool,
+    ) -> BoxResult<(Vec<String>, Vec<Commit>)> {
+        let mut release_tags = self.release_tags(is_release_tag)?;
+
+        let mut release = None;
+        for oid in self.revwalk_head()?.skip(1) {
+            let oid = oid?;
+            if release_tags.contains_key(&oid) {
+                release = Some(oid);
+                break;
+            }
+        }
+
+        // Walk again, excluding everything reachable from the release,
+        // which the first walk might have passed on merged branches.
+        let mut revwalk = self.revwalk_head()?;
+        if let Some(release) = release {
+            revwalk.hide(release)?;
+        }
+        let mut commits = vec![];
+        for oid in revwalk {
+            let commit = self.repo.find_commit(oid?)?;
+            commits.push(Commit {
+                sha: commit.id().to_string(),
+                message: String::from_utf8_lossy(commit.message_bytes()).into_owned(),
+                merge: commit.parent_count() > 1,
+            });
+        }
+        let tags = release
+            .and_then(|release| release_tags.remove(&release))
+            .unwrap_or_default();
+        Ok((tags, commits))
+    }
+
+    /// Returns the names of the tags accepted by `is_release_tag`
+    /// on the last commit before HEAD that has any.
+    /// Tags on HEAD itself are not considered.
+    //
+    /// # Errors
+    ///
+    /// If some git-related magic goes south.
+    pub fn previous_release_tags(
+        &self,
+        is_release_tag: &dyn Fn(&str) -> bool,
+    ) -> BoxResult<Vec<String>> {
+        let mut release_tags = self.release_tags(is_release_tag)?;
+        if release_tags.is_empty() {
+            return Ok(vec![]);
+        }
+        for oid in self.revwalk_head()?.skip(1) {
+            if let Some(tags) = release_tags.remove(&oid?) {
+                return Ok(tags);
+            }
+        }
+        Ok(vec![])
+    }
+
+    /// Returns whether this is a shallow clone,
+    /// in which only part of the history is available.
+    #[must_use]
+    pub fn is_shallow(&self) -> bool {
+        self.repo.is_shallow()
+    }
+
+    /// Returns the number of commits reachable from HEAD,
+    /// including HEAD itself.
+    //
+    /// # Errors
+    ///
+    /// If some git-related magic goes south.
+    pub fn commit_count(&self) -> BoxResult<usize> {
+        let mut count = 0;
+        for oid in self.revwalk_head()? {
+            oid?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Returns the number of unique authors of the commits reachable from HEAD,
+    /// identified by their e-mail address, after applying the repos `.mailmap`.
+    //
+    /// # Errors
+    ///
+    /// If some git-related magic goes south.
+    pub fn author_count(&self) -> BoxResult<usize> {
+        let mailmap = self.repo.mailmap()?;
+        let mut authors = HashSet::new();
+        for oid in self.revwalk_head()? {
+            let commit = self.repo.find_commit(oid?)?;
+            let author = commit.author_with_mailmap(&mailmap)?;
+            authors.insert(String::from_utf8_lossy(author.email_bytes()).to_lowercase());
+        }
+        Ok(authors.len())
+    }
+
+    /// Returns the commit-time of the earliest root commit
+    /// (one without parents) reachable from HEAD.
+    //
+    /// # Errors
+    ///
+    /// If some git-related magic goes south.
+    pub fn first_commit_date(&self) -> BoxResult<DateTime<FixedOffset>> {
+        let mut root_times = vec![];
+        for oid in self.revwalk_head()? {
+            let commit = self.repo.find_commit(oid?)?;
+            if commit.parent_count() == 0 {
+                root_times.push(commit.time());
+            }
+        }
+        let first = root_times
+            .into_iter()
+            .min_by_key(git2::Time::seconds)
+            .ok_or("No root commit found")?;
+        to_date_time(first)
+    }
+
+    /// Returns the SHA and commit-time of the last commit reachable from HEAD
+    /// that changed the file or directory at the repo-relative `path`,
+    /// or `None` if it was never committed.
+    /// Merge commits only count if they changed it
+    /// compared to all of their parents.
+    //
+    /// # Errors
+    ///
+    /// If some git-related magic goes south.
+    pub fn last_commit_touching(
+        &self,
+        path: &Path,
+    ) -> BoxResult<Option<(String, DateTime<FixedOffset>)>> {
+        fn entry_id(commit: &git2::Commit, path: &Path) -> BoxResult<Option<git2::Oid>> {
+            match commit.tree()?.get_path(path) {
+                Ok(entry) => Ok(Some(entry.id())),
+                Err(err) if err.code() == git2::ErrorCode::NotFound => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        }
+
+        for oid in self.revwalk_head()? {
+            let commit = self.repo.find_commit(oid?)?;
+            let id = entry_id(&commit, path)?;
+            let mut touched = id.is_some() || commit.parent_count() > 0;
+            for parent in commit.parents() {
+                if entry_id(&parent, path)? == id {
+                    touched = false;
+                    break;
+                }
+            }
+            if touched {
+                return Ok(Some((
+                    commit.id().to_string(),
+                    to_date_time(commit.time())?,
+                )));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the number of commits between the last tag
+    /// and the currently checked-out commit,
+    /// or `None` if there is no tag in the history.
+    //
+    /// # Errors
+    ///
+    /// If some git-related magic goes south.
+    pub fn commits_since_tag(&self) -> BoxResult<Option<u32>> {
+        lazy_static! {
+            static ref R_DESCRIBE_DISTANCE: Regex =
+                Regex::new(r"-(?P<distance>[0-9]+)-g[0-9a-f]+$").unwrap();
+        }
+        let describe = match self
+            .repo
+            .describe(git2::DescribeOptions::new().describe_tags())
+        {
+            Ok(describe) => describe,
+            // There is no tag to describe the commit with
+            // (libgit2 reports this as a generic error of the describe class)
+            Err(err)
+                if err.code() == git2::ErrorCode::NotFound
+                    || err.class() == git2::ErrorClass::Describe =>
+            {
+                return Ok(None)
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let long = describe.format(Some(
+            git2::DescribeFormatOptions::new().always_use_long_format(true),
+        ))?;
+        Ok(match R_DESCRIBE_DISTANCE.captures(&long) {
+            Some(caps) => Some(caps["distance"].parse()?),
+            None => None,
+        })
+    }
+
     /// Returns the local name of the currently checked-out branch,
     /// if any.
     //
@@ -293,15 +590,9 @@ impl Repo {
     /// # Errors
     ///
     /// If some git-related magic goes south.
-    pub fn commit_date(&self, date_format: &str) -> BoxResult<String> {
+    pub fn commit_date(&self) -> BoxResult<DateTime<FixedOffset>> {
         let head = self.repo.head()?;
-        let commit_time_git2 = head.peel_to_commit()?.time();
-        let commit_time_chrono = DateTime::<Utc>::from_utc(
-            NaiveDateTime::from_timestamp(commit_time_git2.seconds(), 0),
-            Utc,
-        );
-        Ok(commit_time_chrono.format(date_format).to_string())
-        // date.fromtimestamp(repo.head.ref.commit.committed_date).strftime(date_format)
+        to_date_time(head.peel_to_commit()?.time())
     }
 }
 
@@ -346,3 +637,234 @@ mod tests {
     }
 }
 */
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Creates a fresh, empty repository in a temporary directory.
+    pub(crate) fn test_repo(name: &str) -> Repo {
+        let path =
+            std::env::temp_dir().join(format!("projvar-test-git-{}-{}", name, std::process::id()));
+        let _ignored = std::fs::remove_dir_all(&path);
+        Repo {
+            repo: Repository::init(&path).unwrap(),
+        }
+    }
+
+    /// Writes `content` to `file` and commits it on top of HEAD,
+    /// with `seconds` (since the epoch) as author- and commit-time.
+    pub(crate) fn commit(
+        repo: &Repo,
+        file: &str,
+        content: &str,
+        message: &str,
+        seconds: i64,
+    ) -> git2::Oid {
+        commit_by(repo, "jane@example.org", file, content, message, seconds)
+    }
+
+    /// Like [`commit`], but authored by `email`.
+    pub(crate) fn commit_by(
+        repo: &Repo,
+        email: &str,
+        file: &str,
+        content: &str,
+        message: &str,
+        seconds: i64,
+    ) -> git2::Oid {
+        let repo = &repo.repo;
+        let path = repo.workdir().unwrap().join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(file)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature =
+            git2::Signature::new("Jane Doe", email, &git2::Time::new(seconds, 0)).unwrap();
+        let parents: Vec<git2::Commit> = repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    /// Returns the root directory of the working tree.
+    pub(crate) fn workdir(repo: &Repo) -> PathBuf {
+        repo.repo.workdir().unwrap().to_owned()
+    }
+
+    /// Puts a lightweight tag on HEAD.
+    pub(crate) fn tag(repo: &Repo, name: &str) {
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        repo.repo
+            .tag_lightweight(name, head.as_object(), false)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_sha_short() {
+        let repo = test_repo("sha-short");
+        let oid = commit(
+            &repo,
+            "README.md",
+            "# Test\n",
+            "Initial commit",
+            1_600_000_000,
+        );
+        let sha_short = repo.sha_short().unwrap().unwrap();
+        assert_eq!(sha_short.len(), crate::value_conversions::SHORT_SHA_LEN);
+        assert!(oid.to_string().starts_with(&sha_short));
+        assert_eq!(repo.sha().unwrap(), Some(oid.to_string()));
+    }
+
+    #[test]
+    fn test_is_dirty() {
+        let repo = test_repo("is-dirty");
+        commit(
+            &repo,
+            "README.md",
+            "# Test\n",
+            "Initial commit",
+            1_600_000_000,
+        );
+        assert!(!repo.is_dirty().unwrap());
+        let root = repo.repo.workdir().unwrap().to_owned();
+        std::fs::write(root.join("untracked.txt"), "new\n").unwrap();
+        assert!(!repo.is_dirty().unwrap());
+        std::fs::write(root.join("README.md"), "# Changed\n").unwrap();
+        assert!(repo.is_dirty().unwrap());
+    }
+
+    #[test]
+    fn test_commits_since_tag() {
+        let repo = test_repo("commits-since-tag");
+        commit(&repo, "a.txt", "a\n", "First", 1_600_000_000);
+        assert_eq!(repo.commits_since_tag().unwrap(), None);
+        tag(&repo, "v1.0.0");
+        assert_eq!(repo.commits_since_tag().unwrap(), Some(0));
+        commit(&repo, "b.txt", "b\n", "Second", 1_600_000_100);
+        commit(&repo, "c.txt", "c\n", "Third", 1_600_000_200);
+        assert_eq!(repo.commits_since_tag().unwrap(), Some(2));
+    }
+
+    #[test]
+    fn test_release_tags_skips_non_commits() {
+        let repo = test_repo("release-tags-non-commits");
+        let oid = commit(&repo, "a.txt", "a\n", "First", 1_600_000_000);
+        tag(&repo, "v1.0.0");
+        let tree = repo.repo.find_commit(oid).unwrap().tree().unwrap();
+        repo.repo
+            .tag_lightweight("v2.0.0", tree.as_object(), false)
+            .unwrap();
+        let release_tags = repo.release_tags(&|_tag| true).unwrap();
+        assert_eq!(release_tags.len(), 1);
+        assert_eq!(release_tags[&oid], vec!["v1.0.0".to_owned()]);
+    }
+
+    #[test]
+    fn test_commits_since_release() {
+        let is_release_tag = |tag: &str| tag.starts_with('v');
+        let repo = test_repo("commits-since-release");
+        let first = commit(&repo, "a.txt", "a\n", "First", 1_600_000_000);
+        let (tags, commits) = repo.commits_since_release(&is_release_tag).unwrap();
+        assert!(tags.is_empty());
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].sha, first.to_string());
+
+        tag(&repo, "v1.0.0");
+        let second = commit(&repo, "b.txt", "b\n", "feat: Second", 1_600_000_100);
+        tag(&repo, "not-a-release");
+        let third = commit(&repo, "c.txt", "c\n", "fix: Third", 1_600_000_200);
+        let expected = vec![third.to_string(), second.to_string()];
+        let (tags, commits) = repo.commits_since_release(&is_release_tag).unwrap();
+        assert_eq!(tags, vec!["v1.0.0".to_owned()]);
+        assert_eq!(
+            commits
+                .iter()
+                .map(|commit| commit.sha.clone())
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(commits[0].message, "fix: Third");
+        assert!(!commits[0].merge);
+
+        // A release tag on HEAD does not hide HEAD
+        tag(&repo, "v1.1.0");
+        let (tags, commits) = repo.commits_since_release(&is_release_tag).unwrap();
+        assert_eq!(tags, vec!["v1.0.0".to_owned()]);
+        assert_eq!(
+            commits
+                .iter()
+                .map(|commit| commit.sha.clone())
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            repo.previous_release_tags(&is_release_tag).unwrap(),
+            vec!["v1.0.0".to_owned()]
+        );
+    }
+
+    #[test]
+    fn test_history() {
+        let repo = test_repo("history");
+        commit(&repo, "a.txt", "a\n", "First", 1_600_000_000);
+        commit_by(
+            &repo,
+            "john@example.org",
+            "b.txt",
+            "b\n",
+            "Second",
+            1_600_000_100,
+        );
+        commit_by(
+            &repo,
+            "JANE@example.org",
+            "c.txt",
+            "c\n",
+            "Third",
+            1_600_000_200,
+        );
+        assert_eq!(repo.commit_count().unwrap(), 3);
+        assert_eq!(repo.author_count().unwrap(), 2);
+        std::fs::write(
+            workdir(&repo).join(".mailmap"),
+            "Jane Doe <jane@example.org> <john@example.org>\n",
+        )
+        .unwrap();
+        assert_eq!(repo.author_count().unwrap(), 1);
+        assert_eq!(repo.first_commit_date().unwrap().timestamp(), 1_600_000_000);
+        assert!(!repo.is_shallow());
+    }
+
+    #[test]
+    fn test_last_commit_touching() {
+        let repo = test_repo("last-commit-touching");
+        commit(&repo, "a.txt", "a\n", "Add a", 1_600_000_000);
+        let add_b = commit(&repo, "dir/b.txt", "b\n", "Add b", 1_600_000_100);
+        let change_a = commit(&repo, "a.txt", "a2\n", "Change a", 1_600_000_200);
+        commit(&repo, "c.txt", "c\n", "Add c", 1_600_000_300);
+        let last = |path: &str| {
+            repo.last_commit_touching(Path::new(path))
+                .unwrap()
+                .map(|(sha, date)| (sha, date.timestamp()))
+        };
+        assert_eq!(last("a.txt"), Some((change_a.to_string(), 1_600_000_200)));
+        assert_eq!(last("dir/b.txt"), Some((add_b.to_string(), 1_600_000_100)));
+        assert_eq!(last("dir"), Some((add_b.to_string(), 1_600_000_100)));
+        assert_eq!(last("missing.txt"), None);
+    }
+}
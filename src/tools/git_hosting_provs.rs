@@ -27,6 +27,11 @@ pub enum PublicSite {
     RepoOrCz,
     /// <https://sourceforge.net> - hosts only OS, software is OSS: Allura
     SourceForgeNet,
+    /// <https://dev.azure.com> - commercial, free OS hosting, software is proprietary: Azure DevOps
+    DevAzureCom,
+    /// <https://aws.amazon.com/codecommit> - commercial, software is proprietary: AWS CodeCommit;
+    /// one host per AWS region
+    AwsCodeCommit,
     /// NOTE: The rust masters said, this is better then Option<None>!
     Unknown,
 }
@@ -40,6 +45,8 @@ impl PublicSite {
             match main_domain.as_str() {
                 constants::DS_GIT_HUB_IO_SUFIX => Self::GitHubCom,
                 constants::DS_GIT_LAB_IO_SUFIX => Self::GitLabCom,
+                constants::DS_CODE_BERG_PAGE_SUFIX => Self::CodeBergOrg,
+                constants::DS_SOURCE_FORGE_IO_SUFIX => Self::SourceForgeNet,
                 _ => Self::Unknown, // TODO implement the rest, where applicable (BitBucket does not have a hosting site, for example)
             }
         } else {
@@ -47,6 +54,18 @@ impl PublicSite {
         }
     }
 
+    /// Extracts the AWS region from an AWS CodeCommit git host
+    /// ("git-codecommit.<region>.amazonaws.com")
+    /// or an AWS console host ("<region>.console.aws.amazon.com").
+    #[must_use]
+    pub fn aws_code_commit_region(domain: &str) -> Option<&str> {
+        domain
+            .strip_prefix(constants::DP_AWS_CODE_COMMIT_GIT)
+            .and_then(|rest| rest.strip_suffix(constants::DS_AWS_CODE_COMMIT_GIT_SUFIX))
+            .or_else(|| domain.strip_suffix(constants::DS_AWS_CONSOLE_SUFIX))
+            .filter(|region| !region.is_empty() && !region.contains('.'))
+    }
+
     #[must_use]
     pub fn from_hosting_domain_option(host: Option<&Host<&str>>) -> Self {
         host.map_or(Self::Unknown, Self::from_hosting_domain)
@@ -68,7 +87,15 @@ impl From<Host<&str>> for PublicSite {
             Host::Domain(constants::D_GIT_SOURCE_HUT) => Self::SourceHut,
             Host::Domain(constants::D_REPO_OR_CZ) => Self::RepoOrCz,
             Host::Domain(constants::D_CODE_BERG_ORG) => Self::CodeBergOrg,
-            Host::Domain(constants::D_SOURCE_FORGE_NET) => Self::SourceForgeNet,
+            Host::Domain(constants::D_SOURCE_FORGE_NET | constants::D_SOURCE_FORGE_GIT) => {
+                Self::SourceForgeNet
+            }
+            Host::Domain(constants::D_DEV_AZURE_COM | constants::D_DEV_AZURE_COM_SSH) => {
+                Self::DevAzureCom
+            }
+            Host::Domain(domain) if Self::aws_code_commit_region(domain).is_some() => {
+                Self::AwsCodeCommit
+            }
             Host::Domain(_) | Host::Ipv4(_) | Host::Ipv6(_) => Self::Unknown,
         }
     }
@@ -101,6 +128,10 @@ pub enum HostingType {
     RocketGit,
     /// <https://allura.apache.org> - OSS
     Allura,
+    /// <https://azure.microsoft.com/products/devops/repos> - proprietary
+    AzureDevOps,
+    /// <https://aws.amazon.com/codecommit> - proprietary
+    CodeCommit,
     /// NOTE: The rust masters said, this is better then Option<None>!
     Unknown,
 }
@@ -109,7 +140,11 @@ impl HostingType {
     #[must_use]
     pub fn is_oss(self) -> bool {
         match self {
-            HostingType::GitHub | HostingType::BitBucket | HostingType::Unknown => false,
+            HostingType::GitHub
+            | HostingType::BitBucket
+            | HostingType::AzureDevOps
+            | HostingType::CodeCommit
+            | HostingType::Unknown => false,
             HostingType::GitLab
             | HostingType::SourceHut
             | HostingType::Gitea
@@ -136,6 +171,8 @@ impl From<PublicSite> for HostingType {
             PublicSite::RepoOrCz => Self::Girocco,
             PublicSite::CodeBergOrg => Self::Gitea,
             PublicSite::SourceForgeNet => Self::Allura,
+            PublicSite::DevAzureCom => Self::AzureDevOps,
+            PublicSite::AwsCodeCommit => Self::CodeCommit,
             PublicSite::Unknown => Self::Unknown,
         }
     }
@@ -4,3 +4,4 @@
 
 pub mod git;
 pub mod git_hosting_provs;
+pub mod secrets;
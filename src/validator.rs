@@ -2,16 +2,22 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
+use crate::custom_keys::{self, Builtin, Check};
 use crate::license;
+use crate::normalize;
 use crate::tools::git;
 use crate::tools::git_hosting_provs::HostingType;
+use crate::value::Value;
+use crate::value_conversions::SHORT_SHA_LEN;
 use crate::var::{Confidence, Key};
+use crate::version::{self, Bump, Scheme};
 use crate::{constants, environment::Environment};
-use chrono::{DateTime, NaiveDateTime};
 use lazy_static::lazy_static;
 use regex::Regex;
+use std::str::FromStr;
+use strum::VariantNames;
 use thiserror::Error;
-use url::Url;
+use url::{Position, Url};
 
 pub type Result = std::result::Result<Validity, Error>;
 pub type Validator = fn(&mut Environment, &str) -> Result;
@@ -131,26 +137,74 @@ fn missing(environment: &mut Environment, key: Key) -> Result {
     }
 }
 
+lazy_static! {
+    static ref R_GIT_SHA: Regex = Regex::new(r"^g?[0-9a-f]{7,40}$").unwrap();
+    static ref R_UNKNOWN_VERS: Regex = Regex::new(r"^($|#|//)").unwrap();
+}
+
 fn validate_version(environment: &mut Environment, value: &str) -> Result {
+    match environment.settings.version_scheme {
+        Scheme::SemVer => validate_version_semver(environment, value),
+        scheme => validate_version_scheme(environment, value, scheme),
+    }
+}
+
+/// Validates a version in any scheme but [`Scheme::SemVer`],
+/// which gets a more fine-grained treatment
+/// (see [`validate_version_semver`]).
+fn validate_version_scheme(environment: &mut Environment, value: &str, scheme: Scheme) -> Result {
+    if R_UNKNOWN_VERS.is_match(value) {
+        return missing(environment, Key::Version);
+    }
+    if git::is_git_dirty_version(value) {
+        log::warn!(
+            "Dirty project version '{}'; you have uncommitted changes in your project",
+            value
+        );
+    }
+    let scheme_name: &str = scheme.into();
+    match version::Describe::parse(value) {
+        Some(describe) if scheme.is_valid(describe.base) => {
+            if describe.is_exact() {
+                Ok(Validity::Low { msg: "This is a release version, which indicates either that we are on a release commit, or that it is imprecise, and actually a left-over from the previous release.".to_owned() })
+            } else {
+                Ok(Validity::High {
+                    msg: Some(format!(
+                        "A git version starting with a {} version",
+                        scheme_name
+                    )),
+                })
+            }
+        }
+        Some(describe) if R_GIT_SHA.is_match(describe.base) => Ok(Validity::Suboptimal {
+            msg:
+                "This version (a raw git SHA) is technically ok, but not a release-version, and not human-readable"
+                    .to_owned(),
+            source: None,
+        }),
+        _ => Err(Error::BadValue {
+            msg: format!("Not a valid version in the {} scheme", scheme_name),
+            value: value.to_owned(),
+        }),
+    }
+}
+
+fn validate_version_semver(environment: &mut Environment, value: &str) -> Result {
     lazy_static! {
-        // The official SemVer regex as of September 2021, taken from
-        // https://semver.org/#is-there-a-suggested-regular-expression-regex-to-check-a-semver-string
-        // TODO PRIO Think of what to do if we have a "v" prefix, as in "v1.2.3" -> best: remove it, but where.. a kind of pre-validator function?
-        // TODO PRIO Use this create for semver checking: https://github.com/dtolnay/semver (does not need to be with a Regex!)
-        static ref R_SEM_VERS_RELEASE: Regex = Regex::new(r"^(?P<major>0|[1-9]\d*)\.(?P<minor>0|[1-9]\d*)\.(?P<patch>0|[1-9]\d*)$").unwrap();
-        static ref R_SEM_VERS: Regex = Regex::new(r"^(?P<major>0|[1-9]\d*)\.(?P<minor>0|[1-9]\d*)\.(?P<patch>0|[1-9]\d*)(?:-(?P<prerelease>(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*)(?:\.(?:0|[1-9]\d*|\d*[a-zA-Z-][0-9a-zA-Z-]*))*))?(?:\+(?P<buildmetadata>[0-9a-zA-Z-]+(?:\.[0-9a-zA-Z-]+)*))?$").unwrap();
         static ref R_GIT_VERS: Regex = Regex::new(r"^((g[0-9a-f]{7})|((0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)))(-(0|[1-9]\d*)-(g[0-9a-f]{7}))?((-dirty(-broken)?)|-broken(-dirty)?)?$").unwrap();
-        static ref R_GIT_SHA: Regex = Regex::new(r"^g?[0-9a-f]{7,40}$").unwrap();
         static ref R_GIT_SHA_PREFIX: Regex = Regex::new(r"^g[0-9a-f]{7}").unwrap();
-        static ref R_UNKNOWN_VERS: Regex = Regex::new(r"^($|#|//)").unwrap();
     }
+    // The prefix is only still there if the user chose to keep it
+    // (see [`crate::settings::Settings::keep_version_prefix`])
+    let value = normalize::strip_version_prefix(value);
+    let sem_vers = semver::Version::parse(value).ok();
     if git::is_git_dirty_version(value) {
         log::warn!(
             "Dirty project version '{}'; you have uncommitted changes in your project",
             value
         );
     }
-    if R_SEM_VERS_RELEASE.is_match(value) {
+    if matches!(&sem_vers, Some(version) if version.pre.is_empty() && version.build.is_empty()) {
         Ok(Validity::Low { msg: "This is a release version, which indicates either that we are on a release commit, or that it is imprecise, and actually a left-over from the previous release.".to_owned() })
     } else if git::is_git_dirty_version(value) {
         log::warn!(
@@ -202,7 +256,7 @@ fn validate_version(environment: &mut Environment, value: &str) -> Result {
                 })
             },
         }
-    } else if R_SEM_VERS.is_match(value) {
+    } else if sem_vers.is_some() {
         // This version is technically good,
         // but not a release-version
         // (i.e., does not look so nice).
@@ -254,7 +308,9 @@ fn validate_licenses(environment: &mut Environment, value: &str) -> Result {
     if value.is_empty() {
         missing(environment, Key::Licenses)
     } else {
-        // TODO PRIO Implement SPDX expressions detection, not just (as is now) single identifiers; see: TODO
+        // Either a list separated by ',', as supplied by sources,
+        // or a single expression, as rendered from a parsed list
+        // (see [`Value::Licenses`])
         for license in value.split(',') {
             let license = license.trim();
             let res = validate_license(environment, license);
@@ -270,17 +326,52 @@ fn validate_licenses(environment: &mut Environment, value: &str) -> Result {
             }
         }
         Ok(Validity::High {
-            msg: Some(
-                "Consists of a list of SPDX license identifiers, separated by ','".to_owned(),
-            ),
+            msg: Some("Consists of SPDX license expressions".to_owned()),
         })
     }
 }
 
 fn check_public_url(
+    environment: &mut Environment,
+    value: &str,
+    allow_ssh: bool,
+) -> std::result::Result<Url, Error> {
+    check_public_url_opts(environment, value, allow_ssh, false)
+}
+
+/// Like [`check_public_url`], but allows query arguments for URLs
+/// of hosting types that need them to address versioned content.
+/// This is the case for Girocco,
+/// whose gitweb UI specifies project, action and version in the query,
+/// and for Azure DevOps, which specifies version and path in the query.
+fn check_public_versioned_url(
+    environment: &mut Environment,
+    value: &str,
+) -> std::result::Result<(Url, HostingType), Error> {
+    let url = check_public_url_opts(environment, value, false, true)?;
+    let hosting_type = eval_hosting_type(environment, &url);
+    if !matches!(
+        hosting_type,
+        HostingType::Girocco | HostingType::AzureDevOps
+    ) {
+        if let Some(query) = url.query() {
+            return Err(Error::AlmostUsableValue {
+                msg: format!(
+                    "Should be a simple URL, but uses query arguments: {}",
+                    query
+                ),
+                value: value.to_owned(),
+            });
+        }
+    }
+    Ok((url, hosting_type))
+}
+
+fn check_public_url_opts(
     _environment: &mut Environment,
     value: &str,
     allow_ssh: bool,
+    allow_query: bool,
 ) -> std::result::Result<Url, Error> {
     match Url::parse(value) {
         Err(_err) => Err(Error::BadValue {
@@ -310,7 +401,7 @@ fn check_public_url(
                     msg: "Should be anonymous access, but contains a password".to_owned(),
                     value: value.to_owned(),
                 })
-            } else if let Some(query) = url.query() {
+            } else if let (false, Some(query)) = (allow_query, url.query()) {
                 Err(Error::AlmostUsableValue {
                     msg: format!(
                         "Should be a simple URL, but uses query arguments: {}",
@@ -353,15 +444,19 @@ fn eval_hosting_type_from_hosting_suffix(environment: &mut Environment, url: &Ur
     environment.settings.hosting_type_from_hosting_suffix(url)
 }
 
+/// Checks the path part of the URL against the given regex.
+/// If the URL has a query part, it is included,
+/// as in `"/path/to/file?query"`.
 fn check_url_path(value: &str, url_desc: &str, url: &Url, path_reg: Option<&Regex>) -> Result {
     if let (Some(path_reg), Some(host)) = (path_reg, url.host().as_ref()) {
-        if path_reg.is_match(url.path()) {
+        let path = &url[Position::BeforePath..Position::AfterQuery];
+        if path_reg.is_match(path) {
             Ok(Validity::High {
                 msg: Some(format!(
                     r#"For {}, the path part of the {} URL ("{}") matches regex "{}""#,
                     host,
                     url_desc,
-                    url.path(),
+                    path,
                     path_reg.as_str()
                 )),
             })
@@ -371,7 +466,7 @@ fn check_url_path(value: &str, url_desc: &str, url: &Url, path_reg: Option<&Rege
                     r#"For {}, this path part of the {} URL is invalid: "{}"; it should match "{}""#,
                     host,
                     url_desc,
-                    url.path(),
+                    path,
                     path_reg.as_str()
                 ),
                 value: value.to_owned(),
@@ -419,6 +514,15 @@ fn validate_repo_web_url(environment: &mut Environment, value: &str) -> Result {
         static ref R_GIT_LAB_PATH: Regex =
             Regex::new(r"^/(?P<user>[^/]+)/((?P<structure>[^/]+)/)*(?P<repo>[^/]+)/?$").unwrap();
         static ref R_BIT_BUCKET_PATH: Regex = (*R_GIT_HUB_PATH).clone();
+        static ref R_GITEA_PATH: Regex = (*R_GIT_HUB_PATH).clone();
+        static ref R_GIROCCO_PATH: Regex = Regex::new(r"^/(?P<repo>.+)\.git$").unwrap();
+        static ref R_ALLURA_PATH: Regex =
+            Regex::new(r"^/p/(?P<repo>[^/]+)/(?P<mount>[^/]+)/?$").unwrap();
+        static ref R_AZURE_DEV_OPS_PATH: Regex =
+            Regex::new(r"^/(?P<org>[^/]+)/(?P<project>[^/]+)/_git/(?P<repo>[^/]+)/?$").unwrap();
+        static ref R_CODE_COMMIT_PATH: Regex =
+            Regex::new(r"^/codesuite/codecommit/repositories/(?P<repo>[^/]+)(/browse)?/?$")
+                .unwrap();
     }
 
     let url = check_public_url(environment, value, false)?;
@@ -427,6 +531,11 @@ fn validate_repo_web_url(environment: &mut Environment, value: &str) -> Result {
         HostingType::GitHub => Some(&R_GIT_HUB_PATH),
         HostingType::GitLab => Some(&R_GIT_LAB_PATH),
         HostingType::BitBucket => Some(&R_BIT_BUCKET_PATH),
+        HostingType::Gitea => Some(&R_GITEA_PATH),
+        HostingType::Girocco => Some(&R_GIROCCO_PATH),
+        HostingType::Allura => Some(&R_ALLURA_PATH),
+        HostingType::AzureDevOps => Some(&R_AZURE_DEV_OPS_PATH),
+        HostingType::CodeCommit => Some(&R_CODE_COMMIT_PATH),
         _ => None, // TODO Implement the others
     };
     check_url_path(value, "versioned web", &url, host_reg)
@@ -438,9 +547,21 @@ lazy_static! {
     static ref R_GIT_LAB_CLONE_PATH: Regex =
         Regex::new(r"^/(?P<user>[^/]+)/((?P<structure>[^/]+)/)*(?P<repo>[^/]+)\.git$").unwrap();
     static ref R_BIT_BUCKET_CLONE_PATH: Regex = (*R_GIT_HUB_CLONE_PATH).clone();
+    static ref R_GITEA_CLONE_PATH: Regex = (*R_GIT_HUB_CLONE_PATH).clone();
+    static ref R_GIROCCO_CLONE_PATH: Regex = Regex::new(r"^/(?P<repo>.+)\.git$").unwrap();
+    static ref R_ALLURA_CLONE_PATH: Regex =
+        Regex::new(r"^/p/(?P<repo>[^/]+)/(?P<mount>[^/]+)$").unwrap();
+    static ref R_AZURE_DEV_OPS_CLONE_PATH: Regex =
+        Regex::new(r"^/(?P<org>[^/]+)/(?P<project>[^/]+)/_git/(?P<repo>[^/]+)$").unwrap();
+    static ref R_CODE_COMMIT_CLONE_PATH: Regex =
+        Regex::new(r"^/v1/repos/(?P<repo>[^/]+)$").unwrap();
 }
 
 // * https://git@bitbucket.org/Aouatef/master_arbeit.git
+// * https://repo.or.cz/girocco.git
+// * https://git.code.sf.net/p/sevenzip/code
+// * https://dev.azure.com/dnceng-public/public/_git/runtime
+// * https://git-codecommit.eu-west-1.amazonaws.com/v1/repos/projvar
 fn validate_repo_clone_url(environment: &mut Environment, value: &str) -> Result {
     let url = check_public_url(environment, value, false)?;
     let hosting_type = eval_hosting_type(environment, &url);
@@ -448,6 +569,11 @@ fn validate_repo_clone_url(environment: &mut Environment, value: &str) -> Result
         HostingType::GitHub => Some(&R_GIT_HUB_CLONE_PATH),
         HostingType::GitLab => Some(&R_GIT_LAB_CLONE_PATH),
         HostingType::BitBucket => Some(&R_BIT_BUCKET_CLONE_PATH),
+        HostingType::Gitea => Some(&R_GITEA_CLONE_PATH),
+        HostingType::Girocco => Some(&R_GIROCCO_CLONE_PATH),
+        HostingType::Allura => Some(&R_ALLURA_CLONE_PATH),
+        HostingType::AzureDevOps => Some(&R_AZURE_DEV_OPS_CLONE_PATH),
+        HostingType::CodeCommit => Some(&R_CODE_COMMIT_CLONE_PATH),
         _ => None, // TODO Implement the others
     };
     check_url_path(value, "repo clone", &url, host_reg)
@@ -455,10 +581,16 @@ fn validate_repo_clone_url(environment: &mut Environment, value: &str) -> Result
 
 // * git@bitbucket.org:Aouatef/master_arbeit.git
 // * ssh://bitbucket.org/Aouatef/master_arbeit.git
+// * ssh://repo.or.cz/girocco.git
+// * ssh://git.code.sf.net/p/sevenzip/code
+// * git@ssh.dev.azure.com:v3/dnceng-public/public/runtime
+// * ssh://git-codecommit.eu-west-1.amazonaws.com/v1/repos/projvar
 fn validate_repo_clone_url_ssh(environment: &mut Environment, value: &str) -> Result {
     lazy_static! {
         // NOTE We only accept the user "git", as it stands for anonymous access
         static ref R_SSH_CLONE_URL: Regex = Regex::new(r"^(?P<user>git@)?(?P<host>[^/:]+)((:|/)(?P<path>.+))?$").unwrap();
+        static ref R_AZURE_DEV_OPS_SSH_CLONE_PATH: Regex =
+            Regex::new(r"^/v3/(?P<org>[^/]+)/(?P<project>[^/]+)/(?P<repo>[^/]+)$").unwrap();
     }
 
     let url = match check_public_url(environment, value, true) {
@@ -487,6 +619,11 @@ fn validate_repo_clone_url_ssh(environment: &mut Environment, value: &str) -> Re
         HostingType::GitHub => Some(&R_GIT_HUB_CLONE_PATH),
         HostingType::GitLab => Some(&R_GIT_LAB_CLONE_PATH),
         HostingType::BitBucket => Some(&R_BIT_BUCKET_CLONE_PATH),
+        HostingType::Gitea => Some(&R_GITEA_CLONE_PATH),
+        HostingType::Girocco => Some(&R_GIROCCO_CLONE_PATH),
+        HostingType::Allura => Some(&R_ALLURA_CLONE_PATH),
+        HostingType::AzureDevOps => Some(&R_AZURE_DEV_OPS_SSH_CLONE_PATH),
+        HostingType::CodeCommit => Some(&R_CODE_COMMIT_CLONE_PATH),
         _ => None, // TODO Implement the others
     };
     check_url_path(value, "repo clone ssh", &url, host_reg)
@@ -497,6 +634,7 @@ fn validate_repo_clone_url_ssh(environment: &mut Environment, value: &str) -> Re
 // * https://gitlab.com/OSEGermany/osh-tool/-/raw/master/data/source_extension_formats.csv
 // * https://gitlab.com/OSEGermany/osh-tool/raw/master/data/source_extension_formats.csv
 // * https://bitbucket.org/Aouatef/master_arbeit/raw/ae4a42a850b359a23da2483eb8f867f21c5382d4/procExData/import.sh
+// * https://codeberg.org/Codeberg/build-deploy-gitea/raw/commit/9c9cf7b1b9f0d2c8e5a86fd0ff1fb0fc1cb4ff6b/README.md
 fn validate_repo_raw_versioned_prefix_url(environment: &mut Environment, value: &str) -> Result {
     lazy_static! {
         static ref R_GIT_HUB_PATH: Regex =
@@ -506,14 +644,25 @@ fn validate_repo_raw_versioned_prefix_url(environment: &mut Environment, value:
                 .unwrap();
         static ref R_BIT_BUCKET_PATH: Regex =
             Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/raw$").unwrap();
+        static ref R_GITEA_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/raw/commit$").unwrap();
+        static ref R_GIROCCO_PATH: Regex =
+            Regex::new(r"^/\?p=(?P<repo>.+\.git);a=blob_plain;hb=$").unwrap();
+        static ref R_ALLURA_PATH: Regex =
+            Regex::new(r"^/p/(?P<repo>[^/]+)/(?P<mount>[^/]+)/ci$").unwrap();
+        static ref R_AZURE_DEV_OPS_PATH: Regex =
+            Regex::new(r"^/(?P<org>[^/]+)/(?P<project>[^/]+)/_apis/git/repositories/(?P<repo>[^/]+)/items\?versionDescriptor\.versionType=commit&versionDescriptor\.version=$").unwrap();
     }
 
-    let url = check_public_url(environment, value, false)?;
-    let hosting_type = eval_hosting_type(environment, &url);
+    let (url, hosting_type) = check_public_versioned_url(environment, value)?;
     let host_reg: Option<&Regex> = match hosting_type {
         HostingType::GitHub => Some(&R_GIT_HUB_PATH),
         HostingType::GitLab => Some(&R_GIT_LAB_PATH),
         HostingType::BitBucket => Some(&R_BIT_BUCKET_PATH),
+        HostingType::Gitea => Some(&R_GITEA_PATH),
+        HostingType::Girocco => Some(&R_GIROCCO_PATH),
+        HostingType::Allura => Some(&R_ALLURA_PATH),
+        HostingType::AzureDevOps => Some(&R_AZURE_DEV_OPS_PATH),
         _ => None, // TODO Implement the others
     };
     check_url_path(value, "raw versioned prefix", &url, host_reg)
@@ -529,14 +678,29 @@ fn validate_repo_versioned_file_prefix_url(environment: &mut Environment, value:
                 .unwrap();
         static ref R_BIT_BUCKET_PATH: Regex =
             Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/src$").unwrap();
+        static ref R_GITEA_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/src/commit$").unwrap();
+        static ref R_GIROCCO_PATH: Regex =
+            Regex::new(r"^/\?p=(?P<repo>.+\.git);a=blob;hb=$").unwrap();
+        static ref R_ALLURA_PATH: Regex =
+            Regex::new(r"^/p/(?P<repo>[^/]+)/(?P<mount>[^/]+)/ci$").unwrap();
+        static ref R_AZURE_DEV_OPS_PATH: Regex =
+            Regex::new(r"^/(?P<org>[^/]+)/(?P<project>[^/]+)/_git/(?P<repo>[^/]+)\?version=GC$")
+                .unwrap();
+        static ref R_CODE_COMMIT_PATH: Regex =
+            Regex::new(r"^/codesuite/codecommit/repositories/(?P<repo>[^/]+)/browse$").unwrap();
     }
 
-    let url = check_public_url(environment, value, false)?;
-    let hosting_type = eval_hosting_type(environment, &url);
+    let (url, hosting_type) = check_public_versioned_url(environment, value)?;
     let host_reg: Option<&Regex> = match hosting_type {
         HostingType::GitHub => Some(&R_GIT_HUB_PATH),
         HostingType::GitLab => Some(&R_GIT_LAB_PATH),
         HostingType::BitBucket => Some(&R_BIT_BUCKET_PATH),
+        HostingType::Gitea => Some(&R_GITEA_PATH),
+        HostingType::Girocco => Some(&R_GIROCCO_PATH),
+        HostingType::Allura => Some(&R_ALLURA_PATH),
+        HostingType::AzureDevOps => Some(&R_AZURE_DEV_OPS_PATH),
+        HostingType::CodeCommit => Some(&R_CODE_COMMIT_PATH),
         _ => None, // TODO Implement the others
     };
     check_url_path(value, "versioned file prefix", &url, host_reg)
@@ -552,14 +716,29 @@ fn validate_repo_versioned_dir_prefix_url(environment: &mut Environment, value:
                 .unwrap();
         static ref R_BIT_BUCKET_PATH: Regex =
             Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/src$").unwrap();
+        static ref R_GITEA_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/src/commit$").unwrap();
+        static ref R_GIROCCO_PATH: Regex =
+            Regex::new(r"^/\?p=(?P<repo>.+\.git);a=tree;hb=$").unwrap();
+        static ref R_ALLURA_PATH: Regex =
+            Regex::new(r"^/p/(?P<repo>[^/]+)/(?P<mount>[^/]+)/ci$").unwrap();
+        static ref R_AZURE_DEV_OPS_PATH: Regex =
+            Regex::new(r"^/(?P<org>[^/]+)/(?P<project>[^/]+)/_git/(?P<repo>[^/]+)\?version=GC$")
+                .unwrap();
+        static ref R_CODE_COMMIT_PATH: Regex =
+            Regex::new(r"^/codesuite/codecommit/repositories/(?P<repo>[^/]+)/browse$").unwrap();
     }
 
-    let url = check_public_url(environment, value, false)?;
-    let hosting_type = eval_hosting_type(environment, &url);
+    let (url, hosting_type) = check_public_versioned_url(environment, value)?;
     let host_reg: Option<&Regex> = match hosting_type {
         HostingType::GitHub => Some(&R_GIT_HUB_PATH),
         HostingType::GitLab => Some(&R_GIT_LAB_PATH),
         HostingType::BitBucket => Some(&R_BIT_BUCKET_PATH),
+        HostingType::Gitea => Some(&R_GITEA_PATH),
+        HostingType::Girocco => Some(&R_GIROCCO_PATH),
+        HostingType::Allura => Some(&R_ALLURA_PATH),
+        HostingType::AzureDevOps => Some(&R_AZURE_DEV_OPS_PATH),
+        HostingType::CodeCommit => Some(&R_CODE_COMMIT_PATH),
         _ => None, // TODO Implement the others
     };
     check_url_path(value, "versioned dir prefix", &url, host_reg)
@@ -575,19 +754,249 @@ fn validate_repo_commit_prefix_url(environment: &mut Environment, value: &str) -
                 .unwrap();
         static ref R_BIT_BUCKET_PATH: Regex =
             Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/commits$").unwrap();
+        static ref R_GITEA_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/commit$").unwrap();
+        static ref R_GIROCCO_PATH: Regex =
+            Regex::new(r"^/\?p=(?P<repo>.+\.git);a=commit;h=$").unwrap();
+        static ref R_ALLURA_PATH: Regex =
+            Regex::new(r"^/p/(?P<repo>[^/]+)/(?P<mount>[^/]+)/ci$").unwrap();
+        static ref R_AZURE_DEV_OPS_PATH: Regex =
+            Regex::new(r"^/(?P<org>[^/]+)/(?P<project>[^/]+)/_git/(?P<repo>[^/]+)/commit$")
+                .unwrap();
+        static ref R_CODE_COMMIT_PATH: Regex =
+            Regex::new(r"^/codesuite/codecommit/repositories/(?P<repo>[^/]+)/commit$").unwrap();
     }
 
-    let url = check_public_url(environment, value, false)?;
-    let hosting_type = eval_hosting_type(environment, &url);
+    let (url, hosting_type) = check_public_versioned_url(environment, value)?;
     let host_reg: Option<&Regex> = match hosting_type {
         HostingType::GitHub => Some(&R_GIT_HUB_PATH),
         HostingType::GitLab => Some(&R_GIT_LAB_PATH),
         HostingType::BitBucket => Some(&R_BIT_BUCKET_PATH),
+        HostingType::Gitea => Some(&R_GITEA_PATH),
+        HostingType::Girocco => Some(&R_GIROCCO_PATH),
+        HostingType::Allura => Some(&R_ALLURA_PATH),
+        HostingType::AzureDevOps => Some(&R_AZURE_DEV_OPS_PATH),
+        HostingType::CodeCommit => Some(&R_CODE_COMMIT_PATH),
         _ => None, // TODO Implement the others
     };
     check_url_path(value, "commit prefix", &url, host_reg)
 }
 
+/// The kinds of URLs pointing into the repo at a specific commit.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum PinnedUrl {
+    VersionedFileBase,
+    Tree,
+    RawBase,
+    Commit,
+}
+
+/// The regexes the path part of pinned URLs have to match,
+/// per kind and hosting type.
+/// See also `value_conversions::versioned_base_url` and `value_conversions::commit_url`.
+const PINNED_URL_PATHS: &[(PinnedUrl, HostingType, &str)] = &[
+    (
+        PinnedUrl::VersionedFileBase,
+        HostingType::GitHub,
+        r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/blob/(?P<ref>.+)$",
+    ),
+    (
+        PinnedUrl::VersionedFileBase,
+        HostingType::GitLab,
+        r"^/(?P<user>[^/]+)/((?P<structure>[^/]+)/)*(?P<repo>[^/]+)/(-/)?blob/(?P<ref>.+)$",
+    ),
+    (
+        PinnedUrl::VersionedFileBase,
+        HostingType::BitBucket,
+        r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/src/(?P<ref>.+)$",
+    ),
+    (
+        PinnedUrl::VersionedFileBase,
+        HostingType::Gitea,
+        r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/src/commit/(?P<ref>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::VersionedFileBase,
+        HostingType::Girocco,
+        r"^/\?p=(?P<repo>.+\.git);a=blob;hb=(?P<ref>.+)$",
+    ),
+    (
+        PinnedUrl::VersionedFileBase,
+        HostingType::Allura,
+        r"^/p/(?P<repo>[^/]+)/(?P<mount>[^/]+)/ci/(?P<ref>[^/]+)/tree$",
+    ),
+    (
+        PinnedUrl::VersionedFileBase,
+        HostingType::AzureDevOps,
+        r"^/(?P<org>[^/]+)/(?P<project>[^/]+)/_git/(?P<repo>[^/]+)\?version=GC(?P<ref>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::VersionedFileBase,
+        HostingType::CodeCommit,
+        r"^/codesuite/codecommit/repositories/(?P<repo>[^/]+)/browse/(?P<ref>.+)/--$",
+    ),
+    (
+        PinnedUrl::Tree,
+        HostingType::GitHub,
+        r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/tree/(?P<ref>.+)$",
+    ),
+    (
+        PinnedUrl::Tree,
+        HostingType::GitLab,
+        r"^/(?P<user>[^/]+)/((?P<structure>[^/]+)/)*(?P<repo>[^/]+)/(-/)?tree/(?P<ref>.+)$",
+    ),
+    (
+        PinnedUrl::Tree,
+        HostingType::BitBucket,
+        r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/src/(?P<ref>.+)$",
+    ),
+    (
+        PinnedUrl::Tree,
+        HostingType::Gitea,
+        r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/src/commit/(?P<ref>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::Tree,
+        HostingType::Girocco,
+        r"^/\?p=(?P<repo>.+\.git);a=tree;hb=(?P<ref>.+)$",
+    ),
+    (
+        PinnedUrl::Tree,
+        HostingType::Allura,
+        r"^/p/(?P<repo>[^/]+)/(?P<mount>[^/]+)/ci/(?P<ref>[^/]+)/tree$",
+    ),
+    (
+        PinnedUrl::Tree,
+        HostingType::AzureDevOps,
+        r"^/(?P<org>[^/]+)/(?P<project>[^/]+)/_git/(?P<repo>[^/]+)\?version=GC(?P<ref>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::Tree,
+        HostingType::CodeCommit,
+        r"^/codesuite/codecommit/repositories/(?P<repo>[^/]+)/browse/(?P<ref>.+)/--$",
+    ),
+    // Allura has no raw base URL,
+    // as it expects the raw format to be requested after the file path
+    (
+        PinnedUrl::RawBase,
+        HostingType::GitHub,
+        r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/(?P<ref>.+)$",
+    ),
+    (
+        PinnedUrl::RawBase,
+        HostingType::GitLab,
+        r"^/(?P<user>[^/]+)/((?P<structure>[^/]+)/)*(?P<repo>[^/]+)/(-/)?raw/(?P<ref>.+)$",
+    ),
+    (
+        PinnedUrl::RawBase,
+        HostingType::BitBucket,
+        r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/raw/(?P<ref>.+)$",
+    ),
+    (
+        PinnedUrl::RawBase,
+        HostingType::Gitea,
+        r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/raw/commit/(?P<ref>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::RawBase,
+        HostingType::Girocco,
+        r"^/\?p=(?P<repo>.+\.git);a=blob_plain;hb=(?P<ref>.+)$",
+    ),
+    (
+        PinnedUrl::RawBase,
+        HostingType::AzureDevOps,
+        r"^/(?P<org>[^/]+)/(?P<project>[^/]+)/_apis/git/repositories/(?P<repo>[^/]+)/items\?versionDescriptor\.versionType=commit&versionDescriptor\.version=(?P<ref>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::Commit,
+        HostingType::GitHub,
+        r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/commit/(?P<sha>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::Commit,
+        HostingType::GitLab,
+        r"^/(?P<user>[^/]+)/((?P<structure>[^/]+)/)*(?P<repo>[^/]+)/(-/)?commit/(?P<sha>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::Commit,
+        HostingType::BitBucket,
+        r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/commits/(?P<sha>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::Commit,
+        HostingType::Gitea,
+        r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/commit/(?P<sha>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::Commit,
+        HostingType::Girocco,
+        r"^/\?p=(?P<repo>.+\.git);a=commit;h=(?P<sha>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::Commit,
+        HostingType::Allura,
+        r"^/p/(?P<repo>[^/]+)/(?P<mount>[^/]+)/ci/(?P<sha>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::Commit,
+        HostingType::AzureDevOps,
+        r"^/(?P<org>[^/]+)/(?P<project>[^/]+)/_git/(?P<repo>[^/]+)/commit/(?P<sha>[0-9a-f]+)$",
+    ),
+    (
+        PinnedUrl::Commit,
+        HostingType::CodeCommit,
+        r"^/codesuite/codecommit/repositories/(?P<repo>[^/]+)/commit/(?P<sha>[0-9a-f]+)$",
+    ),
+];
+
+/// Validates a URL pointing into the repo at a specific commit,
+/// by the regex for its kind and hosting type from [`PINNED_URL_PATHS`].
+fn validate_pinned_url(
+    environment: &mut Environment,
+    value: &str,
+    kind: PinnedUrl,
+    url_desc: &str,
+) -> Result {
+    lazy_static! {
+        static ref R_PINNED_URL_PATHS: Vec<(PinnedUrl, HostingType, Regex)> = PINNED_URL_PATHS
+            .iter()
+            .map(|(kind, hosting_type, path_reg)| {
+                (*kind, *hosting_type, Regex::new(path_reg).unwrap())
+            })
+            .collect();
+    }
+
+    let (url, hosting_type) = check_public_versioned_url(environment, value)?;
+    let host_reg = R_PINNED_URL_PATHS
+        .iter()
+        .find(|(reg_kind, reg_hosting_type, _path_reg)| {
+            *reg_kind == kind && *reg_hosting_type == hosting_type
+        })
+        .map(|(_kind, _hosting_type, path_reg)| path_reg);
+    check_url_path(value, url_desc, &url, host_reg)
+}
+
+fn validate_repo_versioned_file_url_base(environment: &mut Environment, value: &str) -> Result {
+    validate_pinned_url(
+        environment,
+        value,
+        PinnedUrl::VersionedFileBase,
+        "versioned file base",
+    )
+}
+
+fn validate_repo_tree_url(environment: &mut Environment, value: &str) -> Result {
+    validate_pinned_url(environment, value, PinnedUrl::Tree, "tree")
+}
+
+fn validate_repo_raw_url_base(environment: &mut Environment, value: &str) -> Result {
+    validate_pinned_url(environment, value, PinnedUrl::RawBase, "raw base")
+}
+
+fn validate_repo_commit_url(environment: &mut Environment, value: &str) -> Result {
+    validate_pinned_url(environment, value, PinnedUrl::Commit, "commit")
+}
+
 fn validate_repo_issues_url(environment: &mut Environment, value: &str) -> Result {
     lazy_static! {
         static ref R_GIT_HUB_PATH: Regex =
@@ -597,6 +1006,11 @@ fn validate_repo_issues_url(environment: &mut Environment, value: &str) -> Resul
                 .unwrap();
         static ref R_BIT_BUCKET_PATH: Regex =
             Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/issues$").unwrap();
+        static ref R_GITEA_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/issues$").unwrap();
+        static ref R_ALLURA_PATH: Regex = Regex::new(r"^/p/(?P<repo>[^/]+)/tickets/?$").unwrap();
+        static ref R_AZURE_DEV_OPS_PATH: Regex =
+            Regex::new(r"^/(?P<org>[^/]+)/(?P<project>[^/]+)/_workitems$").unwrap();
     }
 
     let url = check_public_url(environment, value, false)?;
@@ -605,23 +1019,204 @@ fn validate_repo_issues_url(environment: &mut Environment, value: &str) -> Resul
         HostingType::GitHub => Some(&R_GIT_HUB_PATH),
         HostingType::GitLab => Some(&R_GIT_LAB_PATH),
         HostingType::BitBucket => Some(&R_BIT_BUCKET_PATH),
+        HostingType::Gitea => Some(&R_GITEA_PATH),
+        HostingType::Allura => Some(&R_ALLURA_PATH),
+        HostingType::AzureDevOps => Some(&R_AZURE_DEV_OPS_PATH),
         _ => None, // TODO Implement the others
     };
     check_url_path(value, "issues", &url, host_reg)
 }
 
+fn validate_repo_releases_url(environment: &mut Environment, value: &str) -> Result {
+    lazy_static! {
+        static ref R_GIT_HUB_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/releases/?$").unwrap();
+        static ref R_GIT_LAB_PATH: Regex = Regex::new(
+            r"^/(?P<user>[^/]+)/((?P<structure>[^/]+)/)*(?P<repo>[^/]+)/(-/)?releases/?$"
+        )
+        .unwrap();
+        static ref R_BIT_BUCKET_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/downloads/?$").unwrap();
+        static ref R_GITEA_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/releases/?$").unwrap();
+    }
+
+    let url = check_public_url(environment, value, false)?;
+    let hosting_type = eval_hosting_type(environment, &url);
+    let host_reg: Option<&Regex> = match hosting_type {
+        HostingType::GitHub => Some(&R_GIT_HUB_PATH),
+        HostingType::GitLab => Some(&R_GIT_LAB_PATH),
+        HostingType::BitBucket => Some(&R_BIT_BUCKET_PATH),
+        HostingType::Gitea => Some(&R_GITEA_PATH),
+        _ => None, // TODO Implement the others
+    };
+    check_url_path(value, "releases", &url, host_reg)
+}
+
+fn validate_repo_tag_url(environment: &mut Environment, value: &str) -> Result {
+    lazy_static! {
+        static ref R_GIT_HUB_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/(releases/tag|tree)/(?P<tag>.+)$").unwrap();
+        static ref R_GIT_LAB_PATH: Regex = Regex::new(
+            r"^/(?P<user>[^/]+)/((?P<structure>[^/]+)/)*(?P<repo>[^/]+)/(-/)?(tags|releases)/(?P<tag>.+)$"
+        )
+        .unwrap();
+        static ref R_BIT_BUCKET_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/src/(?P<tag>.+)$").unwrap();
+        static ref R_GITEA_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/(releases/tag|src/tag)/(?P<tag>.+)$").unwrap();
+    }
+
+    let url = check_public_url(environment, value, false)?;
+    let hosting_type = eval_hosting_type(environment, &url);
+    let host_reg: Option<&Regex> = match hosting_type {
+        HostingType::GitHub => Some(&R_GIT_HUB_PATH),
+        HostingType::GitLab => Some(&R_GIT_LAB_PATH),
+        HostingType::BitBucket => Some(&R_BIT_BUCKET_PATH),
+        HostingType::Gitea => Some(&R_GITEA_PATH),
+        _ => None, // TODO Implement the others
+    };
+    check_url_path(value, "tag", &url, host_reg)
+}
+
+fn validate_repo_compare_url(environment: &mut Environment, value: &str) -> Result {
+    lazy_static! {
+        static ref R_GIT_HUB_PATH: Regex = Regex::new(
+            r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/compare/(?P<from>.+)\.\.\.?(?P<to>.+)$"
+        )
+        .unwrap();
+        static ref R_GIT_LAB_PATH: Regex = Regex::new(
+            r"^/(?P<user>[^/]+)/((?P<structure>[^/]+)/)*(?P<repo>[^/]+)/(-/)?compare/(?P<from>.+)\.\.\.?(?P<to>.+)$"
+        )
+        .unwrap();
+        static ref R_BIT_BUCKET_PATH: Regex = Regex::new(
+            r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/branches/compare/(?P<to>.+)%0D(?P<from>.+)$"
+        )
+        .unwrap();
+        static ref R_GITEA_PATH: Regex = Regex::new(
+            r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/compare/(?P<from>.+)\.\.\.?(?P<to>.+)$"
+        )
+        .unwrap();
+    }
+
+    let url = check_public_url(environment, value, false)?;
+    let hosting_type = eval_hosting_type(environment, &url);
+    let host_reg: Option<&Regex> = match hosting_type {
+        HostingType::GitHub => Some(&R_GIT_HUB_PATH),
+        HostingType::GitLab => Some(&R_GIT_LAB_PATH),
+        HostingType::BitBucket => Some(&R_BIT_BUCKET_PATH),
+        HostingType::Gitea => Some(&R_GITEA_PATH),
+        _ => None, // TODO Implement the others
+    };
+    check_url_path(value, "compare", &url, host_reg)
+}
+
+fn validate_repo_new_merge_request_url(environment: &mut Environment, value: &str) -> Result {
+    lazy_static! {
+        static ref R_GIT_HUB_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/(compare|pull/new/.+)$").unwrap();
+        static ref R_GIT_LAB_PATH: Regex = Regex::new(
+            r"^/(?P<user>[^/]+)/((?P<structure>[^/]+)/)*(?P<repo>[^/]+)/(-/)?merge_requests/new$"
+        )
+        .unwrap();
+        static ref R_BIT_BUCKET_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/pull-requests/new$").unwrap();
+        static ref R_GITEA_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/compare(/.+)?$").unwrap();
+    }
+
+    let url = check_public_url(environment, value, false)?;
+    let hosting_type = eval_hosting_type(environment, &url);
+    let host_reg: Option<&Regex> = match hosting_type {
+        HostingType::GitHub => Some(&R_GIT_HUB_PATH),
+        HostingType::GitLab => Some(&R_GIT_LAB_PATH),
+        HostingType::BitBucket => Some(&R_BIT_BUCKET_PATH),
+        HostingType::Gitea => Some(&R_GITEA_PATH),
+        _ => None, // TODO Implement the others
+    };
+    check_url_path(value, "new merge request", &url, host_reg)
+}
+
+fn validate_repo_wiki_url(environment: &mut Environment, value: &str) -> Result {
+    lazy_static! {
+        static ref R_GIT_HUB_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/wiki/?$").unwrap();
+        static ref R_GIT_LAB_PATH: Regex = Regex::new(
+            r"^/(?P<user>[^/]+)/((?P<structure>[^/]+)/)*(?P<repo>[^/]+)/(-/)?wikis(/home)?/?$"
+        )
+        .unwrap();
+        static ref R_BIT_BUCKET_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/wiki(/Home)?/?$").unwrap();
+        static ref R_GITEA_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/wiki/?$").unwrap();
+    }
+
+    let url = check_public_url(environment, value, false)?;
+    let hosting_type = eval_hosting_type(environment, &url);
+    let host_reg: Option<&Regex> = match hosting_type {
+        HostingType::GitHub => Some(&R_GIT_HUB_PATH),
+        HostingType::GitLab => Some(&R_GIT_LAB_PATH),
+        HostingType::BitBucket => Some(&R_BIT_BUCKET_PATH),
+        HostingType::Gitea => Some(&R_GITEA_PATH),
+        _ => None, // TODO Implement the others
+    };
+    check_url_path(value, "wiki", &url, host_reg)
+}
+
+fn validate_repo_pipelines_url(environment: &mut Environment, value: &str) -> Result {
+    lazy_static! {
+        static ref R_GIT_HUB_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/actions/?$").unwrap();
+        static ref R_GIT_LAB_PATH: Regex = Regex::new(
+            r"^/(?P<user>[^/]+)/((?P<structure>[^/]+)/)*(?P<repo>[^/]+)/(-/)?pipelines/?$"
+        )
+        .unwrap();
+        static ref R_BIT_BUCKET_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/pipelines/?$").unwrap();
+        static ref R_GITEA_PATH: Regex =
+            Regex::new(r"^/(?P<user>[^/]+)/(?P<repo>[^/]+)/actions/?$").unwrap();
+    }
+
+    let url = check_public_url(environment, value, false)?;
+    let hosting_type = eval_hosting_type(environment, &url);
+    let host_reg: Option<&Regex> = match hosting_type {
+        HostingType::GitHub => Some(&R_GIT_HUB_PATH),
+        HostingType::GitLab => Some(&R_GIT_LAB_PATH),
+        HostingType::BitBucket => Some(&R_BIT_BUCKET_PATH),
+        HostingType::Gitea => Some(&R_GITEA_PATH),
+        _ => None, // TODO Implement the others
+    };
+    check_url_path(value, "pipelines", &url, host_reg)
+}
+
 fn validate_build_hosting_url(environment: &mut Environment, value: &str) -> Result {
     lazy_static! {
         static ref R_GIT_HUB_HOST: Regex = Regex::new(r"^(?P<user>[^/.]+)\.github\.io$").unwrap();
         static ref R_GIT_LAB_HOST: Regex = Regex::new(r"^(?P<user>[^/.]+)\.gitlab\.io$").unwrap();
+        static ref R_CODE_BERG_HOST: Regex = Regex::new(r"^(?P<user>[^/.]+)\.codeberg\.page$").unwrap();
+        static ref R_SOURCE_FORGE_HOST: Regex = Regex::new(r"^(?P<repo>[^/.]+)\.sourceforge\.io$").unwrap();
         // NOTE BitBucket does not have this feature, it only supports one "page" repo per user, not per repo
     }
 
     let url = check_public_url(environment, value, false)?;
     let hosting_type = eval_hosting_type_from_hosting_suffix(environment, &url);
+    // Self-hosted GitLab instances serve their pages under a domain of their own
+    let pages_domain_host = match (hosting_type, &environment.settings.pages_domain) {
+        (HostingType::GitLab, Some(pages_domain)) => Regex::new(&format!(
+            r"^(?P<user>[^/.]+)\.{}$",
+            regex::escape(pages_domain)
+        ))
+        .ok(),
+        _ => None,
+    };
     let host_reg: Option<&Regex> = match hosting_type {
         HostingType::GitHub => Some(&R_GIT_HUB_HOST),
-        HostingType::GitLab => Some(&R_GIT_LAB_HOST),
+        HostingType::GitLab if R_GIT_LAB_HOST.is_match(url.host_str().unwrap_or_default()) => {
+            Some(&R_GIT_LAB_HOST)
+        }
+        HostingType::GitLab => pages_domain_host.as_ref(),
+        HostingType::Gitea => Some(&R_CODE_BERG_HOST),
+        HostingType::Allura => Some(&R_SOURCE_FORGE_HOST),
         _ => None, // TODO Implement the others (BitBucket does not have pages though, so skip it!)
     };
     check_url_host(value, "build hosting", &url, host_reg)
@@ -652,43 +1247,148 @@ fn validate_name_machine_readable(environment: &mut Environment, value: &str) ->
     }
 }
 
-fn check_date(environment: &mut Environment, value: &str, date_desc: &str) -> Result {
-    if value.is_empty() {
+/// Checks a repo slug or a part of it,
+/// consisting of `min_parts` or more non-empty parts, separated by '/'.
+fn check_slug(environment: &mut Environment, value: &str, min_parts: usize, desc: &str) -> Result {
+    lazy_static! {
+        static ref R_SLUG: Regex = Regex::new(r"^[^/\s]+(/[^/\s]+)*$").unwrap();
+    }
+
+    check_empty(environment, value, desc)?;
+    if !R_SLUG.is_match(value) {
         return Err(Error::BadValue {
-            // TODO Maybe replace with a call to missing(...) ?
-            msg: format!("{} date can not be empty", date_desc),
+            msg: format!(
+                "{} has to consist of non-empty parts without white-space, separated by '/', matching '{}'",
+                desc,
+                R_SLUG.as_str()
+            ),
+            value: value.to_owned(),
+        });
+    }
+    let parts = value.split('/').count();
+    if parts < min_parts {
+        return Err(Error::BadValue {
+            msg: format!(
+                "{} has to consist of at least {} parts separated by '/', but has {}",
+                desc, min_parts, parts
+            ),
+            value: value.to_owned(),
+        });
+    }
+    Ok(Validity::High {
+        msg: Some(format!("Matches regex '{}'", R_SLUG.as_str())),
+    })
+}
+
+fn validate_repo_slug(environment: &mut Environment, value: &str) -> Result {
+    check_slug(environment, value, 2, "Repo slug")
+}
+
+fn validate_repo_namespace(environment: &mut Environment, value: &str) -> Result {
+    check_slug(environment, value, 1, "Repo namespace")
+}
+
+fn validate_repo_owner(environment: &mut Environment, value: &str) -> Result {
+    if value.contains('/') {
+        return Err(Error::BadValue {
+            msg: "Repo owner can not contain '/'".to_owned(),
             value: value.to_owned(),
         });
     }
+    check_slug(environment, value, 1, "Repo owner")
+}
+
+fn validate_container_image_name(environment: &mut Environment, value: &str) -> Result {
+    lazy_static! {
+        // See <https://github.com/distribution/distribution/blob/main/reference/regexp.go>,
+        // though we require the registry part
+        static ref R_CONTAINER_IMAGE_NAME: Regex = Regex::new(
+            r"^(?P<registry>[a-zA-Z0-9]([a-zA-Z0-9-]*[a-zA-Z0-9])?(\.[a-zA-Z0-9]([a-zA-Z0-9-]*[a-zA-Z0-9])?)*(:[0-9]+)?)(?P<path>(/[a-z0-9]+((\.|_|__|-+)[a-z0-9]+)*)+)$"
+        )
+        .unwrap();
+    }
 
-    let parse_err = NaiveDateTime::parse_from_str(value, &environment.settings.date_format)
-        .err()
-        .and_then(|_err| DateTime::parse_from_str(value, &environment.settings.date_format).err());
-    if let Some(err) = parse_err {
-        // log::error!("XXX {}", NaiveDateTime::parse_from_str(value, &environment.settings.date_format).unwrap_err());
+    check_empty(environment, value, "Container image name")?;
+    if R_CONTAINER_IMAGE_NAME.is_match(value) {
+        Ok(Validity::High {
+            msg: Some(format!(
+                "Matches regex '{}'",
+                R_CONTAINER_IMAGE_NAME.as_str()
+            )),
+        })
+    } else {
         Err(Error::BadValue {
             msg: format!(
-                r#"Not a {} date according to the date-format "{}": {}"#,
-                date_desc, environment.settings.date_format, err
+                "Not a valid container image name (including the registry), does not match '{}'",
+                R_CONTAINER_IMAGE_NAME.as_str()
             ),
             value: value.to_owned(),
         })
-    } else {
+    }
+}
+
+fn validate_container_image_tag(environment: &mut Environment, value: &str) -> Result {
+    lazy_static! {
+        static ref R_CONTAINER_IMAGE_TAG: Regex =
+            Regex::new(r"^[a-zA-Z0-9_][a-zA-Z0-9_.-]{0,127}$").unwrap();
+    }
+
+    check_empty(environment, value, "Container image tag")?;
+    if R_CONTAINER_IMAGE_TAG.is_match(value) {
         Ok(Validity::High {
             msg: Some(format!(
-                "Matches the date format '{}'",
-                environment.settings.date_format
+                "Matches regex '{}'",
+                R_CONTAINER_IMAGE_TAG.as_str()
             )),
         })
+    } else {
+        Err(Error::BadValue {
+            msg: format!(
+                "Not a valid container image tag, does not match '{}'",
+                R_CONTAINER_IMAGE_TAG.as_str()
+            ),
+            value: value.to_owned(),
+        })
+    }
+}
+
+/// Checks a date in RFC 3339 format,
+/// or in the date format of the key (see [`crate::settings::Settings::date_format`]).
+/// This accepts exactly what [`Value::parse`] would parse into a [`Value::Date`].
+fn check_date(environment: &mut Environment, key: Key, value: &str, date_desc: &str) -> Result {
+    if value.is_empty() {
+        return Err(Error::BadValue {
+            // TODO Maybe replace with a call to missing(...) ?
+            msg: format!("{} date can not be empty", date_desc),
+            value: value.to_owned(),
+        });
+    }
+
+    let date_format = environment.settings.date_format(key);
+    if date_format
+        .parse(value, environment.settings.timezone)
+        .is_some()
+    {
+        Ok(Validity::High {
+            msg: Some(format!("Matches the date format '{}'", date_format)),
+        })
+    } else {
+        Err(Error::BadValue {
+            msg: format!(
+                r#"Not a {} date in RFC 3339 format, nor according to the date-format "{}""#,
+                date_desc, date_format
+            ),
+            value: value.to_owned(),
+        })
     }
 }
 
 fn validate_version_date(environment: &mut Environment, value: &str) -> Result {
-    check_date(environment, value, "version")
+    check_date(environment, Key::VersionDate, value, "version")
 }
 
 fn validate_build_date(environment: &mut Environment, value: &str) -> Result {
-    check_date(environment, value, "build")
+    check_date(environment, Key::BuildDate, value, "build")
 }
 
 fn validate_build_branch(environment: &mut Environment, value: &str) -> Result {
@@ -754,6 +1454,20 @@ fn validate_build_number(environment: &mut Environment, value: &str) -> Result {
     }
 }
 
+fn validate_source_date_epoch(environment: &mut Environment, value: &str) -> Result {
+    check_empty(environment, value, "Source date epoch")?;
+    match value.parse::<u64>() {
+        Err(err) => Err(Error::BadValue {
+            msg: format!(
+                "Has to be a non-negative integer (seconds since the Unix epoch): {}",
+                err
+            ),
+            value: value.to_owned(),
+        }),
+        Ok(_int_value) => Ok(Validity::High { msg: None }),
+    }
+}
+
 fn validate_ci(environment: &mut Environment, value: &str) -> Result {
     check_empty(environment, value, "CI")?;
     match value {
@@ -766,35 +1480,353 @@ fn validate_ci(environment: &mut Environment, value: &str) -> Result {
     }
 }
 
-#[remain::check]
-#[must_use]
-pub fn get(key: Key) -> Validator {
-    // TODO This match could be written by a macro
-    #[remain::sorted]
-    match key {
-        Key::BuildArch => validate_build_arch,
-        Key::BuildBranch => validate_build_branch,
-        Key::BuildDate => validate_build_date,
+fn validate_commit_sha(environment: &mut Environment, value: &str) -> Result {
+    lazy_static! {
+        // SHA-1 or SHA-256 hash, in lower-case hex
+        static ref R_COMMIT_SHA: Regex = Regex::new(r"^([0-9a-f]{40}|[0-9a-f]{64})$").unwrap();
+    }
+
+    check_empty(environment, value, "Commit SHA")?;
+    if R_COMMIT_SHA.is_match(value) {
+        Ok(Validity::High { msg: None })
+    } else {
+        Err(Error::BadValue {
+            msg: "Has to be a full SHA-1 (40) or SHA-256 (64) hash in lower-case hex".to_owned(),
+            value: value.to_owned(),
+        })
+    }
+}
+
+fn validate_commit_sha_short(environment: &mut Environment, value: &str) -> Result {
+    lazy_static! {
+        static ref R_COMMIT_SHA_SHORT: Regex = Regex::new(r"^[0-9a-f]{4,64}$").unwrap();
+    }
+
+    check_empty(environment, value, "Commit SHA short")?;
+    if !R_COMMIT_SHA_SHORT.is_match(value) {
+        Err(Error::BadValue {
+            msg: "Has to be an abbreviated hash of 4 or more lower-case hex characters".to_owned(),
+            value: value.to_owned(),
+        })
+    } else if value.len() < SHORT_SHA_LEN {
+        Ok(Validity::Suboptimal {
+            msg: format!(
+                "Abbreviated hashes with less than {} characters are likely to be ambiguous",
+                SHORT_SHA_LEN
+            ),
+            source: None,
+        })
+    } else {
+        Ok(Validity::High { msg: None })
+    }
+}
+
+fn check_count(environment: &mut Environment, value: &str, part_desc: &str) -> Result {
+    check_empty(environment, value, part_desc)?;
+    match value.parse::<u32>() {
+        Err(err) => Err(Error::BadValue {
+            msg: format!("Has to be a positive integer: {}", err),
+            value: value.to_owned(),
+        }),
+        Ok(_int_value) => Ok(Validity::High { msg: None }),
+    }
+}
+
+fn validate_commits_since_tag(environment: &mut Environment, value: &str) -> Result {
+    check_count(environment, value, "Commits since tag")
+}
+
+fn validate_commit_count(environment: &mut Environment, value: &str) -> Result {
+    check_count(environment, value, "Commit count")?;
+    if value == "0" {
+        return Err(Error::BadValue {
+            msg: "There is at least one commit, the one being built".to_owned(),
+            value: value.to_owned(),
+        });
+    }
+    Ok(Validity::High { msg: None })
+}
+
+fn validate_author_count(environment: &mut Environment, value: &str) -> Result {
+    check_count(environment, value, "Author count")?;
+    if value == "0" {
+        return Err(Error::BadValue {
+            msg: "There is at least one author, the one of the commit being built".to_owned(),
+            value: value.to_owned(),
+        });
+    }
+    Ok(Validity::High { msg: None })
+}
+
+fn validate_first_commit_date(environment: &mut Environment, value: &str) -> Result {
+    check_date(environment, Key::FirstCommitDate, value, "first commit")
+}
+
+fn validate_previous_tag(environment: &mut Environment, value: &str) -> Result {
+    check_empty(environment, value, "Previous tag")?;
+    let pattern = environment.settings.release_tag_pattern.as_ref();
+    if version::release_version(pattern, value).is_some() {
+        Ok(Validity::High { msg: None })
+    } else {
+        Err(Error::BadValue {
+            msg: "Not a release tag (see --release-tag-pattern)".to_owned(),
+            value: value.to_owned(),
+        })
+    }
+}
+
+fn check_bool(environment: &mut Environment, value: &str, part_desc: &str) -> Result {
+    check_empty(environment, value, part_desc)?;
+    match value {
+        "true" | "false" => Ok(Validity::High { msg: None }),
+        _ => Err(Error::BadValue {
+            msg: "Has to be either 'true' or 'false'".to_owned(),
+            value: value.to_owned(),
+        }),
+    }
+}
+
+fn validate_dirty(environment: &mut Environment, value: &str) -> Result {
+    check_bool(environment, value, "Dirty")
+}
+
+fn validate_is_release(environment: &mut Environment, value: &str) -> Result {
+    check_bool(environment, value, "Is release")
+}
+
+fn check_version_component(environment: &mut Environment, value: &str, part_desc: &str) -> Result {
+    check_empty(environment, value, part_desc)?;
+    match value.parse::<u64>() {
+        Ok(_int_value) if value == "0" || !value.starts_with('0') => {
+            Ok(Validity::High { msg: None })
+        }
+        Ok(_int_value) => Err(Error::AlmostUsableValue {
+            msg: "Leading zeros are not allowed in semantic versions".to_owned(),
+            value: value.to_owned(),
+        }),
+        Err(err) => Err(Error::BadValue {
+            msg: format!("Has to be a positive integer: {}", err),
+            value: value.to_owned(),
+        }),
+    }
+}
+
+fn validate_version_major(environment: &mut Environment, value: &str) -> Result {
+    check_version_component(environment, value, "Version major")
+}
+
+fn validate_version_minor(environment: &mut Environment, value: &str) -> Result {
+    check_version_component(environment, value, "Version minor")
+}
+
+fn validate_version_patch(environment: &mut Environment, value: &str) -> Result {
+    check_version_component(environment, value, "Version patch")
+}
+
+fn check_version_in_scheme(
+    environment: &mut Environment,
+    value: &str,
+    scheme: Scheme,
+    part_desc: &str,
+) -> Result {
+    check_empty(environment, value, part_desc)?;
+    if scheme.is_valid(value) {
+        Ok(Validity::High { msg: None })
+    } else {
+        Err(Error::BadValue {
+            msg: format!("Not a valid {} version", <&str>::from(scheme)),
+            value: value.to_owned(),
+        })
+    }
+}
+
+fn validate_version_debian(environment: &mut Environment, value: &str) -> Result {
+    check_version_in_scheme(environment, value, Scheme::Debian, "Debian version")
+}
+
+fn validate_version_pep440(environment: &mut Environment, value: &str) -> Result {
+    check_version_in_scheme(environment, value, Scheme::Pep440, "PEP 440 version")
+}
+
+fn validate_version_bump(environment: &mut Environment, value: &str) -> Result {
+    check_empty(environment, value, "Version bump")?;
+    match Bump::from_str(value) {
+        Ok(_bump) => Ok(Validity::High { msg: None }),
+        Err(_err) => Err(Error::BadValue {
+            msg: format!("Has to be one of: {}", Bump::VARIANTS.join(", ")),
+            value: value.to_owned(),
+        }),
+    }
+}
+
+fn validate_version_next(environment: &mut Environment, value: &str) -> Result {
+    check_empty(environment, value, "Next version")?;
+    match semver::Version::parse(value) {
+        Ok(_version) => Ok(Validity::High { msg: None }),
+        Err(err) => Err(Error::BadValue {
+            msg: format!("Not a semantic version (see https://semver.org): {}", err),
+            value: value.to_owned(),
+        }),
+    }
+}
+
+fn validate_version_prerelease(environment: &mut Environment, value: &str) -> Result {
+    check_empty(environment, value, "Version pre-release")?;
+    match semver::Prerelease::new(value) {
+        Ok(_pre) => Ok(Validity::High { msg: None }),
+        Err(err) => Err(Error::BadValue {
+            msg: format!("Not a valid semantic version pre-release: {}", err),
+            value: value.to_owned(),
+        }),
+    }
+}
+
+/// Used for keys that can not be validated through a plain [`Validator`];
+/// see [`validate`].
+fn validate_unknown(_environment: &mut Environment, _value: &str) -> Result {
+    Ok(Validity::Unknown)
+}
+
+fn validate_custom(environment: &mut Environment, id: custom_keys::Id, value: &str) -> Result {
+    let custom_key = custom_keys::get(id);
+    check_empty(environment, value, custom_key.name)?;
+    match &custom_key.check {
+        None => Ok(Validity::Unknown),
+        Some(Check::Regex(regex)) => {
+            if regex.is_match(value) {
+                Ok(Validity::High {
+                    msg: Some(format!("Matches regex '{}'", regex.as_str())),
+                })
+            } else {
+                Err(Error::BadValue {
+                    msg: format!("Does not match regex '{}'", regex.as_str()),
+                    value: value.to_owned(),
+                })
+            }
+        }
+        Some(Check::Builtin(Builtin::Bool)) => match value {
+            "true" | "false" => Ok(Validity::High { msg: None }),
+            _ => Err(Error::BadValue {
+                msg: "Has to be either 'true' or 'false'".to_owned(),
+                value: value.to_owned(),
+            }),
+        },
+        Some(Check::Builtin(Builtin::Date)) => {
+            check_date(environment, Key::Custom(id), value, custom_key.name)
+        }
+        Some(Check::Builtin(Builtin::Integer)) => match value.parse::<i64>() {
+            Ok(_int_value) => Ok(Validity::High { msg: None }),
+            Err(err) => Err(Error::BadValue {
+                msg: format!("Not an integer: {}", err),
+                value: value.to_owned(),
+            }),
+        },
+        Some(Check::Builtin(Builtin::Semver)) => {
+            if semver::Version::parse(value).is_ok() {
+                Ok(Validity::High { msg: None })
+            } else {
+                Err(Error::BadValue {
+                    msg: "Not a semantic version (see https://semver.org)".to_owned(),
+                    value: value.to_owned(),
+                })
+            }
+        }
+        Some(Check::Builtin(Builtin::Url)) => match Url::parse(value) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => {
+                Ok(Validity::High { msg: None })
+            }
+            Ok(url) => Err(Error::BadValue {
+                msg: format!("Not an HTTP(S) URL, but uses the scheme '{}'", url.scheme()),
+                value: value.to_owned(),
+            }),
+            Err(err) => Err(Error::BadValue {
+                msg: format!("Not a valid URL: {}", err),
+                value: value.to_owned(),
+            }),
+        },
+    }
+}
+
+/// Checks the validity of a `value` for a specific `key`.
+/// Unlike [`get`], this also supports custom keys
+/// (see [`crate::custom_keys`]).
+/// Values are checked in their canonical text form
+/// (see [`Value`]), dates in RFC 3339 format.
+///
+/// # Errors
+///
+/// If the value is not valid for the key.
+pub fn validate(environment: &mut Environment, key: Key, value: &Value) -> Result {
+    let value = value.to_string();
+    match key {
+        Key::Custom(id) => validate_custom(environment, id, &value),
+        _ => get(key)(environment, &value),
+    }
+}
+
+#[remain::check]
+#[must_use]
+pub fn get(key: Key) -> Validator {
+    // TODO This match could be written by a macro
+    #[remain::sorted]
+    match key {
+        Key::AuthorCount => validate_author_count,
+        Key::BuildArch => validate_build_arch,
+        Key::BuildBranch => validate_build_branch,
+        Key::BuildDate => validate_build_date,
         Key::BuildHostingUrl => validate_build_hosting_url,
         Key::BuildNumber => validate_build_number,
         Key::BuildOs => validate_build_os,
         Key::BuildOsFamily => validate_build_os_family,
         Key::BuildTag => validate_build_tag,
         Key::Ci => validate_ci,
+        Key::CommitCount => validate_commit_count,
+        Key::CommitSha => validate_commit_sha,
+        Key::CommitShaShort => validate_commit_sha_short,
+        Key::CommitsSinceTag => validate_commits_since_tag,
+        Key::ContainerImageName => validate_container_image_name,
+        Key::ContainerImageTag => validate_container_image_tag,
+        Key::Custom(_) => validate_unknown,
+        Key::Dirty => validate_dirty,
+        Key::FirstCommitDate => validate_first_commit_date,
+        Key::IsRelease => validate_is_release,
         Key::License => validate_license,
         Key::Licenses => validate_licenses,
         Key::Name => validate_name,
         Key::NameMachineReadable => validate_name_machine_readable,
+        Key::PreviousTag => validate_previous_tag,
         Key::RepoCloneUrl => validate_repo_clone_url,
         Key::RepoCloneUrlSsh => validate_repo_clone_url_ssh,
         Key::RepoCommitPrefixUrl => validate_repo_commit_prefix_url,
+        Key::RepoCommitUrl => validate_repo_commit_url,
+        Key::RepoCompareUrl => validate_repo_compare_url,
         Key::RepoIssuesUrl => validate_repo_issues_url,
+        Key::RepoNamespace => validate_repo_namespace,
+        Key::RepoNewMergeRequestUrl => validate_repo_new_merge_request_url,
+        Key::RepoOwner => validate_repo_owner,
+        Key::RepoPipelinesUrl => validate_repo_pipelines_url,
+        Key::RepoRawUrlBase => validate_repo_raw_url_base,
         Key::RepoRawVersionedPrefixUrl => validate_repo_raw_versioned_prefix_url,
+        Key::RepoReleasesUrl => validate_repo_releases_url,
+        Key::RepoSlug => validate_repo_slug,
+        Key::RepoTagUrl => validate_repo_tag_url,
+        Key::RepoTreeUrl => validate_repo_tree_url,
         Key::RepoVersionedDirPrefixUrl => validate_repo_versioned_dir_prefix_url,
         Key::RepoVersionedFilePrefixUrl => validate_repo_versioned_file_prefix_url,
+        Key::RepoVersionedFileUrlBase => validate_repo_versioned_file_url_base,
         Key::RepoWebUrl => validate_repo_web_url,
+        Key::RepoWikiUrl => validate_repo_wiki_url,
+        Key::SourceDateEpoch => validate_source_date_epoch,
         Key::Version => validate_version,
         Key::VersionDate => validate_version_date,
+        Key::VersionBump => validate_version_bump,
+        Key::VersionDebian => validate_version_debian,
+        Key::VersionMajor => validate_version_major,
+        Key::VersionMinor => validate_version_minor,
+        Key::VersionNext => validate_version_next,
+        Key::VersionPatch => validate_version_patch,
+        Key::VersionPep440 => validate_version_pep440,
+        Key::VersionPrerelease => validate_version_prerelease,
     }
 }
 
@@ -962,6 +1994,108 @@ mod tests {
         // TODO Add some more bad cases. producing various different errors
     }
 
+    #[test]
+    fn test_validate_version_prefix() {
+        let mut environment = Environment::stub();
+        assert!(is_good(validate_version(&mut environment, "v0.1.19")));
+        assert!(is_high(validate_version(
+            &mut environment,
+            "v0.1.19-12-gad8f844"
+        )));
+        assert!(is_bad_value(validate_version(&mut environment, "version")));
+        assert!(is_high(validate_version_major(&mut environment, "0")));
+        assert!(is_high(validate_version_minor(&mut environment, "10")));
+        assert!(validate_version_patch(&mut environment, "03").is_err());
+        assert!(is_bad_value(validate_version_patch(&mut environment, "x")));
+        assert!(is_high(validate_version_prerelease(
+            &mut environment,
+            "rc.1"
+        )));
+        assert!(is_bad_value(validate_version_prerelease(
+            &mut environment,
+            "rc..1"
+        )));
+    }
+
+    #[test]
+    fn test_validate_version_scheme() {
+        let mut environment = Environment::stub();
+        environment.settings.version_scheme = Scheme::CalVer;
+        assert!(is_good(validate_version(&mut environment, "2024.03")));
+        assert!(is_high(validate_version(
+            &mut environment,
+            "2024.03-4-gad8f844-dirty"
+        )));
+        assert!(is_suboptimal(validate_version(
+            &mut environment,
+            "gad8f844"
+        )));
+        assert!(is_bad_value(validate_version(&mut environment, "1.2.3")));
+        assert!(is_missing_err(validate_version(&mut environment, "")));
+        environment.settings.version_scheme = Scheme::Pep440;
+        assert!(is_high(validate_version(
+            &mut environment,
+            "1.2.3rc1-4-gad8f844"
+        )));
+        assert!(is_bad_value(validate_version(
+            &mut environment,
+            "1.2.3-rc.1"
+        )));
+        assert!(is_high(validate_version_pep440(
+            &mut environment,
+            "1.2.3.dev4+gad8f844.dirty"
+        )));
+        assert!(is_high(validate_version_debian(
+            &mut environment,
+            "1.2.3~rc.1+4.gad8f844"
+        )));
+        assert!(is_bad_value(validate_version_debian(
+            &mut environment,
+            "v1.2.3"
+        )));
+    }
+
+    #[test]
+    fn test_validate_commit_sha() {
+        let mut environment = Environment::stub();
+        let full_sha = "cf73ea34fcc785b1ac44ffb20d655c917e77c83d";
+        assert!(is_high(validate_commit_sha(&mut environment, full_sha)));
+        assert!(validate_commit_sha(&mut environment, &full_sha[0..7]).is_err());
+        assert!(validate_commit_sha(&mut environment, &full_sha.to_uppercase()).is_err());
+        assert!(is_high(validate_commit_sha_short(
+            &mut environment,
+            &full_sha[0..7]
+        )));
+        assert!(is_suboptimal(validate_commit_sha_short(
+            &mut environment,
+            &full_sha[0..5]
+        )));
+        assert!(validate_commit_sha_short(&mut environment, "cf7").is_err());
+        assert!(validate_commit_sha_short(&mut environment, "v1.2.3").is_err());
+    }
+
+    #[test]
+    fn test_validate_counts() {
+        let mut environment = Environment::stub();
+        assert!(is_high(validate_commit_count(&mut environment, "1")));
+        assert!(is_high(validate_commit_count(&mut environment, "1234")));
+        assert!(validate_commit_count(&mut environment, "0").is_err());
+        assert!(validate_commit_count(&mut environment, "-3").is_err());
+        assert!(is_high(validate_author_count(&mut environment, "7")));
+        assert!(validate_author_count(&mut environment, "0").is_err());
+        assert!(validate_author_count(&mut environment, "").is_err());
+        assert!(is_high(validate_commits_since_tag(&mut environment, "0")));
+    }
+
+    #[test]
+    fn test_validate_previous_tag() {
+        let mut environment = Environment::stub();
+        assert!(is_high(validate_previous_tag(&mut environment, "v1.2.3")));
+        assert!(is_high(validate_previous_tag(&mut environment, "1.2.3")));
+        assert!(validate_previous_tag(&mut environment, "nightly").is_err());
+        assert!(validate_previous_tag(&mut environment, "").is_err());
+    }
+
     #[test]
     fn test_validate_license() {
         let mut environment = Environment::stub();
@@ -995,6 +2129,26 @@ mod tests {
         // todo!(); // TODO Add some more bad cases; Producing different errors
     }
 
+    #[test]
+    fn test_validate_typed() {
+        let mut environment = Environment::stub();
+        let licenses = Value::parse(&environment, Key::Licenses, "MIT, Apache-2.0".to_owned());
+        assert!(is_high(validate(
+            &mut environment,
+            Key::Licenses,
+            &licenses
+        )));
+        let date = Value::parse(
+            &environment,
+            Key::BuildDate,
+            "2021-12-31T23:59:59+02:00".to_owned(),
+        );
+        assert!(matches!(
+            validate(&mut environment, Key::BuildDate, &date),
+            Ok(Validity::High { msg: Some(msg) }) if msg.starts_with("Matches the date format")
+        ));
+    }
+
     #[test]
     fn test_validate_repo_versioned_dir_prefix_url() -> std::result::Result<(), Error> {
         let mut environment = Environment::stub();
@@ -1005,4 +2159,290 @@ mod tests {
         )?;
         Ok(())
     }
+
+    #[test]
+    fn test_validate_pinned_urls() {
+        let mut environment = Environment::stub();
+        let sha = "525b3c9b8962dd02aab6ea867eebdee3719a6634";
+        assert!(is_high(validate_repo_versioned_file_url_base(
+            &mut environment,
+            &format!("https://github.com/hoijui/projvar/blob/{}", sha)
+        )));
+        assert!(is_high(validate_repo_versioned_file_url_base(
+            &mut environment,
+            "https://gitlab.com/hoijui/projvar/-/blob/v0.1.0"
+        )));
+        assert!(validate_repo_versioned_file_url_base(
+            &mut environment,
+            "https://github.com/hoijui/projvar/blob"
+        )
+        .is_err());
+        assert!(is_high(validate_repo_tree_url(
+            &mut environment,
+            &format!("https://codeberg.org/hoijui/projvar/src/commit/{}", sha)
+        )));
+        assert!(is_high(validate_repo_raw_url_base(
+            &mut environment,
+            "https://gitlab.com/hoijui/projvar/-/raw/v0.1.0"
+        )));
+        assert!(is_high(validate_repo_commit_url(
+            &mut environment,
+            &format!("https://bitbucket.org/hoijui/projvar/commits/{}", sha)
+        )));
+        assert!(validate_repo_commit_url(
+            &mut environment,
+            "https://github.com/hoijui/projvar/commit/v0.1.0"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate_repo_slug() {
+        let mut environment = Environment::stub();
+        assert!(is_high(validate_repo_slug(
+            &mut environment,
+            "OSEGermany/sub-group/osh-tool"
+        )));
+        assert!(validate_repo_slug(&mut environment, "osh-tool").is_err());
+        assert!(validate_repo_slug(&mut environment, "OSEGermany//osh-tool").is_err());
+        assert!(is_high(validate_repo_namespace(
+            &mut environment,
+            "OSEGermany/sub-group"
+        )));
+        assert!(is_high(validate_repo_owner(&mut environment, "OSEGermany")));
+        assert!(validate_repo_owner(&mut environment, "OSEGermany/sub-group").is_err());
+        assert!(validate_repo_owner(&mut environment, "").is_err());
+    }
+
+    #[test]
+    fn test_validate_container_image() {
+        let mut environment = Environment::stub();
+        assert!(is_high(validate_container_image_name(
+            &mut environment,
+            "ghcr.io/hoijui/projvar"
+        )));
+        assert!(is_high(validate_container_image_name(
+            &mut environment,
+            "registry.gitlab.com/osegermany/sub-group/osh-tool"
+        )));
+        assert!(is_high(validate_container_image_name(
+            &mut environment,
+            "localhost:5000/some_image"
+        )));
+        assert!(validate_container_image_name(&mut environment, "ghcr.io/hoijui/ProjVar").is_err());
+        assert!(validate_container_image_name(&mut environment, "projvar").is_err());
+        assert!(is_high(validate_container_image_tag(
+            &mut environment,
+            "1.2.3-rc.1-build.5"
+        )));
+        assert!(validate_container_image_tag(&mut environment, "1.2.3+build.5").is_err());
+        assert!(validate_container_image_tag(&mut environment, ".hidden").is_err());
+        assert!(validate_container_image_tag(&mut environment, &"a".repeat(129)).is_err());
+    }
+
+    #[test]
+    fn test_validate_forge_page_urls() {
+        let mut environment = Environment::stub();
+        assert!(is_high(validate_repo_releases_url(
+            &mut environment,
+            "https://gitlab.com/hoijui/some-group/projvar/-/releases"
+        )));
+        assert!(is_high(validate_repo_tag_url(
+            &mut environment,
+            "https://github.com/hoijui/projvar/releases/tag/v0.1.0"
+        )));
+        assert!(is_high(validate_repo_compare_url(
+            &mut environment,
+            "https://github.com/hoijui/projvar/compare/v0.1.0...v0.2.0"
+        )));
+        assert!(is_high(validate_repo_compare_url(
+            &mut environment,
+            "https://bitbucket.org/hoijui/projvar/branches/compare/v0.2.0%0Dv0.1.0"
+        )));
+        assert!(validate_repo_compare_url(
+            &mut environment,
+            "https://github.com/hoijui/projvar/compare"
+        )
+        .is_err());
+        assert!(is_high(validate_repo_new_merge_request_url(
+            &mut environment,
+            "https://gitlab.com/hoijui/projvar/-/merge_requests/new"
+        )));
+        assert!(is_high(validate_repo_wiki_url(
+            &mut environment,
+            "https://codeberg.org/hoijui/projvar/wiki"
+        )));
+        assert!(is_high(validate_repo_pipelines_url(
+            &mut environment,
+            "https://github.com/hoijui/projvar/actions"
+        )));
+        assert!(validate_repo_pipelines_url(
+            &mut environment,
+            "https://github.com/hoijui/projvar/pipelines"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate_gitea_urls() {
+        let mut environment = Environment::stub();
+        assert!(is_high(validate_repo_web_url(
+            &mut environment,
+            "https://codeberg.org/hoijui/projvar"
+        )));
+        assert!(is_high(validate_repo_clone_url(
+            &mut environment,
+            "https://codeberg.org/hoijui/projvar.git"
+        )));
+        assert!(is_high(validate_repo_raw_versioned_prefix_url(
+            &mut environment,
+            "https://codeberg.org/hoijui/projvar/raw/commit"
+        )));
+        assert!(is_high(validate_repo_versioned_file_prefix_url(
+            &mut environment,
+            "https://codeberg.org/hoijui/projvar/src/commit"
+        )));
+        assert!(is_high(validate_repo_versioned_dir_prefix_url(
+            &mut environment,
+            "https://codeberg.org/hoijui/projvar/src/commit"
+        )));
+        assert!(is_high(validate_repo_commit_prefix_url(
+            &mut environment,
+            "https://codeberg.org/hoijui/projvar/commit"
+        )));
+        assert!(is_high(validate_repo_issues_url(
+            &mut environment,
+            "https://codeberg.org/hoijui/projvar/issues"
+        )));
+        assert!(is_high(validate_build_hosting_url(
+            &mut environment,
+            "https://hoijui.codeberg.page/projvar"
+        )));
+    }
+
+    #[test]
+    fn test_validate_girocco_urls() {
+        let mut environment = Environment::stub();
+        assert!(is_high(validate_repo_web_url(
+            &mut environment,
+            "https://repo.or.cz/girocco.git"
+        )));
+        assert!(is_high(validate_repo_clone_url(
+            &mut environment,
+            "https://repo.or.cz/girocco.git"
+        )));
+        assert!(is_high(validate_repo_raw_versioned_prefix_url(
+            &mut environment,
+            "https://repo.or.cz/?p=girocco.git;a=blob_plain;hb="
+        )));
+        assert!(is_high(validate_repo_versioned_file_prefix_url(
+            &mut environment,
+            "https://repo.or.cz/?p=girocco.git;a=blob;hb="
+        )));
+        assert!(is_high(validate_repo_versioned_dir_prefix_url(
+            &mut environment,
+            "https://repo.or.cz/?p=girocco.git;a=tree;hb="
+        )));
+        assert!(is_high(validate_repo_commit_prefix_url(
+            &mut environment,
+            "https://repo.or.cz/?p=girocco.git;a=commit;h="
+        )));
+        // query arguments are only fine for gitweb
+        assert!(validate_repo_commit_prefix_url(
+            &mut environment,
+            "https://github.com/hoijui/projvar/commit?a=b"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate_allura_urls() {
+        let mut environment = Environment::stub();
+        assert!(is_high(validate_repo_web_url(
+            &mut environment,
+            "https://sourceforge.net/p/projvar/code/"
+        )));
+        assert!(is_high(validate_repo_clone_url(
+            &mut environment,
+            "https://git.code.sf.net/p/projvar/code"
+        )));
+        assert!(is_high(validate_repo_versioned_file_prefix_url(
+            &mut environment,
+            "https://sourceforge.net/p/projvar/code/ci"
+        )));
+        assert!(is_high(validate_repo_commit_prefix_url(
+            &mut environment,
+            "https://sourceforge.net/p/projvar/code/ci"
+        )));
+        assert!(is_high(validate_repo_issues_url(
+            &mut environment,
+            "https://sourceforge.net/p/projvar/tickets"
+        )));
+        assert!(is_high(validate_build_hosting_url(
+            &mut environment,
+            "https://projvar.sourceforge.io/"
+        )));
+    }
+
+    #[test]
+    fn test_validate_azure_dev_ops_urls() {
+        let mut environment = Environment::stub();
+        assert!(is_high(validate_repo_web_url(
+            &mut environment,
+            "https://dev.azure.com/hoijui/projvar/_git/projvar"
+        )));
+        assert!(is_high(validate_repo_clone_url(
+            &mut environment,
+            "https://dev.azure.com/hoijui/projvar/_git/projvar"
+        )));
+        assert!(is_high(validate_repo_clone_url_ssh(
+            &mut environment,
+            "git@ssh.dev.azure.com:v3/hoijui/projvar/projvar"
+        )));
+        assert!(is_high(validate_repo_raw_versioned_prefix_url(
+            &mut environment,
+            "https://dev.azure.com/hoijui/projvar/_apis/git/repositories/projvar/items?versionDescriptor.versionType=commit&versionDescriptor.version="
+        )));
+        assert!(is_high(validate_repo_versioned_file_prefix_url(
+            &mut environment,
+            "https://dev.azure.com/hoijui/projvar/_git/projvar?version=GC"
+        )));
+        assert!(is_high(validate_repo_versioned_dir_prefix_url(
+            &mut environment,
+            "https://dev.azure.com/hoijui/projvar/_git/projvar?version=GC"
+        )));
+        assert!(is_high(validate_repo_commit_prefix_url(
+            &mut environment,
+            "https://dev.azure.com/hoijui/projvar/_git/projvar/commit"
+        )));
+        assert!(is_high(validate_repo_issues_url(
+            &mut environment,
+            "https://dev.azure.com/hoijui/projvar/_workitems"
+        )));
+    }
+
+    #[test]
+    fn test_validate_code_commit_urls() {
+        let mut environment = Environment::stub();
+        assert!(is_high(validate_repo_web_url(
+            &mut environment,
+            "https://eu-west-1.console.aws.amazon.com/codesuite/codecommit/repositories/projvar/browse"
+        )));
+        assert!(is_high(validate_repo_clone_url(
+            &mut environment,
+            "https://git-codecommit.eu-west-1.amazonaws.com/v1/repos/projvar"
+        )));
+        assert!(is_high(validate_repo_clone_url_ssh(
+            &mut environment,
+            "ssh://git-codecommit.eu-west-1.amazonaws.com/v1/repos/projvar"
+        )));
+        assert!(is_high(validate_repo_versioned_file_prefix_url(
+            &mut environment,
+            "https://eu-west-1.console.aws.amazon.com/codesuite/codecommit/repositories/projvar/browse"
+        )));
+        assert!(is_high(validate_repo_commit_prefix_url(
+            &mut environment,
+            "https://eu-west-1.console.aws.amazon.com/codesuite/codecommit/repositories/projvar/commit"
+        )));
+    }
 }
@@ -0,0 +1,316 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Typed property values.
+//! Sources supply plain strings
+//! (dates in RFC 3339 format, lists of licenses separated by ','),
+//! which get parsed into the type associated with the key (see [`kind`])
+//! before they are stored.
+//! Each sink then renders them as appropriate for its format.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset};
+use serde_json::json;
+use strum_macros::IntoStaticStr;
+use url::Url;
+
+use crate::custom_keys::{self, Builtin, Check};
+use crate::date::Timezone;
+use crate::environment::Environment;
+use crate::settings::Settings;
+use crate::var::Key;
+
+/// The type of the values of a key.
+#[derive(Debug, IntoStaticStr, PartialEq, Eq, Clone, Copy)]
+pub enum Kind {
+    Bool,
+    Date,
+    Licenses,
+    Text,
+    Url,
+    Version,
+}
+
+/// A typed property value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Bool(bool),
+    /// A point in time, including the timezone it was recorded in
+    Date(DateTime<FixedOffset>),
+    /// SPDX license expressions, which all apply;
+    /// rendered as a single SPDX expression, joining them with "AND"
+    Licenses(Vec<String>),
+    /// Any other list of texts, rendered separated by ", "
+    List(Vec<String>),
+    /// Any value that does not fit one of the other types,
+    /// including those that failed to parse as their keys [`Kind`]
+    Text(String),
+    Url(Url),
+    Version(semver::Version),
+}
+
+/// Returns the type of the values of `key`.
+#[remain::check]
+#[must_use]
+pub fn kind(key: Key) -> Kind {
+    #[remain::sorted]
+    match key {
+        Key::AuthorCount
+        | Key::BuildArch
+        | Key::BuildBranch
+        | Key::BuildNumber
+        | Key::BuildOs
+        | Key::BuildOsFamily
+        | Key::BuildTag
+        | Key::CommitCount
+        | Key::CommitSha
+        | Key::CommitShaShort
+        | Key::CommitsSinceTag
+        | Key::ContainerImageName
+        | Key::ContainerImageTag
+        | Key::License
+        | Key::Name
+        | Key::NameMachineReadable
+        | Key::PreviousTag
+        | Key::RepoCloneUrlSsh
+        | Key::RepoNamespace
+        | Key::RepoOwner
+        | Key::RepoSlug
+        | Key::SourceDateEpoch
+        | Key::VersionBump
+        | Key::VersionDebian
+        | Key::VersionMajor
+        | Key::VersionMinor
+        | Key::VersionPatch
+        | Key::VersionPep440
+        | Key::VersionPrerelease => Kind::Text,
+        Key::BuildDate | Key::FirstCommitDate | Key::VersionDate => Kind::Date,
+        Key::BuildHostingUrl
+        | Key::RepoCloneUrl
+        | Key::RepoCommitPrefixUrl
+        | Key::RepoCommitUrl
+        | Key::RepoCompareUrl
+        | Key::RepoIssuesUrl
+        | Key::RepoNewMergeRequestUrl
+        | Key::RepoPipelinesUrl
+        | Key::RepoRawUrlBase
+        | Key::RepoRawVersionedPrefixUrl
+        | Key::RepoReleasesUrl
+        | Key::RepoTagUrl
+        | Key::RepoTreeUrl
+        | Key::RepoVersionedDirPrefixUrl
+        | Key::RepoVersionedFilePrefixUrl
+        | Key::RepoVersionedFileUrlBase
+        | Key::RepoWebUrl
+        | Key::RepoWikiUrl => Kind::Url,
+        Key::Ci | Key::Dirty | Key::IsRelease => Kind::Bool,
+        Key::Custom(id) => match custom_keys::get(id).check {
+            Some(Check::Builtin(Builtin::Bool)) => Kind::Bool,
+            Some(Check::Builtin(Builtin::Date)) => Kind::Date,
+            Some(Check::Builtin(Builtin::Semver)) => Kind::Version,
+            Some(Check::Builtin(Builtin::Url)) => Kind::Url,
+            Some(Check::Builtin(Builtin::Integer) | Check::Regex(_)) | None => Kind::Text,
+        },
+        Key::Licenses => Kind::Licenses,
+        Key::Version | Key::VersionNext => Kind::Version,
+    }
+}
+
+impl Value {
+    /// Parses a value supplied by a source into the type of `key`.
+    /// Dates are expected in RFC 3339 format,
+    /// or in the date format of the key.
+    /// If that fails, the value is kept as [`Value::Text`],
+    /// and it is up to the validator to judge it.
+    #[must_use]
+    pub fn parse(environment: &Environment, key: Key, value: String) -> Value {
+        let settings = &environment.settings;
+        let parsed = match kind(key) {
+            Kind::Bool => match value.as_str() {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            Kind::Date => settings
+                .date_format(key)
+                .parse(&value, settings.timezone)
+                .map(Value::Date),
+            Kind::Licenses => Some(Value::Licenses(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect(),
+            )),
+            Kind::Text => None,
+            Kind::Url => Url::parse(&value).ok().map(Value::Url),
+            Kind::Version => semver::Version::parse(&value).ok().map(Value::Version),
+        };
+        parsed.unwrap_or(Value::Text(value))
+    }
+
+    /// Renders the value of `key` as text,
+    /// with dates in the format and timezone from the settings.
+    /// This is what the text based sinks use.
+    #[must_use]
+    pub fn render(&self, settings: &Settings, key: Key) -> String {
+        match self {
+            Value::Date(date) => settings.date_format(key).format(date, settings.timezone),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Renders the value as JSON,
+    /// with lists as arrays and dates in RFC 3339 format,
+    /// converted into the given timezone.
+    #[must_use]
+    pub fn to_json(&self, timezone: Timezone) -> serde_json::Value {
+        match self {
+            Value::Bool(value) => json!(value),
+            Value::Date(date) => json!(timezone.apply(date).to_rfc3339()),
+            Value::Licenses(items) | Value::List(items) => json!(items),
+            _ => json!(self.to_string()),
+        }
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+/// The canonical text representation,
+/// with dates in RFC 3339 format.
+impl fmt::Display for Value {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(value) => write!(formatter, "{}", value),
+            Value::Date(date) => write!(formatter, "{}", date.to_rfc3339()),
+            Value::Licenses(expressions) => {
+                let expressions: Vec<Cow<str>> = expressions
+                    .iter()
+                    .map(|expression| {
+                        // "AND" binds stronger than "OR"
+                        if expression.contains(" OR ") {
+                            Cow::Owned(format!("({})", expression))
+                        } else {
+                            Cow::Borrowed(expression.as_str())
+                        }
+                    })
+                    .collect();
+                write!(formatter, "{}", expressions.join(" AND "))
+            }
+            Value::List(items) => write!(formatter, "{}", items.join(", ")),
+            Value::Text(text) => write!(formatter, "{}", text),
+            Value::Url(url) => {
+                // `Url` adds a '/' to URLs without path,
+                // which we do not want for URLs like "https://example.org".
+                if url.path() == "/" && url.query().is_none() && url.fragment().is_none() {
+                    write!(formatter, "{}", url.as_str().trim_end_matches('/'))
+                } else {
+                    write!(formatter, "{}", url)
+                }
+            }
+            Value::Version(version) => write!(formatter, "{}", version),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::date::{Format, Preset};
+
+    #[test]
+    fn test_parse() {
+        let environment = Environment::stub();
+        assert_eq!(
+            Value::parse(&environment, Key::Ci, "true".to_owned()),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            Value::parse(&environment, Key::Ci, "yes".to_owned()),
+            Value::Text("yes".to_owned())
+        );
+        assert_eq!(
+            Value::parse(&environment, Key::Licenses, "MIT, CC0-1.0,".to_owned()),
+            Value::Licenses(vec!["MIT".to_owned(), "CC0-1.0".to_owned()])
+        );
+        assert!(matches!(
+            Value::parse(&environment, Key::Version, "1.2.3-rc.1".to_owned()),
+            Value::Version(_)
+        ));
+        assert_eq!(
+            Value::parse(&environment, Key::Version, "v1.2".to_owned()),
+            Value::Text("v1.2".to_owned())
+        );
+        assert!(matches!(
+            Value::parse(
+                &environment,
+                Key::VersionDate,
+                "2021-12-31T23:59:59+02:00".to_owned()
+            ),
+            Value::Date(_)
+        ));
+    }
+
+    #[test]
+    fn test_render() {
+        let mut environment = Environment::stub();
+        environment.settings.date_format = Format::Custom("%Y-%m-%d %H:%M:%S %z".to_owned());
+        environment
+            .settings
+            .key_date_formats
+            .insert(Key::BuildDate, Format::Preset(Preset::Epoch));
+        let date = Value::parse(
+            &environment,
+            Key::VersionDate,
+            "2021-12-31T23:59:59+02:00".to_owned(),
+        );
+        assert_eq!(
+            date.render(&environment.settings, Key::VersionDate),
+            "2021-12-31 23:59:59 +0200"
+        );
+        assert_eq!(
+            date.render(&environment.settings, Key::BuildDate),
+            "1640987999"
+        );
+        assert_eq!(
+            date.to_json(Timezone::Commit),
+            json!("2021-12-31T23:59:59+02:00")
+        );
+        environment.settings.timezone = Timezone::Utc;
+        assert_eq!(
+            date.render(&environment.settings, Key::VersionDate),
+            "2021-12-31 21:59:59 +0000"
+        );
+        assert_eq!(
+            date.to_json(Timezone::Utc),
+            json!("2021-12-31T21:59:59+00:00")
+        );
+        let url = Value::parse(
+            &environment,
+            Key::RepoWebUrl,
+            "https://example.org".to_owned(),
+        );
+        assert_eq!(url.to_string(), "https://example.org");
+        let licenses = Value::Licenses(vec!["MIT".to_owned(), "CC0-1.0".to_owned()]);
+        assert_eq!(licenses.to_string(), "MIT AND CC0-1.0");
+        assert_eq!(
+            licenses.to_json(Timezone::Commit),
+            json!(["MIT", "CC0-1.0"])
+        );
+        let licenses = Value::Licenses(vec![
+            "GPL-3.0-only".to_owned(),
+            "MIT OR Apache-2.0".to_owned(),
+        ]);
+        assert_eq!(licenses.to_string(), "GPL-3.0-only AND (MIT OR Apache-2.0)");
+        let list = Value::List(vec!["2021 Jane Doe".to_owned(), "2022 John Doe".to_owned()]);
+        assert_eq!(list.to_string(), "2021 Jane Doe, 2022 John Doe");
+    }
+}
@@ -2,13 +2,13 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-use enum_map::Enum;
+use enum_map::{Enum, EnumArray};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
-    fmt::Display,
+    fmt::{self, Display},
     io::BufRead,
     iter::Iterator,
 };
@@ -17,7 +17,7 @@ use strum_macros::{EnumCount, EnumIter, EnumString, IntoStaticStr};
 
 use std::str::FromStr;
 
-use crate::{environment::Environment, BoxResult};
+use crate::{custom_keys, environment::Environment, BoxResult};
 
 pub type Confidence = u8;
 pub const C_HIGH: Confidence = 75;
@@ -25,8 +25,7 @@ pub const C_MIDDLE: Confidence = 50;
 pub const C_LOW: Confidence = 25;
 
 // #[derive(Clone)]
-// #[derive(Debug)]
-#[derive(Default)]
+#[derive(Default, Debug)]
 pub struct Variable {
     key: &'static str,
     pub description: &'static str,
@@ -35,6 +34,21 @@ pub struct Variable {
 }
 
 impl Variable {
+    /// Creates the meta-data for a variable that is not built-in,
+    /// see [`crate::custom_keys`].
+    #[must_use]
+    pub(crate) fn new(
+        key: &'static str,
+        description: &'static str,
+        default_required: bool,
+    ) -> Variable {
+        Variable {
+            key,
+            description,
+            default_required,
+        }
+    }
+
     #[must_use]
     pub fn key(&self, environment: &Environment) -> Cow<str> {
         match &environment.settings.key_prefix {
@@ -56,6 +70,8 @@ impl Display for Variable {
 
 lazy_static! {
     static ref D_VARIABLE: Variable = Variable::default();
+    /// All the built-in keys, in order of declaration
+    static ref BUILT_IN_KEYS: Vec<Key> = Key::iter().collect();
 }
 
 impl<'a> Default for &'a Variable {
@@ -68,13 +84,11 @@ impl<'a> Default for &'a Variable {
 // #[derive(Debug, EnumString, EnumIter, IntoStaticStr, PartialEq, Eq, Hash, Copy, Clone, Enum)]
 // #[derive(Debug, EnumString, EnumIter, IntoStaticStr, Hash, Enum, EnumSetType)]
 #[derive(
-    Debug,
     EnumCount,
     EnumString,
     EnumIter,
     IntoStaticStr,
     Hash,
-    Enum,
     PartialEq,
     Eq,
     PartialOrd,
@@ -83,6 +97,7 @@ impl<'a> Default for &'a Variable {
     Copy,
 )]
 pub enum Key {
+    AuthorCount,
     BuildArch,
     BuildBranch,
     BuildDate,
@@ -93,22 +108,75 @@ pub enum Key {
     BuildOsFamily,
     BuildTag,
     Ci,
+    CommitCount,
+    CommitSha,
+    CommitShaShort,
+    CommitsSinceTag,
+    ContainerImageName,
+    ContainerImageTag,
+    /// A key defined by the user in the configuration,
+    /// see [`crate::custom_keys`].
+    #[strum(disabled)]
+    Custom(custom_keys::Id),
+    Dirty,
+    FirstCommitDate,
+    IsRelease,
     License,
     Licenses,
     Name,
     NameMachineReadable,
+    PreviousTag,
     RepoCloneUrl,
     RepoCloneUrlSsh,
     RepoCommitPrefixUrl,
+    RepoCommitUrl,
+    RepoCompareUrl,
     RepoIssuesUrl,
+    RepoNamespace,
+    RepoNewMergeRequestUrl,
+    RepoOwner,
+    RepoPipelinesUrl,
+    RepoRawUrlBase,
     RepoRawVersionedPrefixUrl,
+    RepoReleasesUrl,
+    RepoSlug,
+    RepoTagUrl,
+    RepoTreeUrl,
     RepoVersionedDirPrefixUrl,
     RepoVersionedFilePrefixUrl,
+    RepoVersionedFileUrlBase,
     RepoWebUrl,
+    RepoWikiUrl,
+    SourceDateEpoch,
     Version,
+    VersionBump,
     VersionDate,
+    VersionDebian,
+    VersionMajor,
+    VersionMinor,
+    VersionNext,
+    VersionPatch,
+    VersionPep440,
+    VersionPrerelease,
 }
 
+/// Keys whose values are inherently specific to the host we are building on.
+/// These are omitted in reproducible mode,
+/// unless they are explicitly required.
+pub const HOST_SPECIFIC_KEYS: [Key; 3] = [Key::BuildArch, Key::BuildOs, Key::BuildOsFamily];
+
+/// Keys whose values require walking the whole history of the repo,
+/// which may take a while for big ones.
+/// These are only fetched if they are required.
+pub const HISTORY_KEYS: [Key; 6] = [
+    Key::AuthorCount,
+    Key::CommitCount,
+    Key::FirstCommitDate,
+    Key::PreviousTag,
+    Key::VersionBump,
+    Key::VersionNext,
+];
+
 /// Converts a `"CamelCase"` string into an `"UPPER_SNAKE_CASE"` one.
 ///
 /// for example:
@@ -190,16 +258,83 @@ impl Key {
     /// Tries to create a `Key` from a string identifier.
     /// This might be the exact name of the `Key` (like "Name"),
     /// or the associated variable key (like `"PROJECT_NAME"`).
+    /// Custom keys (see [`crate::custom_keys`]) are considered too.
     ///
     /// # Errors
     ///
     /// If the given identifier could not be mapped to any `Key` variant.
     pub fn from_name_or_var_key(key_prefix: &Regex, id: &str) -> BoxResult<Key> {
-        Ok(Self::from_str(id).or_else(|_| {
-            Self::from_str(&upper_snake_to_camel_case(
-                key_prefix.replace(id, "").as_ref(),
-            ))
-        })?)
+        if let Ok(key) = Self::from_name(id) {
+            return Ok(key);
+        }
+        Self::from_name(&upper_snake_to_camel_case(
+            key_prefix.replace(id, "").as_ref(),
+        ))
+    }
+
+    /// Tries to create a `Key` from its exact name (like "Name"),
+    /// considering custom keys too.
+    ///
+    /// # Errors
+    ///
+    /// If the given name could not be mapped to any `Key`.
+    pub fn from_name(name: &str) -> BoxResult<Key> {
+        Ok(Self::from_str(name).or_else(|err| custom_keys::find(name).ok_or(err))?)
+    }
+
+    /// The name of this key, for example "Name" or "BuildArch".
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Key::Custom(id) => custom_keys::get(id).name,
+            _ => self.into(),
+        }
+    }
+
+    /// Iterates over all the built-in keys, followed by the custom ones
+    /// (see [`crate::custom_keys`]).
+    pub fn iter_all() -> impl Iterator<Item = Key> {
+        Key::iter().chain(custom_keys::keys())
+    }
+}
+
+/// Built-in keys are indexed by their position in the enum,
+/// custom keys (see [`crate::custom_keys`]) come after them.
+/// This can not be derived, because of [`Key::Custom`].
+impl Enum for Key {
+    // `COUNT` includes the `Custom` variant itself
+    const LENGTH: usize = <Key as EnumCount>::COUNT - 1 + (1 << custom_keys::Id::BITS);
+
+    fn from_usize(value: usize) -> Self {
+        let num_built_in = <Key as EnumCount>::COUNT - 1;
+        if value < num_built_in {
+            BUILT_IN_KEYS[value]
+        } else {
+            Key::Custom(
+                custom_keys::Id::try_from(value - num_built_in)
+                    .expect("Index out of range for custom keys"),
+            )
+        }
+    }
+
+    fn into_usize(self) -> usize {
+        match self {
+            Key::Custom(id) => <Key as EnumCount>::COUNT - 1 + usize::from(id),
+            // The derived order is the order of declaration
+            _ => BUILT_IN_KEYS
+                .binary_search(&self)
+                .expect("All built-in keys are listed"),
+        }
+    }
+}
+
+impl<V> EnumArray<V> for Key {
+    type Array = [V; <Key as Enum>::LENGTH];
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.name())
     }
 }
 
@@ -295,7 +430,7 @@ pub fn list_keys(environment: &Environment) -> String {
     table.push_str("\n\n");
     table.push_str(HEADER);
     table.push_str(HEADER_SEP);
-    for key in Key::iter() {
+    for key in Key::iter_all() {
         let var = get(key);
         let def = if var.default_required { "[x]" } else { "[ ]" };
         table.push_str(&format!(
@@ -313,6 +448,7 @@ pub fn list_keys(environment: &Environment) -> String {
     table
 }
 
+pub const KEY_AUTHOR_COUNT: &str = "AUTHOR_COUNT";
 pub const KEY_BUILD_ARCH: &str = "BUILD_ARCH";
 pub const KEY_BUILD_BRANCH: &str = "BUILD_BRANCH";
 pub const KEY_BUILD_DATE: &str = "BUILD_DATE";
@@ -322,21 +458,58 @@ pub const KEY_BUILD_OS: &str = "BUILD_OS";
 pub const KEY_BUILD_OS_FAMILY: &str = "BUILD_OS_FAMILY";
 pub const KEY_BUILD_TAG: &str = "BUILD_TAG";
 pub const KEY_CI: &str = "CI";
+pub const KEY_COMMITS_SINCE_TAG: &str = "COMMITS_SINCE_TAG";
+pub const KEY_COMMIT_COUNT: &str = "COMMIT_COUNT";
+pub const KEY_COMMIT_SHA: &str = "COMMIT_SHA";
+pub const KEY_COMMIT_SHA_SHORT: &str = "COMMIT_SHA_SHORT";
+pub const KEY_CONTAINER_IMAGE_NAME: &str = "CONTAINER_IMAGE_NAME";
+pub const KEY_CONTAINER_IMAGE_TAG: &str = "CONTAINER_IMAGE_TAG";
+pub const KEY_DIRTY: &str = "DIRTY";
+pub const KEY_FIRST_COMMIT_DATE: &str = "FIRST_COMMIT_DATE";
+pub const KEY_IS_RELEASE: &str = "IS_RELEASE";
 pub const KEY_LICENSE: &str = "LICENSE";
 pub const KEY_LICENSES: &str = "LICENSES";
 pub const KEY_NAME: &str = "NAME";
 pub const KEY_NAME_MACHINE_READABLE: &str = "NAME_MACHINE_READABLE";
+pub const KEY_PREVIOUS_TAG: &str = "PREVIOUS_TAG";
 pub const KEY_REPO_CLONE_URL: &str = "REPO_CLONE_URL";
 pub const KEY_REPO_CLONE_URL_SSH: &str = "REPO_CLONE_URL_SSH";
 pub const KEY_REPO_COMMIT_PREFIX_URL: &str = "REPO_COMMIT_PREFIX_URL";
+pub const KEY_REPO_COMMIT_URL: &str = "REPO_COMMIT_URL";
+pub const KEY_REPO_COMPARE_URL: &str = "REPO_COMPARE_URL";
 pub const KEY_REPO_ISSUES_URL: &str = "REPO_ISSUES_URL";
+pub const KEY_REPO_NAMESPACE: &str = "REPO_NAMESPACE";
+pub const KEY_REPO_NEW_MERGE_REQUEST_URL: &str = "REPO_NEW_MERGE_REQUEST_URL";
+pub const KEY_REPO_OWNER: &str = "REPO_OWNER";
+pub const KEY_REPO_PIPELINES_URL: &str = "REPO_PIPELINES_URL";
+pub const KEY_REPO_RAW_URL_BASE: &str = "REPO_RAW_URL_BASE";
 pub const KEY_REPO_RAW_VERSIONED_PREFIX_URL: &str = "REPO_RAW_VERSIONED_PREFIX_URL";
+pub const KEY_REPO_RELEASES_URL: &str = "REPO_RELEASES_URL";
+pub const KEY_REPO_SLUG: &str = "REPO_SLUG";
+pub const KEY_REPO_TAG_URL: &str = "REPO_TAG_URL";
+pub const KEY_REPO_TREE_URL: &str = "REPO_TREE_URL";
 pub const KEY_REPO_VERSIONED_DIR_PREFIX_URL: &str = "REPO_VERSIONED_DIR_PREFIX_URL";
 pub const KEY_REPO_VERSIONED_FILE_PREFIX_URL: &str = "REPO_VERSIONED_FILE_PREFIX_URL";
+pub const KEY_REPO_VERSIONED_FILE_URL_BASE: &str = "REPO_VERSIONED_FILE_URL_BASE";
 pub const KEY_REPO_WEB_URL: &str = "REPO_WEB_URL";
+pub const KEY_REPO_WIKI_URL: &str = "REPO_WIKI_URL";
+pub const KEY_SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
 pub const KEY_VERSION: &str = "VERSION";
+pub const KEY_VERSION_BUMP: &str = "VERSION_BUMP";
 pub const KEY_VERSION_DATE: &str = "VERSION_DATE";
-
+pub const KEY_VERSION_DEBIAN: &str = "VERSION_DEBIAN";
+pub const KEY_VERSION_MAJOR: &str = "VERSION_MAJOR";
+pub const KEY_VERSION_MINOR: &str = "VERSION_MINOR";
+pub const KEY_VERSION_NEXT: &str = "VERSION_NEXT";
+pub const KEY_VERSION_PATCH: &str = "VERSION_PATCH";
+pub const KEY_VERSION_PEP440: &str = "VERSION_PEP440";
+pub const KEY_VERSION_PRERELEASE: &str = "VERSION_PRERELEASE";
+
+const VAR_AUTHOR_COUNT: Variable = Variable {
+    key: KEY_AUTHOR_COUNT,
+    description: r#"The number of unique authors (by e-mail address) of the commits reachable from HEAD, for example: "7""#,
+    default_required: false,
+};
 const VAR_BUILD_ARCH: Variable = Variable {
     key: KEY_BUILD_ARCH,
     description:
@@ -385,6 +558,52 @@ const VAR_CI: Variable = Variable {
     description: "'true' if running on a CI/build-bot; unset otherwise.",
     default_required: false,
 };
+const VAR_COMMITS_SINCE_TAG: Variable = Variable {
+    key: KEY_COMMITS_SINCE_TAG,
+    description: r#"The number of commits since the last tag, for example: "0" if the commit is tagged, "12" if there were twelve commits after it"#,
+    default_required: false,
+};
+const VAR_COMMIT_COUNT: Variable = Variable {
+    key: KEY_COMMIT_COUNT,
+    description: r#"The number of commits reachable from HEAD, including itself, for example: "1234""#,
+    default_required: false,
+};
+const VAR_COMMIT_SHA: Variable = Variable {
+    key: KEY_COMMIT_SHA,
+    description: r#"The full SHA of the commit being built, for example: "cf73ea34fcc785b1ac44ffb20d655c917e77c83d""#,
+    default_required: false,
+};
+const VAR_COMMIT_SHA_SHORT: Variable = Variable {
+    key: KEY_COMMIT_SHA_SHORT,
+    description: r#"The abbreviated SHA of the commit being built, for example: "cf73ea3""#,
+    default_required: false,
+};
+const VAR_CONTAINER_IMAGE_NAME: Variable = Variable {
+    key: KEY_CONTAINER_IMAGE_NAME,
+    description: "The name of the container image of the project, as published to the container registry of the hosting platform, in lower-case, for example: ghcr.io/hoijui/projvar",
+    default_required: false,
+};
+const VAR_CONTAINER_IMAGE_TAG: Variable = Variable {
+    key: KEY_CONTAINER_IMAGE_TAG,
+    description: "The container image tag, derived from the version, or else the branch, with all characters not allowed in a tag replaced by '-', for example: 1.2.3-rc.1-build.5",
+    default_required: false,
+};
+const VAR_DIRTY: Variable = Variable {
+    key: KEY_DIRTY,
+    description:
+        "'true' if there are uncommitted changes to tracked files in the repo, 'false' otherwise",
+    default_required: false,
+};
+const VAR_FIRST_COMMIT_DATE: Variable = Variable {
+    key: KEY_FIRST_COMMIT_DATE,
+    description: r#"Date of the first commit in the history of HEAD, marking the start of the project, for example: "2019-05-01 12:00:00" (see --date-format)"#,
+    default_required: false,
+};
+const VAR_IS_RELEASE: Variable = Variable {
+    key: KEY_IS_RELEASE,
+    description: r#"Whether the version is a release version, without pre-release part: "true" or "false""#,
+    default_required: false,
+};
 const VAR_LICENSE: Variable = Variable {
     key: KEY_LICENSE,
     description: r#"The main License identifier of the sources, prefferably from the SPDX specs, for example: "AGPL-3.0-or-later", "CC-BY-SA-4.0""#,
@@ -392,7 +611,7 @@ const VAR_LICENSE: Variable = Variable {
 };
 const VAR_LICENSES: Variable = Variable {
     key: KEY_LICENSES,
-    description: r#"The identifiers of all the licenses of this project, prefferably from the SPDX specs, comma separated, for example: "AGPL-3.0-or-later, CC0-1.0, Unlicense""#,
+    description: r#"The identifiers of all the licenses of this project, prefferably from the SPDX specs, for example: "AGPL-3.0-or-later AND CC0-1.0 AND Unlicense" (sources supply them comma separated; JSON output lists them as an array)"#,
     default_required: true,
 };
 const VAR_NAME: Variable = Variable {
@@ -405,6 +624,11 @@ const VAR_NAME_MACHINE_READABLE: Variable = Variable {
     description: "The machine readable name of the project.",
     default_required: true,
 };
+const VAR_PREVIOUS_TAG: Variable = Variable {
+    key: KEY_PREVIOUS_TAG,
+    description: r#"The last release tag before the commit being built (see --release-tag-pattern), for example: "v1.2.3""#,
+    default_required: false,
+};
 const VAR_REPO_CLONE_URL: Variable = Variable {
     key: KEY_REPO_CLONE_URL,
     description: "The repo clone URL, HTTP(S) version. This is commonly used for anonymous fetch-only access.",
@@ -417,44 +641,155 @@ const VAR_REPO_CLONE_URL_SSH: Variable = Variable {
 };
 const VAR_REPO_COMMIT_PREFIX_URL: Variable = Variable {
     key: KEY_REPO_COMMIT_PREFIX_URL,
-    description: "The repo commit prefix URL. Add commit SHA. The part in []: [https://github.com/hoijui/nim-ci/commit]/23f84b91]",
+    description: r#"The repo commit prefix URL. Add commit SHA. The part in []: [https://github.com/hoijui/nim-ci/commit]/23f84b91] (on Girocco, the SHA follows without a "/": [https://repo.or.cz/?p=girocco.git;a=commit;h=]23f84b91)"#,
     default_required: true,
 };
+const VAR_REPO_COMMIT_URL: Variable = Variable {
+    key: KEY_REPO_COMMIT_URL,
+    description: "The web URL of the commit being built. For example: [https://github.com/hoijui/nim-ci/commit/ae4a42a850b359a23da2483eb8f867f21c5382d4]",
+    default_required: false,
+};
+const VAR_REPO_COMPARE_URL: Variable = Variable {
+    key: KEY_REPO_COMPARE_URL,
+    description: "The web URL comparing the previous release tag with the tag (or else the commit) being built, for example: https://github.com/hoijui/projvar/compare/0.10.0...0.11.0 (derived from PreviousTag, which walks the history, so this is only evaluated if either of them is required)",
+    default_required: false,
+};
 const VAR_REPO_ISSUES_URL: Variable = Variable {
     key: KEY_REPO_ISSUES_URL,
     description: "The repo issues URL, for example: https://gitlab.com/openflexure/openflexure-microscope/issues",
     default_required: true,
 };
+const VAR_REPO_NAMESPACE: Variable = Variable {
+    key: KEY_REPO_NAMESPACE,
+    description: "The namespace of the repo on its hosting platform, which is the slug without the project name, and may have several levels on GitLab, for example: hoijui or OSEGermany/sub-group",
+    default_required: false,
+};
+const VAR_REPO_NEW_MERGE_REQUEST_URL: Variable = Variable {
+    key: KEY_REPO_NEW_MERGE_REQUEST_URL,
+    description: "The web URL for creating a new merge request (AKA pull request), for example: https://gitlab.com/OSEGermany/osh-tool/-/merge_requests/new",
+    default_required: false,
+};
+const VAR_REPO_OWNER: Variable = Variable {
+    key: KEY_REPO_OWNER,
+    description: "The owner of the repo on its hosting platform, which is the user or top-level group, for example: hoijui",
+    default_required: false,
+};
+const VAR_REPO_PIPELINES_URL: Variable = Variable {
+    key: KEY_REPO_PIPELINES_URL,
+    description: "The web URL of the CI pipelines overview of the repo, for example: https://github.com/hoijui/projvar/actions",
+    default_required: false,
+};
+const VAR_REPO_RAW_URL_BASE: Variable = Variable {
+    key: KEY_REPO_RAW_URL_BASE,
+    description: "The repo raw prefix URL, pinned to the tag (if any) or else the commit being built. Add the file path. The part in []: [https://raw.githubusercontent.com/hoijui/nim-ci/v0.1.0]/.github/workflows/docker.yml (not available for Allura, which expects ?format=raw after the file path)",
+    default_required: false,
+};
 const VAR_REPO_RAW_VERSIONED_PREFIX_URL: Variable = Variable {
     key: KEY_REPO_RAW_VERSIONED_PREFIX_URL,
-    description: "The repo raw prefix URL. Add version (tag, branch, SHA) and file path. The part in []: [https://raw.githubusercontent.com/hoijui/nim-ci]/master/.github/workflows/docker.yml]",
+    description: r#"The repo raw prefix URL. Add version (tag, branch, SHA) and file path. The part in []: [https://raw.githubusercontent.com/hoijui/nim-ci]/master/.github/workflows/docker.yml] (some hosting types need them added in their own way, for example Allura: [https://sourceforge.net/p/sevenzip/code/ci]/master/tree/README.md?format=raw, Girocco: [https://repo.or.cz/?p=girocco.git;a=blob_plain;hb=]master;f=README.md or Azure DevOps: [...&versionDescriptor.version=]23f84b91&path=/README.md)"#,
     default_required: true,
 };
+const VAR_REPO_RELEASES_URL: Variable = Variable {
+    key: KEY_REPO_RELEASES_URL,
+    description:
+        "The repo releases URL, for example: https://gitlab.com/OSEGermany/osh-tool/-/releases",
+    default_required: false,
+};
+const VAR_REPO_SLUG: Variable = Variable {
+    key: KEY_REPO_SLUG,
+    description: "The path of the repo on its hosting platform, for example: hoijui/projvar or OSEGermany/sub-group/osh-tool",
+    default_required: false,
+};
+const VAR_REPO_TAG_URL: Variable = Variable {
+    key: KEY_REPO_TAG_URL,
+    description: "The web URL of the tag being built, for example: https://github.com/hoijui/projvar/releases/tag/0.11.0",
+    default_required: false,
+};
+const VAR_REPO_TREE_URL: Variable = Variable {
+    key: KEY_REPO_TREE_URL,
+    description: "The web URL of the repo root directory, pinned to the tag (if any) or else the commit being built. For example: [https://github.com/hoijui/nim-ci/tree/ae4a42a850b359a23da2483eb8f867f21c5382d4]",
+    default_required: false,
+};
 const VAR_REPO_VERSIONED_DIR_PREFIX_URL: Variable = Variable {
     key: KEY_REPO_VERSIONED_DIR_PREFIX_URL,
-    description: "The repo directory prefix URL. Add version (tag, branch, SHA) and directory path. The part in []: [https://github.com/hoijui/nim-ci]/master/.github/workflows/docker.yml]",
+    description: r#"The repo directory prefix URL. Add version (tag, branch, SHA) and directory path. The part in []: [https://github.com/hoijui/nim-ci]/master/.github/workflows/docker.yml] (some hosting types need them added in their own way, for example Allura: [https://sourceforge.net/p/sevenzip/code/ci]/master/tree/doc, Girocco: [https://repo.or.cz/?p=girocco.git;a=tree;hb=]master;f=doc or AWS CodeCommit: [.../repositories/projvar/browse]/master/--/doc)"#,
     default_required: true,
 };
 const VAR_REPO_VERSIONED_FILE_PREFIX_URL: Variable = Variable {
     key: KEY_REPO_VERSIONED_FILE_PREFIX_URL,
-    description: "The repo file prefix URL. Add version (tag, branch, SHA) and file path. The part in []: [https://github.com/hoijui/nim-ci]/master/.github/workflows/docker.yml]",
+    description: r#"The repo file prefix URL. Add version (tag, branch, SHA) and file path. The part in []: [https://github.com/hoijui/nim-ci]/master/.github/workflows/docker.yml] (some hosting types need them added in their own way, for example Allura: [https://sourceforge.net/p/sevenzip/code/ci]/master/tree/README.md, Girocco: [https://repo.or.cz/?p=girocco.git;a=blob;hb=]master;f=README.md or Azure DevOps: [https://dev.azure.com/org/project/_git/repo?version=GC]23f84b91&path=/README.md)"#,
     default_required: true,
 };
+const VAR_REPO_VERSIONED_FILE_URL_BASE: Variable = Variable {
+    key: KEY_REPO_VERSIONED_FILE_URL_BASE,
+    description: "The repo file prefix URL, pinned to the tag (if any) or else the commit being built. Add the file path. The part in []: [https://github.com/hoijui/nim-ci/blob/v0.1.0]/.github/workflows/docker.yml",
+    default_required: false,
+};
 const VAR_REPO_WEB_URL: Variable = Variable {
     key: KEY_REPO_WEB_URL,
     description: "The repo web UI URL, for example: https://gitlab.com/OSEGermany/OHS-3105",
     default_required: true,
 };
+const VAR_REPO_WIKI_URL: Variable = Variable {
+    key: KEY_REPO_WIKI_URL,
+    description: "The repo wiki URL, for example: https://github.com/hoijui/projvar/wiki",
+    default_required: false,
+};
+const VAR_SOURCE_DATE_EPOCH: Variable = Variable {
+    key: KEY_SOURCE_DATE_EPOCH,
+    description: r#"The build date as seconds since the Unix epoch, for example: "1640995199"; suitable to set SOURCE_DATE_EPOCH (see https://reproducible-builds.org/specs/source-date-epoch/)"#,
+    default_required: false,
+};
 const VAR_VERSION: Variable = Variable {
     key: KEY_VERSION,
     description: r#"The project version, for example: "1.10.3", "0.2.0-1-ga5387ac-dirty""#,
     default_required: true,
 };
+const VAR_VERSION_BUMP: Variable = Variable {
+    key: KEY_VERSION_BUMP,
+    description: r#"Which part of the version the next release has to increase, according to the Conventional Commits since the last release: "major", "minor", "patch" or "none""#,
+    default_required: false,
+};
 const VAR_VERSION_DATE: Variable = Variable {
     key: KEY_VERSION_DATE,
     description: r#"Date this version was committed to source control, for example: "2021-12-31 23:59:59" (see --date-format)"#,
     default_required: true,
 };
+const VAR_VERSION_DEBIAN: Variable = Variable {
+    key: KEY_VERSION_DEBIAN,
+    description: r#"The version in a format suitable for Debian packages, for example: "1.2.3~rc.1", "1.2.3+4.gabcdef0.dirty""#,
+    default_required: false,
+};
+const VAR_VERSION_MAJOR: Variable = Variable {
+    key: KEY_VERSION_MAJOR,
+    description: r#"The major part of the semantic version, for example: "1" for "1.10.3""#,
+    default_required: false,
+};
+const VAR_VERSION_MINOR: Variable = Variable {
+    key: KEY_VERSION_MINOR,
+    description: r#"The minor part of the semantic version, for example: "10" for "1.10.3""#,
+    default_required: false,
+};
+const VAR_VERSION_NEXT: Variable = Variable {
+    key: KEY_VERSION_NEXT,
+    description: r#"The version of the next release, according to the Conventional Commits since the last release, for example: "1.11.0""#,
+    default_required: false,
+};
+const VAR_VERSION_PATCH: Variable = Variable {
+    key: KEY_VERSION_PATCH,
+    description: r#"The patch part of the semantic version, for example: "3" for "1.10.3""#,
+    default_required: false,
+};
+const VAR_VERSION_PEP440: Variable = Variable {
+    key: KEY_VERSION_PEP440,
+    description: r#"The version in a format suitable for Python packages (PEP 440), for example: "1.2.3rc1", "1.2.3.post4+gabcdef0.dirty""#,
+    default_required: false,
+};
+const VAR_VERSION_PRERELEASE: Variable = Variable {
+    key: KEY_VERSION_PRERELEASE,
+    description: r#"The pre-release part of the semantic version, for example: "rc.1" for "1.10.3-rc.1""#,
+    default_required: false,
+};
 
 /// Returns a reference to the variable settings associated with the given key.
 #[must_use]
@@ -462,6 +797,7 @@ const VAR_VERSION_DATE: Variable = Variable {
 pub fn get(key: Key) -> &'static Variable {
     #[remain::sorted]
     match key {
+        Key::AuthorCount => &VAR_AUTHOR_COUNT,
         Key::BuildArch => &VAR_BUILD_ARCH,
         Key::BuildBranch => &VAR_BUILD_BRANCH,
         Key::BuildDate => &VAR_BUILD_DATE,
@@ -471,26 +807,63 @@ pub fn get(key: Key) -> &'static Variable {
         Key::BuildOsFamily => &VAR_BUILD_OS_FAMILY,
         Key::BuildTag => &VAR_BUILD_TAG,
         Key::Ci => &VAR_CI,
+        Key::CommitCount => &VAR_COMMIT_COUNT,
+        Key::CommitSha => &VAR_COMMIT_SHA,
+        Key::CommitShaShort => &VAR_COMMIT_SHA_SHORT,
+        Key::CommitsSinceTag => &VAR_COMMITS_SINCE_TAG,
+        Key::ContainerImageName => &VAR_CONTAINER_IMAGE_NAME,
+        Key::ContainerImageTag => &VAR_CONTAINER_IMAGE_TAG,
+        Key::Custom(id) => &custom_keys::get(id).variable,
+        Key::Dirty => &VAR_DIRTY,
+        Key::FirstCommitDate => &VAR_FIRST_COMMIT_DATE,
+        Key::IsRelease => &VAR_IS_RELEASE,
         Key::License => &VAR_LICENSE,
         Key::Licenses => &VAR_LICENSES,
         Key::Name => &VAR_NAME,
         Key::NameMachineReadable => &VAR_NAME_MACHINE_READABLE,
+        Key::PreviousTag => &VAR_PREVIOUS_TAG,
         Key::RepoCloneUrl => &VAR_REPO_CLONE_URL,
         Key::RepoCloneUrlSsh => &VAR_REPO_CLONE_URL_SSH,
         Key::RepoCommitPrefixUrl => &VAR_REPO_COMMIT_PREFIX_URL,
+        Key::RepoCommitUrl => &VAR_REPO_COMMIT_URL,
+        Key::RepoCompareUrl => &VAR_REPO_COMPARE_URL,
         Key::RepoIssuesUrl => &VAR_REPO_ISSUES_URL,
+        Key::RepoNamespace => &VAR_REPO_NAMESPACE,
+        Key::RepoNewMergeRequestUrl => &VAR_REPO_NEW_MERGE_REQUEST_URL,
+        Key::RepoOwner => &VAR_REPO_OWNER,
+        Key::RepoPipelinesUrl => &VAR_REPO_PIPELINES_URL,
+        Key::RepoRawUrlBase => &VAR_REPO_RAW_URL_BASE,
         Key::RepoRawVersionedPrefixUrl => &VAR_REPO_RAW_VERSIONED_PREFIX_URL,
+        Key::RepoReleasesUrl => &VAR_REPO_RELEASES_URL,
+        Key::RepoSlug => &VAR_REPO_SLUG,
+        Key::RepoTagUrl => &VAR_REPO_TAG_URL,
+        Key::RepoTreeUrl => &VAR_REPO_TREE_URL,
         Key::RepoVersionedDirPrefixUrl => &VAR_REPO_VERSIONED_DIR_PREFIX_URL,
         Key::RepoVersionedFilePrefixUrl => &VAR_REPO_VERSIONED_FILE_PREFIX_URL,
+        Key::RepoVersionedFileUrlBase => &VAR_REPO_VERSIONED_FILE_URL_BASE,
         Key::RepoWebUrl => &VAR_REPO_WEB_URL,
+        Key::RepoWikiUrl => &VAR_REPO_WIKI_URL,
+        Key::SourceDateEpoch => &VAR_SOURCE_DATE_EPOCH,
         Key::Version => &VAR_VERSION,
+        Key::VersionBump => &VAR_VERSION_BUMP,
         Key::VersionDate => &VAR_VERSION_DATE,
+        Key::VersionDebian => &VAR_VERSION_DEBIAN,
+        Key::VersionMajor => &VAR_VERSION_MAJOR,
+        Key::VersionMinor => &VAR_VERSION_MINOR,
+        Key::VersionNext => &VAR_VERSION_NEXT,
+        Key::VersionPatch => &VAR_VERSION_PATCH,
+        Key::VersionPep440 => &VAR_VERSION_PEP440,
+        Key::VersionPrerelease => &VAR_VERSION_PRERELEASE,
     }
 }
 
-fn create_default_keys() -> HashSet<Key> {
+/// The keys whose values are required by default,
+/// including the custom ones marked as such.
+#[must_use]
+// pub fn default_keys() -> EnumSet<Key> {
+pub fn default_keys() -> HashSet<Key> {
     let mut def_keys = HashSet::<Key>::new();
-    for key in Key::iter() {
+    for key in Key::iter_all() {
         let variable = get(key);
         if variable.default_required {
             def_keys.insert(key);
@@ -499,17 +872,6 @@ fn create_default_keys() -> HashSet<Key> {
     def_keys
 }
 
-lazy_static! {
-    // static ref DEFAULT_KEYS: EnumSet<Key> = create_default_keys();
-    static ref DEFAULT_KEYS: HashSet<Key> = create_default_keys();
-}
-
-#[must_use]
-// pub fn default_keys() -> EnumSet<Key> {
-pub fn default_keys() -> &'static HashSet<Key> {
-    &DEFAULT_KEYS
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,521 @@
+// SPDX-FileCopyrightText: 2022 Robin Vobruba <hoijui.quaero@gmail.com>
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Version schemes other than semantic versioning,
+//! conversions of versions as produced by "git describe"
+//! (for example "1.2.3-4-gabcdef0-dirty")
+//! into the formats required by some packaging ecosystems,
+//! and the computation of the next version
+//! from [Conventional Commits](https://www.conventionalcommits.org).
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use strum_macros::{EnumString, EnumVariantNames, IntoStaticStr};
+
+use crate::normalize;
+
+/// The scheme the versions of a project follow,
+/// which determines how the version is validated.
+#[derive(Debug, EnumString, EnumVariantNames, IntoStaticStr, PartialEq, Eq, Clone, Copy)]
+#[strum(serialize_all = "lowercase")]
+pub enum Scheme {
+    /// Semantic versioning, for example "1.2.3-rc.1";
+    /// see <https://semver.org>
+    SemVer,
+    /// Calendar versioning, for example "2024.03" or "2024.03.1";
+    /// see <https://calver.org>
+    CalVer,
+    /// Python package versions, for example "1.2.3.post4+g1234abc";
+    /// see <https://peps.python.org/pep-0440/>
+    Pep440,
+    /// Debian package versions, for example "1.2.3~rc1";
+    /// see <https://www.debian.org/doc/debian-policy/ch-controlfields.html#version>
+    Debian,
+}
+
+impl Default for Scheme {
+    fn default() -> Self {
+        Self::SemVer
+    }
+}
+
+/// Which part of a semantic version to increase for the next release.
+#[derive(
+    Debug, EnumString, EnumVariantNames, IntoStaticStr, PartialEq, Eq, PartialOrd, Ord, Clone, Copy,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum Bump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+lazy_static! {
+    static ref R_DESCRIBE: Regex = Regex::new(
+        r"^(?P<base>.+?)(-(?P<distance>[0-9]+)-g(?P<sha>[0-9a-f]{4,64}))?(?P<flags>(-dirty|-broken)*)$"
+    )
+    .unwrap();
+    // "YYYY.MM", "YY.MM" or "YYYY.MM.MICRO", optionally with a modifier like "-rc1"
+    static ref R_CALVER: Regex = Regex::new(
+        r"^([0-9]{4}|[0-9]{2})\.(0?[1-9]|1[0-2])(\.(0|[1-9][0-9]*))?(-[0-9a-zA-Z.]+)?$"
+    )
+    .unwrap();
+    // The canonical form from PEP 440, appendix B,
+    // with an optional local version label
+    static ref R_PEP440: Regex = Regex::new(
+        r"^([1-9][0-9]*!)?(0|[1-9][0-9]*)(\.(0|[1-9][0-9]*))*((a|b|rc)(0|[1-9][0-9]*))?(\.post(0|[1-9][0-9]*))?(\.dev(0|[1-9][0-9]*))?(\+[a-z0-9]+(\.[a-z0-9]+)*)?$"
+    )
+    .unwrap();
+    static ref R_DEBIAN: Regex = Regex::new(
+        r"^([0-9]+:)?[0-9][0-9a-zA-Z.+~]*(-[0-9a-zA-Z.+~]+)?$"
+    )
+    .unwrap();
+    // The header of a Conventional Commit, for example "feat(parser)!: Add X"
+    static ref R_CONVENTIONAL: Regex = Regex::new(
+        r"^(?P<type>[a-zA-Z]+)(\((?P<scope>[^()\n]*)\))?(?P<breaking>!)?:[ \t]+(?P<description>[^\n]*)"
+    )
+    .unwrap();
+    static ref R_BREAKING_FOOTER: Regex = Regex::new(r"(?m)^BREAKING[ -]CHANGE:\s").unwrap();
+    static ref R_PRE_RELEASE: Regex = Regex::new(
+        r"^(?P<kind>alpha|a|beta|b|rc|c|pre|preview)[.-]?(?P<number>[0-9]+)?$"
+    )
+    .unwrap();
+}
+
+impl Scheme {
+    /// Checks whether `version` is a valid version in this scheme.
+    #[must_use]
+    pub fn is_valid(self, version: &str) -> bool {
+        match self {
+            Self::SemVer => semver::Version::parse(version).is_ok(),
+            Self::CalVer => R_CALVER.is_match(version),
+            Self::Pep440 => R_PEP440.is_match(version),
+            Self::Debian => R_DEBIAN.is_match(version),
+        }
+    }
+}
+
+/// The parts of a commit message
+/// that follows the Conventional Commits specification.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Conventional<'m> {
+    /// The type in lower-case, for example "feat" or "fix"
+    pub kind: String,
+    pub scope: Option<&'m str>,
+    /// Whether the header is marked with a '!',
+    /// or there is a "BREAKING CHANGE" footer
+    pub breaking: bool,
+    /// The rest of the subject line
+    pub description: &'m str,
+}
+
+impl<'m> Conventional<'m> {
+    /// Parses a commit message,
+    /// returning `None` if it does not follow the specification.
+    #[must_use]
+    pub fn parse(message: &'m str) -> Option<Self> {
+        let caps = R_CONVENTIONAL.captures(message)?;
+        Some(Self {
+            kind: caps["type"].to_lowercase(),
+            scope: caps
+                .name("scope")
+                .map(|scope| scope.as_str())
+                .filter(|scope| !scope.is_empty()),
+            breaking: caps.name("breaking").is_some() || R_BREAKING_FOOTER.is_match(message),
+            description: caps.name("description")?.as_str().trim_end(),
+        })
+    }
+}
+
+/// Extracts the version from the name of a release tag,
+/// using the capture group named "version" of `release_tag_pattern`,
+/// if there is one.
+/// Returns `None` if the tag does not denote a release.
+#[must_use]
+pub fn release_version(release_tag_pattern: Option<&Regex>, tag: &str) -> Option<semver::Version> {
+    let version = match release_tag_pattern {
+        Some(pattern) => {
+            let caps = pattern.captures(tag)?;
+            caps.name("version").map_or(tag, |version| version.as_str())
+        }
+        None => tag,
+    };
+    semver::Version::parse(normalize::strip_version_prefix(version)).ok()
+}
+
+impl Bump {
+    /// Returns the bump a commit message asks for,
+    /// according to the Conventional Commits specification:
+    /// breaking changes ask for a major, features for a minor
+    /// and fixes for a patch version bump.
+    /// Messages of any other type or format ask for none.
+    #[must_use]
+    pub fn from_commit_message(message: &str) -> Self {
+        match Conventional::parse(message) {
+            None => Self::None,
+            Some(conventional) if conventional.breaking => Self::Major,
+            Some(conventional) => match conventional.kind.as_str() {
+                "feat" => Self::Minor,
+                "fix" => Self::Patch,
+                _ => Self::None,
+            },
+        }
+    }
+
+    /// Returns the bump to actually apply to `version`.
+    /// Before 1.0.0, anything may change at any time,
+    /// so breaking changes only ask for a minor bump.
+    #[must_use]
+    pub fn effective(self, version: &semver::Version) -> Self {
+        if self == Self::Major && version.major == 0 {
+            Self::Minor
+        } else {
+            self
+        }
+    }
+
+    /// Returns the version after applying this bump to `version`.
+    /// No bump leaves the version as it is, even a pre-release.
+    /// A pre-release (like "1.0.0-rc.1") is followed by its release ("1.0.0"),
+    /// no matter the bump otherwise.
+    #[must_use]
+    pub fn apply(self, version: &semver::Version) -> semver::Version {
+        let mut next = semver::Version::new(version.major, version.minor, version.patch);
+        if self == Self::None {
+            return version.clone();
+        }
+        if !version.pre.is_empty() {
+            return next;
+        }
+        match self {
+            Self::None => (),
+            Self::Patch => next.patch += 1,
+            Self::Minor => {
+                next.minor += 1;
+                next.patch = 0;
+            }
+            Self::Major => {
+                next.major += 1;
+                next.minor = 0;
+                next.patch = 0;
+            }
+        }
+        next
+    }
+}
+
+/// Computes the bump and the next version
+/// from the last released version (if any)
+/// and the messages of the commits since.
+#[must_use]
+pub fn next(
+    last_release: Option<&semver::Version>,
+    messages: &[String],
+) -> (Bump, semver::Version) {
+    let initial = semver::Version::new(0, 0, 0);
+    let last_release = last_release.unwrap_or(&initial);
+    let bump = messages
+        .iter()
+        .map(|message| Bump::from_commit_message(message))
+        .max()
+        .unwrap_or(Bump::None)
+        .effective(last_release);
+    (bump, bump.apply(last_release))
+}
+
+/// A version as produced by "git describe",
+/// for example "v1.2.3-4-gabcdef0-dirty".
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Describe<'v> {
+    /// The tag, without "v" prefix, for example "1.2.3"
+    pub base: &'v str,
+    /// The number of commits since the tag, for example 4
+    pub distance: Option<u32>,
+    /// The abbreviated SHA of the commit, for example "abcdef0"
+    pub sha: Option<&'v str>,
+    /// Whether there are uncommitted changes
+    pub dirty: bool,
+}
+
+impl<'v> Describe<'v> {
+    /// Splits a "git describe" version into its parts.
+    #[must_use]
+    pub fn parse(version: &'v str) -> Option<Self> {
+        let caps = R_DESCRIBE.captures(version)?;
+        Some(Self {
+            base: normalize::strip_version_prefix(caps.name("base")?.as_str()),
+            distance: caps
+                .name("distance")
+                .and_then(|distance| distance.as_str().parse().ok()),
+            sha: caps.name("sha").map(|sha| sha.as_str()),
+            dirty: caps["flags"].contains("-dirty"),
+        })
+    }
+
+    /// Whether this denotes exactly the tagged commit,
+    /// without uncommitted changes.
+    #[must_use]
+    pub fn is_exact(&self) -> bool {
+        self.distance.unwrap_or(0) == 0 && !self.dirty
+    }
+
+    /// Collects the parts after the tagged version,
+    /// for example ["4", "gabcdef0", "dirty"].
+    fn suffix_parts(&self, with_distance: bool) -> Vec<String> {
+        let mut parts = vec![];
+        match self.distance {
+            Some(distance) if distance > 0 => {
+                if with_distance {
+                    parts.push(distance.to_string());
+                }
+                if let Some(sha) = self.sha {
+                    parts.push(format!("g{}", sha));
+                }
+            }
+            _ => (),
+        }
+        if self.dirty {
+            parts.push("dirty".to_owned());
+        }
+        parts
+    }
+}
+
+/// Appends parts separated by '.' after a '+',
+/// or after the '.' if there already is a '+'.
+fn append_local(version: &mut String, parts: &[String]) {
+    if parts.is_empty() {
+        return;
+    }
+    version.push(if version.contains('+') { '.' } else { '+' });
+    version.push_str(&parts.join("."));
+}
+
+/// Normalizes a purely numeric version like "2024.03" to "2024.3".
+fn numeric_release(version: &str) -> Option<String> {
+    Some(
+        version
+            .split('.')
+            .map(|part| part.parse::<u64>().ok().map(|number| number.to_string()))
+            .collect::<Option<Vec<_>>>()?
+            .join("."),
+    )
+}
+
+/// Converts a "git describe" version into a PEP 440 version,
+/// for example "1.2.3-4-gabcdef0-dirty" -> "1.2.3.post4+gabcdef0.dirty",
+/// "1.2.3-rc.1" -> "1.2.3rc1" or "2024.03" -> "2024.3".
+/// Returns `None` if the tag is neither a PEP 440, a purely numeric
+/// nor a semantic version,
+/// or the pre-release part can not be expressed in PEP 440.
+#[must_use]
+pub fn to_pep440(describe: &Describe) -> Option<String> {
+    let mut version = if Scheme::Pep440.is_valid(describe.base) {
+        describe.base.to_owned()
+    } else if let Some(release) = numeric_release(describe.base) {
+        release
+    } else {
+        let sem_vers = semver::Version::parse(describe.base).ok()?;
+        let mut version = format!("{}.{}.{}", sem_vers.major, sem_vers.minor, sem_vers.patch);
+        if !sem_vers.pre.is_empty() {
+            let caps = R_PRE_RELEASE.captures(sem_vers.pre.as_str())?;
+            let kind = match &caps["kind"] {
+                "alpha" | "a" => "a",
+                "beta" | "b" => "b",
+                _ => "rc",
+            };
+            let number = caps.name("number").map_or("0", |number| number.as_str());
+            version.push_str(kind);
+            version.push_str(&number.parse::<u64>().ok()?.to_string());
+        }
+        version
+    };
+    // A post-release, so commits after the tag sort after it
+    // (".devN" would sort before it)
+    if let Some(distance) = describe.distance.filter(|distance| *distance > 0) {
+        version.push_str(&format!(".post{}", distance));
+    }
+    append_local(&mut version, &describe.suffix_parts(false));
+    Some(version).filter(|version| Scheme::Pep440.is_valid(version))
+}
+
+/// Converts a "git describe" version into a Debian (upstream) version,
+/// for example "1.2.3-4-gabcdef0-dirty" -> "1.2.3+4.gabcdef0.dirty",
+/// or "1.2.3-rc.1" -> "1.2.3~rc.1",
+/// so pre-releases sort before the release.
+/// Returns `None` if the tag is neither a Debian nor a semantic version.
+#[must_use]
+pub fn to_debian(describe: &Describe) -> Option<String> {
+    let mut version = match semver::Version::parse(describe.base) {
+        Ok(sem_vers) => {
+            let mut version = format!("{}.{}.{}", sem_vers.major, sem_vers.minor, sem_vers.patch);
+            if !sem_vers.pre.is_empty() {
+                version.push('~');
+                version.push_str(sem_vers.pre.as_str());
+            }
+            if !sem_vers.build.is_empty() {
+                version.push('+');
+                version.push_str(sem_vers.build.as_str());
+            }
+            version
+        }
+        Err(_) if Scheme::Debian.is_valid(describe.base) => describe.base.to_owned(),
+        Err(_) => return None,
+    };
+    append_local(&mut version, &describe.suffix_parts(true));
+    Some(version).filter(|version| Scheme::Debian.is_valid(version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pep440(version: &str) -> Option<String> {
+        to_pep440(&Describe::parse(version).unwrap())
+    }
+
+    fn debian(version: &str) -> Option<String> {
+        to_debian(&Describe::parse(version).unwrap())
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            Describe::parse("v1.2.3-rc.1-4-gabcdef0-dirty"),
+            Some(Describe {
+                base: "1.2.3-rc.1",
+                distance: Some(4),
+                sha: Some("abcdef0"),
+                dirty: true,
+            })
+        );
+        assert!(Describe::parse("1.2.3").unwrap().is_exact());
+        assert!(!Describe::parse("1.2.3-dirty").unwrap().is_exact());
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(Scheme::CalVer.is_valid("2024.03"));
+        assert!(Scheme::CalVer.is_valid("24.3.1"));
+        assert!(!Scheme::CalVer.is_valid("2024.13"));
+        assert!(Scheme::Pep440.is_valid("1.2.3.dev4+g1234abc"));
+        assert!(!Scheme::Pep440.is_valid("1.2.3-rc.1"));
+        assert!(Scheme::Debian.is_valid("1.2.3~rc1"));
+        assert!(Scheme::Debian.is_valid("1:1.2.3-1"));
+        assert!(!Scheme::Debian.is_valid("v1.2.3"));
+    }
+
+    #[test]
+    fn test_to_pep440() {
+        assert_eq!(
+            pep440("1.2.3-4-gabcdef0-dirty").as_deref(),
+            Some("1.2.3.post4+gabcdef0.dirty")
+        );
+        assert_eq!(
+            pep440("1.2.3-4-gabcdef0").as_deref(),
+            Some("1.2.3.post4+gabcdef0")
+        );
+        assert_eq!(
+            pep440("1.2.3-rc.1-4-gabcdef0").as_deref(),
+            Some("1.2.3rc1.post4+gabcdef0")
+        );
+        assert_eq!(pep440("1.2.3-rc.1").as_deref(), Some("1.2.3rc1"));
+        assert_eq!(pep440("1.2.3-beta").as_deref(), Some("1.2.3b0"));
+        assert_eq!(pep440("2024.03").as_deref(), Some("2024.3"));
+        assert_eq!(pep440("1.2.3-foo.1"), None);
+        assert_eq!(pep440("gabcdef0"), None);
+    }
+
+    #[test]
+    fn test_bump() {
+        assert_eq!(Bump::from_commit_message("feat: Add X"), Bump::Minor);
+        assert_eq!(Bump::from_commit_message("fix(parser): Fix Y"), Bump::Patch);
+        assert_eq!(Bump::from_commit_message("refactor!: Drop Z"), Bump::Major);
+        assert_eq!(
+            Bump::from_commit_message("fix: Fix Y\n\nBREAKING CHANGE: Z is gone\n"),
+            Bump::Major
+        );
+        assert_eq!(Bump::from_commit_message("docs: Explain X"), Bump::None);
+        assert_eq!(Bump::from_commit_message("Add X"), Bump::None);
+    }
+
+    #[test]
+    fn test_conventional() {
+        assert_eq!(
+            Conventional::parse("Feat(parser)!: Add X \n\nBody\n"),
+            Some(Conventional {
+                kind: "feat".to_owned(),
+                scope: Some("parser"),
+                breaking: true,
+                description: "Add X",
+            })
+        );
+        assert_eq!(Conventional::parse("Merge branch 'x'"), None);
+        let pattern = Regex::new("^fw-(?P<version>.+)$").unwrap();
+        assert_eq!(
+            release_version(Some(&pattern), "fw-1.2.3"),
+            Some(semver::Version::new(1, 2, 3))
+        );
+        assert_eq!(release_version(Some(&pattern), "v1.2.3"), None);
+        assert_eq!(
+            release_version(None, "v1.2.3"),
+            Some(semver::Version::new(1, 2, 3))
+        );
+    }
+
+    #[test]
+    fn test_next() {
+        let messages = |messages: &[&str]| -> Vec<String> {
+            messages
+                .iter()
+                .map(|message| (*message).to_owned())
+                .collect()
+        };
+        let version = |version| semver::Version::parse(version).unwrap();
+        assert_eq!(
+            next(Some(&version("1.2.3")), &messages(&["fix: Y", "feat: X"])),
+            (Bump::Minor, version("1.3.0"))
+        );
+        assert_eq!(
+            next(Some(&version("1.2.3")), &messages(&["feat!: X"])),
+            (Bump::Major, version("2.0.0"))
+        );
+        assert_eq!(
+            next(Some(&version("0.2.3")), &messages(&["feat!: X"])),
+            (Bump::Minor, version("0.3.0"))
+        );
+        assert_eq!(
+            next(Some(&version("1.2.3")), &messages(&["docs: X"])),
+            (Bump::None, version("1.2.3"))
+        );
+        assert_eq!(
+            next(Some(&version("2.0.0-rc.1")), &messages(&["fix: Y"])),
+            (Bump::Patch, version("2.0.0"))
+        );
+        assert_eq!(
+            next(Some(&version("2.0.0-rc.1")), &messages(&["docs: X"])),
+            (Bump::None, version("2.0.0-rc.1"))
+        );
+        assert_eq!(
+            next(None, &messages(&["fix: Y"])),
+            (Bump::Patch, version("0.0.1"))
+        );
+    }
+
+    #[test]
+    fn test_to_debian() {
+        assert_eq!(
+            debian("1.2.3-4-gabcdef0-dirty").as_deref(),
+            Some("1.2.3+4.gabcdef0.dirty")
+        );
+        assert_eq!(debian("1.2.3-rc.1").as_deref(), Some("1.2.3~rc.1"));
+        assert_eq!(
+            debian("1.2.3-rc.1-4-gabcdef0").as_deref(),
+            Some("1.2.3~rc.1+4.gabcdef0")
+        );
+        assert_eq!(debian("2024.03").as_deref(), Some("2024.03"));
+        assert_eq!(debian("gabcdef0"), None);
+    }
+}